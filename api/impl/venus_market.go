@@ -5,6 +5,7 @@ import (
 	"encoding/json"
 	"fmt"
 	"io/ioutil"
+	"net/url"
 	"os"
 	"sort"
 	"time"
@@ -27,6 +28,7 @@ import (
 	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	"github.com/filecoin-project/go-state-types/abi"
 
+	"github.com/filecoin-project/venus-market/v2/api"
 	clients2 "github.com/filecoin-project/venus-market/v2/api/clients"
 	"github.com/filecoin-project/venus-market/v2/config"
 	"github.com/filecoin-project/venus-market/v2/minermgr"
@@ -35,7 +37,9 @@ import (
 	"github.com/filecoin-project/venus-market/v2/paychmgr"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	"github.com/filecoin-project/venus-market/v2/retrievalprovider"
+	"github.com/filecoin-project/venus-market/v2/rpc"
 	"github.com/filecoin-project/venus-market/v2/storageprovider"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 
 	"github.com/filecoin-project/go-state-types/builtin/v8/paych"
 	"github.com/filecoin-project/venus/pkg/constants"
@@ -46,6 +50,7 @@ import (
 )
 
 var _ marketapi.IMarket = (*MarketNodeImpl)(nil)
+var _ api.IMarketExt = (*MarketNodeImpl)(nil)
 var log = logging.Logger("market_api")
 
 type MarketNodeImpl struct {
@@ -57,6 +62,7 @@ type MarketNodeImpl struct {
 	Host              host.Host
 	StorageProvider   storageprovider.StorageProvider
 	RetrievalProvider retrievalprovider.IRetrievalProvider
+	HttpRetrieval     *retrievalprovider.HttpRetrievalGateway
 	DataTransfer      network.ProviderDataTransfer
 	DealPublisher     *storageprovider.DealPublisher
 	DealAssigner      storageprovider.DealAssiger
@@ -66,10 +72,14 @@ type MarketNodeImpl struct {
 	DAGStore                                    *dagstore.DAGStore
 	DAGStoreWrapper                             stores.DAGStoreWrapper
 	PieceStorageMgr                             *piecestorage.PieceStorageManager
+	PieceRepairWorker                           *piecestorage.RepairWorker
+	PieceScrubber                               *storageprovider.PieceScrubber
+	PieceGC                                     *storageprovider.PieceGC
 	MinerMgr                                    minermgr.IAddrMgr
 	PaychAPI                                    *paychmgr.PaychAPI
 	Repo                                        repo.Repo
 	Config                                      *config.MarketConfig
+	LocalJwtClient                              *rpc.JwtClient
 	ConsiderOnlineStorageDealsConfigFunc        config.ConsiderOnlineStorageDealsConfigFunc
 	SetConsiderOnlineStorageDealsConfigFunc     config.SetConsiderOnlineStorageDealsConfigFunc
 	ConsiderOnlineRetrievalDealsConfigFunc      config.ConsiderOnlineRetrievalDealsConfigFunc
@@ -856,12 +866,20 @@ func (m MarketNodeImpl) ImportV1Data(ctx context.Context, src string) error {
 
 //GetReadUrl deprecated api
 func (m MarketNodeImpl) GetReadUrl(ctx context.Context, s string) (string, error) {
-	panic("not support")
+	return resourceUrl(s), nil
 }
 
 //GetWriteUrl deprecated api
 func (m MarketNodeImpl) GetWriteUrl(ctx context.Context, s2 string) (string, error) {
-	panic("not support")
+	return resourceUrl(s2), nil
+}
+
+// resourceUrl return the relative url of piece resource served by PieceStorageServer,
+// read with GET and write with PUT
+func resourceUrl(resourceId string) string {
+	query := url.Values{}
+	query.Set("resource-id", resourceId)
+	return "/resource?" + query.Encode()
 }
 
 func (m MarketNodeImpl) AddFsPieceStorage(ctx context.Context, readonly bool, path string, name string) error {
@@ -900,6 +918,103 @@ func (m MarketNodeImpl) GetPieceStorages(ctx context.Context) types.PieceStorage
 	return m.PieceStorageMgr.ListStorageInfos()
 }
 
+func (m MarketNodeImpl) PieceStorageRepairStatus(ctx context.Context) (types2.PieceRepairStatus, error) {
+	return m.PieceRepairWorker.Status(), nil
+}
+
+func (m MarketNodeImpl) PieceStorageScrub(ctx context.Context) error {
+	return m.PieceScrubber.Start()
+}
+
+func (m MarketNodeImpl) PieceStorageScrubReport(ctx context.Context) (types2.PieceScrubReport, error) {
+	return m.PieceScrubber.Report(), nil
+}
+
+func (m MarketNodeImpl) PieceStorageGC(ctx context.Context, dryRun bool) (types2.PieceGCReport, error) {
+	return m.PieceGC.Run(ctx, dryRun)
+}
+
+func (m MarketNodeImpl) ConfigReload(ctx context.Context) (types2.ConfigReloadResult, error) {
+	cfgPath, err := m.Config.ConfigPath()
+	if err != nil {
+		return types2.ConfigReloadResult{}, err
+	}
+	newCfg := &config.MarketConfig{Home: m.Config.Home}
+	if err = config.LoadConfig(cfgPath, newCfg); err != nil {
+		return types2.ConfigReloadResult{}, fmt.Errorf("load config %s: %w", cfgPath, err)
+	}
+
+	result, err := m.PieceStorageMgr.Reload(&newCfg.PieceStorage)
+	if err != nil {
+		return result, err
+	}
+	// settings only read on start are not applied, the config in memory keeps telling what is running
+	cur, reloaded := &m.Config.PieceStorage, &newCfg.PieceStorage
+	result.RestartRequired = pieceStorageRestartRequired(cur, reloaded)
+	cur.Placement = reloaded.Placement
+	cur.Replication.Copies = reloaded.Replication.Copies
+	cur.Replication.MinS3Copies = reloaded.Replication.MinS3Copies
+	cur.Fs = reloaded.Fs
+	cur.S3 = reloaded.S3
+	return result, nil
+}
+
+// pieceStorageRestartRequired returns the changed piece storage settings which only take effect after restarting
+func pieceStorageRestartRequired(cur, reloaded *config.PieceStorage) []string {
+	var changed []string
+	if cur.ScrubInterval != reloaded.ScrubInterval {
+		changed = append(changed, "ScrubInterval")
+	}
+	if cur.GCInterval != reloaded.GCInterval {
+		changed = append(changed, "GCInterval")
+	}
+	if cur.GCGracePeriod != reloaded.GCGracePeriod {
+		changed = append(changed, "GCGracePeriod")
+	}
+	if cur.Replication.RepairInterval != reloaded.Replication.RepairInterval {
+		changed = append(changed, "Replication.RepairInterval")
+	}
+	if cur.RequireAuth != reloaded.RequireAuth {
+		changed = append(changed, "RequireAuth")
+	}
+	return changed
+}
+
+func (m MarketNodeImpl) PieceStorageSignUrl(ctx context.Context, miner address.Address, pieceCid cid.Cid, expire time.Duration) (string, error) {
+	if expire <= 0 {
+		return "", fmt.Errorf("expire duration must be positive")
+	}
+	deals, err := m.Repo.StorageDealRepo().GetDealsByPieceCidAndStatus(ctx, pieceCid, storageprovider.ReadyRetrievalDealStatus...)
+	if err != nil && !errors.Is(err, repo.ErrNotFound) {
+		return "", err
+	}
+	found := false
+	for _, deal := range deals {
+		if deal.Proposal.Provider == miner {
+			found = true
+			break
+		}
+	}
+	if !found {
+		return "", fmt.Errorf("miner %s has no deal of piece %s", miner, pieceCid)
+	}
+
+	signature, err := m.LocalJwtClient.SignPieceUrl(rpc.PieceUrlPayload{
+		PieceCID: pieceCid.String(),
+		Miner:    miner.String(),
+		Expire:   time.Now().Add(expire).Unix(),
+	})
+	if err != nil {
+		return "", err
+	}
+
+	return resourceUrl(pieceCid.String()) + "&signature=" + url.QueryEscape(signature), nil
+}
+
+func (m MarketNodeImpl) MarketListStorageDeals(ctx context.Context, params types2.DealQueryParams) (*types2.DealQueryResult, error) {
+	return m.Repo.StorageDealRepo().QueryDeals(ctx, &params)
+}
+
 func (m MarketNodeImpl) RemovePieceStorage(ctx context.Context, name string) error {
 	err := m.PieceStorageMgr.RemovePieceStorage(name)
 	if err != nil {
@@ -0,0 +1,66 @@
+package api
+
+import (
+	"context"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/ipfs/go-cid"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+// IMarketExt contains market apis which are served along with marketapi.IMarket
+type IMarketExt interface {
+	PieceStorageRepairStatus(ctx context.Context) (types2.PieceRepairStatus, error) //perm:read
+	PieceStorageScrub(ctx context.Context) error                                    //perm:admin
+	PieceStorageScrubReport(ctx context.Context) (types2.PieceScrubReport, error)   //perm:read
+	PieceStorageGC(ctx context.Context, dryRun bool) (types2.PieceGCReport, error)  //perm:admin
+	ConfigReload(ctx context.Context) (types2.ConfigReloadResult, error)            //perm:admin
+	// PieceStorageSignUrl return a relative url of the piece resource which can be downloaded without token before expired
+	PieceStorageSignUrl(ctx context.Context, miner address.Address, pieceCid cid.Cid, expire time.Duration) (string, error) //perm:sign
+	// MarketListStorageDeals return a page of storage deals matching the params, use NextCursor of the result to get the next page
+	MarketListStorageDeals(ctx context.Context, params types2.DealQueryParams) (*types2.DealQueryResult, error) //perm:read
+}
+
+type IMarketExtStruct struct {
+	Internal struct {
+		PieceStorageRepairStatus func(ctx context.Context) (types2.PieceRepairStatus, error)                                              `perm:"read"`
+		PieceStorageScrub        func(ctx context.Context) error                                                                          `perm:"admin"`
+		PieceStorageScrubReport  func(ctx context.Context) (types2.PieceScrubReport, error)                                               `perm:"read"`
+		PieceStorageGC           func(ctx context.Context, dryRun bool) (types2.PieceGCReport, error)                                     `perm:"admin"`
+		ConfigReload             func(ctx context.Context) (types2.ConfigReloadResult, error)                                             `perm:"admin"`
+		PieceStorageSignUrl      func(ctx context.Context, miner address.Address, pieceCid cid.Cid, expire time.Duration) (string, error) `perm:"sign"`
+		MarketListStorageDeals   func(ctx context.Context, params types2.DealQueryParams) (*types2.DealQueryResult, error)                `perm:"read"`
+	}
+}
+
+func (s *IMarketExtStruct) PieceStorageRepairStatus(p0 context.Context) (types2.PieceRepairStatus, error) {
+	return s.Internal.PieceStorageRepairStatus(p0)
+}
+
+func (s *IMarketExtStruct) PieceStorageScrub(p0 context.Context) error {
+	return s.Internal.PieceStorageScrub(p0)
+}
+
+func (s *IMarketExtStruct) PieceStorageScrubReport(p0 context.Context) (types2.PieceScrubReport, error) {
+	return s.Internal.PieceStorageScrubReport(p0)
+}
+
+func (s *IMarketExtStruct) PieceStorageGC(p0 context.Context, p1 bool) (types2.PieceGCReport, error) {
+	return s.Internal.PieceStorageGC(p0, p1)
+}
+
+func (s *IMarketExtStruct) ConfigReload(p0 context.Context) (types2.ConfigReloadResult, error) {
+	return s.Internal.ConfigReload(p0)
+}
+
+func (s *IMarketExtStruct) PieceStorageSignUrl(p0 context.Context, p1 address.Address, p2 cid.Cid, p3 time.Duration) (string, error) {
+	return s.Internal.PieceStorageSignUrl(p0, p1, p2, p3)
+}
+
+func (s *IMarketExtStruct) MarketListStorageDeals(p0 context.Context, p1 types2.DealQueryParams) (*types2.DealQueryResult, error) {
+	return s.Internal.MarketListStorageDeals(p0, p1)
+}
+
+var _ IMarketExt = (*IMarketExtStruct)(nil)
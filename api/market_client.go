@@ -0,0 +1,56 @@
+package api
+
+import (
+	"context"
+
+	"github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+// IMarketClientExt contains market client apis which are served along with clientapi.IMarketClient
+type IMarketClientExt interface {
+	// ClientAggregate packs the data of imports into one CAR, which is registered as a new import
+	ClientAggregate(ctx context.Context, ids []client.ImportID) (*types2.AggregateManifest, error) //perm:write
+	// ClientGetAggregateManifest return the manifest of an aggregated CAR by its root
+	ClientGetAggregateManifest(ctx context.Context, root cid.Cid) (*types2.AggregateManifest, error) //perm:read
+	// ClientStartDeals starts deals in batch with escrow reserved once for each wallet, the error of each deal is in its result
+	ClientStartDeals(ctx context.Context, params []client.StartDealParams) ([]types2.StartDealResult, error) //perm:admin
+	// ClientReplicateDeal proposes deals of the same data to distinct miners selected by their asks and past deals
+	ClientReplicateDeal(ctx context.Context, params types2.ReplicationParams) (*types2.ReplicationResult, error) //perm:admin
+	// ClientListDealRenewals lists the deals proposed by the renewal service to replace deals approaching their end
+	ClientListDealRenewals(ctx context.Context) ([]types2.DealRenewal, error) //perm:read
+}
+
+type IMarketClientExtStruct struct {
+	Internal struct {
+		ClientAggregate            func(ctx context.Context, ids []client.ImportID) (*types2.AggregateManifest, error)           `perm:"write"`
+		ClientGetAggregateManifest func(ctx context.Context, root cid.Cid) (*types2.AggregateManifest, error)                    `perm:"read"`
+		ClientStartDeals           func(ctx context.Context, params []client.StartDealParams) ([]types2.StartDealResult, error)  `perm:"admin"`
+		ClientReplicateDeal        func(ctx context.Context, params types2.ReplicationParams) (*types2.ReplicationResult, error) `perm:"admin"`
+		ClientListDealRenewals     func(ctx context.Context) ([]types2.DealRenewal, error)                                       `perm:"read"`
+	}
+}
+
+func (s *IMarketClientExtStruct) ClientAggregate(p0 context.Context, p1 []client.ImportID) (*types2.AggregateManifest, error) {
+	return s.Internal.ClientAggregate(p0, p1)
+}
+
+func (s *IMarketClientExtStruct) ClientGetAggregateManifest(p0 context.Context, p1 cid.Cid) (*types2.AggregateManifest, error) {
+	return s.Internal.ClientGetAggregateManifest(p0, p1)
+}
+
+func (s *IMarketClientExtStruct) ClientStartDeals(p0 context.Context, p1 []client.StartDealParams) ([]types2.StartDealResult, error) {
+	return s.Internal.ClientStartDeals(p0, p1)
+}
+
+func (s *IMarketClientExtStruct) ClientReplicateDeal(p0 context.Context, p1 types2.ReplicationParams) (*types2.ReplicationResult, error) {
+	return s.Internal.ClientReplicateDeal(p0, p1)
+}
+
+func (s *IMarketClientExtStruct) ClientListDealRenewals(p0 context.Context) ([]types2.DealRenewal, error) {
+	return s.Internal.ClientListDealRenewals(p0)
+}
+
+var _ IMarketClientExt = (*IMarketClientExtStruct)(nil)
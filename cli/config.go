@@ -0,0 +1,51 @@
+package cli
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/urfave/cli/v2"
+)
+
+var ConfigCmd = &cli.Command{
+	Name:  "config",
+	Usage: "Manage config of the running market daemon",
+	Subcommands: []*cli.Command{
+		configReloadCmd,
+	},
+}
+
+var configReloadCmd = &cli.Command{
+	Name:  "reload",
+	Usage: "reload piece storage config from config file without restarting daemon",
+	Action: func(cctx *cli.Context) error {
+		nodeApi, closer, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := ReqContext(cctx)
+
+		result, err := nodeApi.ConfigReload(ctx)
+		if err != nil {
+			return err
+		}
+
+		printNames := func(action string, names []string) {
+			if len(names) == 0 {
+				return
+			}
+			fmt.Printf("%s piece storages: %s\n", action, strings.Join(names, ", "))
+		}
+		printNames("added", result.AddedPieceStorages)
+		printNames("removed", result.RemovedPieceStorages)
+		printNames("updated", result.UpdatedPieceStorages)
+		if len(result.AddedPieceStorages)+len(result.RemovedPieceStorages)+len(result.UpdatedPieceStorages) == 0 {
+			fmt.Println("piece storages are not changed")
+		}
+		if len(result.RestartRequired) > 0 {
+			fmt.Printf("not applied until restarting daemon: %s\n", strings.Join(result.RestartRequired, ", "))
+		}
+		return nil
+	},
+}
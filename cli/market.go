@@ -17,6 +17,7 @@ import (
 
 	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/venus-market/v2/storageprovider"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 	"github.com/filecoin-project/venus/venus-shared/types/market"
 
 	tm "github.com/buger/goterm"
@@ -367,7 +368,7 @@ var dealsImportDataCmd = &cli.Command{
 
 var dealsListCmd = &cli.Command{
 	Name:  "list",
-	Usage: "List all deals for this miner",
+	Usage: "List deals for this miner",
 	Flags: []cli.Flag{
 		&cli.BoolFlag{
 			Name:    "verbose",
@@ -380,6 +381,62 @@ var dealsListCmd = &cli.Command{
 		&cli.StringFlag{
 			Name: "miner",
 		},
+		&cli.StringFlag{
+			Name:  "client",
+			Usage: "wallet address of deal client",
+		},
+		&cli.StringSliceFlag{
+			Name:  "state",
+			Usage: "deal state, eg. StorageDealActive, can be repeated",
+		},
+		&cli.StringSliceFlag{
+			Name:  "piece-state",
+			Usage: "Undefine | Assigned | Packing | Proving | Failed, can be repeated",
+		},
+		&cli.BoolFlag{
+			Name:  "verified",
+			Usage: "only list verified deals if true, or unverified deals if false",
+		},
+		&cli.StringFlag{
+			Name:  "created-after",
+			Usage: "list deals created at or after the time, in the format of RFC3339 or 2006-01-02",
+		},
+		&cli.StringFlag{
+			Name:  "created-before",
+			Usage: "list deals created before the time, in the format of RFC3339 or 2006-01-02",
+		},
+		&cli.StringFlag{
+			Name: "piece-cid",
+		},
+		&cli.StringFlag{
+			Name:  "payload-cid",
+			Usage: "root cid of the deal data",
+		},
+		&cli.Uint64Flag{
+			Name: "deal-id",
+		},
+		&cli.StringFlag{
+			Name:  "sort-by",
+			Usage: "creation_time | deal_id | piece_size",
+			Value: string(types2.DealSortByCreationTime),
+		},
+		&cli.BoolFlag{
+			Name:  "desc",
+			Usage: "sort in descending order",
+		},
+		&cli.StringFlag{
+			Name:  "cursor",
+			Usage: "cursor printed by the previous page",
+		},
+		&cli.IntFlag{
+			Name:  "limit",
+			Usage: "max number of deals in a page",
+			Value: types2.DefaultDealQueryLimit,
+		},
+		&cli.BoolFlag{
+			Name:  "all",
+			Usage: "list deals of all pages, always true when watching",
+		},
 	},
 	Action: func(cctx *cli.Context) error {
 		api, closer, err := NewMarketNode(cctx)
@@ -387,22 +444,40 @@ var dealsListCmd = &cli.Command{
 			return err
 		}
 		defer closer()
-		var maddr = address.Undef
-		if cctx.IsSet("miner") {
-			maddr, err = address.NewFromString(cctx.String("miner"))
-			if err != nil {
-				return fmt.Errorf("para `miner` is invalid: %w", err)
-			}
+
+		extAPI, extCloser, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
 		}
+		defer extCloser()
 
-		ctx := DaemonContext(cctx)
-		deals, err := api.MarketListIncompleteDeals(ctx, maddr)
+		params, err := dealQueryParamsFromFlags(cctx)
 		if err != nil {
 			return err
 		}
 
 		verbose := cctx.Bool("verbose")
 		watch := cctx.Bool("watch")
+		field, err := params.SortField()
+		if err != nil {
+			return err
+		}
+
+		ctx := DaemonContext(cctx)
+		var deals []market.MinerDeal
+		for {
+			res, err := extAPI.MarketListStorageDeals(ctx, *params)
+			if err != nil {
+				return err
+			}
+			for _, deal := range res.Deals {
+				deals = append(deals, *deal)
+			}
+			params.Cursor = res.NextCursor
+			if !(cctx.Bool("all") || watch) || len(params.Cursor) == 0 {
+				break
+			}
+		}
 
 		if watch {
 			updates, err := api.MarketGetDealUpdates(ctx)
@@ -414,7 +489,7 @@ var dealsListCmd = &cli.Command{
 				tm.Clear()
 				tm.MoveCursor(1, 1)
 
-				err = outputStorageDeals(tm.Output, deals, verbose)
+				err = outputStorageDeals(tm.Output, deals, field, params.Desc, verbose)
 				if err != nil {
 					return err
 				}
@@ -425,25 +500,110 @@ var dealsListCmd = &cli.Command{
 				case <-ctx.Done():
 					return nil
 				case updated := <-updates:
+					// a deal no longer matching the filters is removed from the list
+					matched := params.Match(&updated)
 					var found bool
 					for i, existing := range deals {
 						if existing.ProposalCid.Equals(updated.ProposalCid) {
-							deals[i] = updated
+							if matched {
+								deals[i] = updated
+							} else {
+								deals = append(deals[:i], deals[i+1:]...)
+							}
 							found = true
 							break
 						}
 					}
-					if !found {
+					if !found && matched {
 						deals = append(deals, updated)
 					}
 				}
 			}
 		}
 
-		return outputStorageDeals(os.Stdout, deals, verbose)
+		if err := outputStorageDeals(os.Stdout, deals, field, params.Desc, verbose); err != nil {
+			return err
+		}
+		if len(params.Cursor) > 0 {
+			fmt.Printf("\nmore deals, pass `--cursor %s` to list the next page\n", params.Cursor)
+		}
+		return nil
 	},
 }
 
+func dealQueryParamsFromFlags(cctx *cli.Context) (*types2.DealQueryParams, error) {
+	params := &types2.DealQueryParams{
+		SortBy: types2.DealSortField(cctx.String("sort-by")),
+		Desc:   cctx.Bool("desc"),
+		Cursor: cctx.String("cursor"),
+		Limit:  cctx.Int("limit"),
+	}
+	var err error
+	if cctx.IsSet("miner") {
+		if params.Miner, err = address.NewFromString(cctx.String("miner")); err != nil {
+			return nil, fmt.Errorf("para `miner` is invalid: %w", err)
+		}
+	}
+	if cctx.IsSet("client") {
+		if params.Client, err = address.NewFromString(cctx.String("client")); err != nil {
+			return nil, fmt.Errorf("para `client` is invalid: %w", err)
+		}
+	}
+	for _, s := range cctx.StringSlice("state") {
+		state, ok := storageprovider.StringToStorageState[s]
+		if !ok {
+			return nil, fmt.Errorf("para `state` is invalid: unknown state %s", s)
+		}
+		params.States = append(params.States, state)
+	}
+	for _, s := range cctx.StringSlice("piece-state") {
+		params.PieceStatuses = append(params.PieceStatuses, market.PieceStatus(s))
+	}
+	if cctx.IsSet("verified") {
+		verified := cctx.Bool("verified")
+		params.Verified = &verified
+	}
+	if cctx.IsSet("created-after") {
+		if params.CreatedAfter, err = parseTimeFlag(cctx.String("created-after")); err != nil {
+			return nil, fmt.Errorf("para `created-after` is invalid: %w", err)
+		}
+	}
+	if cctx.IsSet("created-before") {
+		if params.CreatedBefore, err = parseTimeFlag(cctx.String("created-before")); err != nil {
+			return nil, fmt.Errorf("para `created-before` is invalid: %w", err)
+		}
+	}
+	if cctx.IsSet("piece-cid") {
+		pieceCid, err := cid.Decode(cctx.String("piece-cid"))
+		if err != nil {
+			return nil, fmt.Errorf("para `piece-cid` is invalid: %w", err)
+		}
+		params.PieceCID = &pieceCid
+	}
+	if cctx.IsSet("payload-cid") {
+		payloadCid, err := cid.Decode(cctx.String("payload-cid"))
+		if err != nil {
+			return nil, fmt.Errorf("para `payload-cid` is invalid: %w", err)
+		}
+		params.PayloadCID = &payloadCid
+	}
+	if cctx.IsSet("deal-id") {
+		dealID := abi.DealID(cctx.Uint64("deal-id"))
+		params.DealID = &dealID
+	}
+	if _, err = params.SortField(); err != nil {
+		return nil, fmt.Errorf("para `sort-by` is invalid: %w", err)
+	}
+	return params, nil
+}
+
+func parseTimeFlag(s string) (time.Time, error) {
+	if t, err := time.Parse(time.RFC3339, s); err == nil {
+		return t, nil
+	}
+	return time.ParseInLocation("2006-01-02", s, time.Local)
+}
+
 var dealStateUsage = func() string {
 	const c, spliter = 5, " | "
 	size := len(storageprovider.StringToStorageState)
@@ -484,7 +644,7 @@ var updateStorageDealStateCmd = &cli.Command{
 		},
 		&cli.StringFlag{
 			Name:  "piece-state",
-			Usage: "Undefine | Assigned | Packing | Proving, empty means no change",
+			Usage: "Undefine | Assigned | Packing | Proving | Failed, empty means no change",
 		},
 		&cli.StringFlag{
 			Name:  "state",
@@ -530,9 +690,14 @@ var updateStorageDealStateCmd = &cli.Command{
 	},
 }
 
-func outputStorageDeals(out io.Writer, deals []market.MinerDeal, verbose bool) error {
-	sort.Slice(deals, func(i, j int) bool {
-		return deals[i].CreationTime.Time().Before(deals[j].CreationTime.Time())
+func outputStorageDeals(out io.Writer, deals []market.MinerDeal, field types2.DealSortField, desc bool, verbose bool) error {
+	// deals from the api are sorted already, watched updates are appended to the end
+	sort.SliceStable(deals, func(i, j int) bool {
+		vi, vj := types2.DealSortValue(field, &deals[i]), types2.DealSortValue(field, &deals[j])
+		if desc {
+			return vi > vj
+		}
+		return vi < vj
 	})
 
 	w := tabwriter.NewWriter(out, 2, 4, 2, ' ', 0)
@@ -3,8 +3,12 @@ package cli
 import (
 	"fmt"
 	"os"
+	"strings"
+	"time"
 
+	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/venus-market/v2/cli/tablewriter"
+	"github.com/ipfs/go-cid"
 	"github.com/urfave/cli/v2"
 )
 
@@ -17,6 +21,10 @@ var PieceStorageCmd = &cli.Command{
 		pieceStorageAddS3Cmd,
 		pieceStorageListCmd,
 		pieceStorageRemoveCmd,
+		pieceStorageRepairStatusCmd,
+		pieceStorageScrubCmd,
+		pieceStorageGCCmd,
+		pieceStorageSignUrlCmd,
 	},
 }
 
@@ -214,3 +222,226 @@ var pieceStorageRemoveCmd = &cli.Command{
 		return nodeApi.RemovePieceStorage(ctx, name)
 	},
 }
+
+var pieceStorageRepairStatusCmd = &cli.Command{
+	Name:  "repair-status",
+	Usage: "show status of the worker which repairs under-replicated pieces",
+	Action: func(cctx *cli.Context) error {
+		nodeApi, closer, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := ReqContext(cctx)
+
+		status, err := nodeApi.PieceStorageRepairStatus(ctx)
+		if err != nil {
+			return err
+		}
+		if !status.Enabled {
+			fmt.Println("piece repair worker is disabled")
+			return nil
+		}
+
+		fmt.Printf("Running:          %t\n", status.Running)
+		fmt.Printf("Last start:       %s\n", formatTime(status.LastStart))
+		fmt.Printf("Last end:         %s\n", formatTime(status.LastEnd))
+		fmt.Printf("Scanned:          %d\n", status.Scanned)
+		fmt.Printf("Under-replicated: %d\n", status.UnderReplicated)
+		fmt.Printf("Repaired:         %d\n", status.Repaired)
+		fmt.Printf("Failed:           %d\n", status.Failed)
+		if len(status.LastError) > 0 {
+			fmt.Printf("Last error:       %s\n", status.LastError)
+		}
+		return nil
+	},
+}
+
+var pieceStorageScrubCmd = &cli.Command{
+	Name:  "scrub",
+	Usage: "show pieces which are corrupt or missing found by the last scrub",
+	Flags: []cli.Flag{
+		&cli.BoolFlag{
+			Name:  "start",
+			Usage: "start a new scrub in background instead of showing the last result",
+		},
+	},
+	Action: func(cctx *cli.Context) error {
+		nodeApi, closer, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := ReqContext(cctx)
+
+		if cctx.Bool("start") {
+			if err := nodeApi.PieceStorageScrub(ctx); err != nil {
+				return err
+			}
+			fmt.Println("piece scrub started")
+			return nil
+		}
+
+		report, err := nodeApi.PieceStorageScrubReport(ctx)
+		if err != nil {
+			return err
+		}
+		if report.Start.IsZero() {
+			fmt.Println("no piece scrub has been run")
+			return nil
+		}
+
+		fmt.Printf("Running: %t\n", report.Running)
+		fmt.Printf("Start:   %s\n", formatTime(report.Start))
+		fmt.Printf("End:     %s\n", formatTime(report.End))
+		fmt.Printf("Checked: %d\n", report.Checked)
+		fmt.Printf("Corrupt: %d\n", len(report.Corrupt))
+		fmt.Printf("Missing: %d\n", len(report.Missing))
+		if len(report.Corrupt)+len(report.Missing) == 0 {
+			return nil
+		}
+		fmt.Println()
+
+		w := tablewriter.New(
+			tablewriter.Col("PieceCid"),
+			tablewriter.Col("Storage"),
+			tablewriter.Col("Actual"),
+			tablewriter.Col("Error"),
+		)
+		for _, res := range append(report.Corrupt, report.Missing...) {
+			actual := "-"
+			if res.Actual.Defined() {
+				actual = res.Actual.String()
+			}
+			storage := "-"
+			if len(res.Storage) > 0 {
+				storage = res.Storage
+			}
+			w.Write(map[string]interface{}{
+				"PieceCid": res.PieceCID.String(),
+				"Storage":  storage,
+				"Actual":   actual,
+				"Error":    res.Error,
+			})
+		}
+		return w.Flush(os.Stdout)
+	},
+}
+
+var pieceStorageGCCmd = &cli.Command{
+	Name:  "gc",
+	Usage: "list pieces whose deals have all ended for longer than the grace period, and delete them with --really-do-it",
+	Description: `The pieces are deleted from writable piece storages, their shards are destroyed in dagstore,
+and their blocks are removed from the cid index. Nothing is deleted without --really-do-it.`,
+	Flags: []cli.Flag{
+		&cli.BoolFlag{
+			Name:  "really-do-it",
+			Usage: "delete the pieces instead of only listing them",
+		},
+	},
+	Action: func(cctx *cli.Context) error {
+		nodeApi, closer, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := ReqContext(cctx)
+
+		report, err := nodeApi.PieceStorageGC(ctx, !cctx.Bool("really-do-it"))
+		if err != nil {
+			return err
+		}
+		if len(report.Pieces) == 0 {
+			fmt.Println("no piece to collect")
+			return nil
+		}
+
+		w := tablewriter.New(
+			tablewriter.Col("PieceCid"),
+			tablewriter.Col("Deals"),
+			tablewriter.Col("Storages"),
+			tablewriter.Col("Shard"),
+			tablewriter.Col("Error"),
+		)
+		failed := 0
+		for _, item := range report.Pieces {
+			storages := "-"
+			if len(item.Storages) > 0 {
+				storages = strings.Join(item.Storages, ",")
+			}
+			row := map[string]interface{}{
+				"PieceCid": item.PieceCID.String(),
+				"Deals":    len(item.Deals),
+				"Storages": storages,
+				"Shard":    item.Shard,
+			}
+			if len(item.Error) > 0 {
+				row["Error"] = item.Error
+				failed++
+			}
+			w.Write(row)
+		}
+		if err := w.Flush(os.Stdout); err != nil {
+			return err
+		}
+
+		if report.DryRun {
+			fmt.Printf("\n%d pieces would be deleted, run with --really-do-it to delete them\n", len(report.Pieces))
+		} else {
+			fmt.Printf("\n%d pieces deleted, %d failed\n", len(report.Pieces)-failed, failed)
+		}
+		return nil
+	},
+}
+
+var pieceStorageSignUrlCmd = &cli.Command{
+	Name:      "sign-url",
+	Usage:     "issue a short-lived url to download a piece of the miner without token",
+	ArgsUsage: "<piece cid>",
+	Flags: []cli.Flag{
+		&cli.StringFlag{
+			Name:     "miner",
+			Usage:    "miner which has a deal of the piece",
+			Required: true,
+		},
+		&cli.DurationFlag{
+			Name:  "expire",
+			Usage: "duration the url keeps valid",
+			Value: time.Hour,
+		},
+	},
+	Action: func(cctx *cli.Context) error {
+		if cctx.Args().Len() != 1 {
+			return fmt.Errorf("must specify piece cid")
+		}
+		pieceCid, err := cid.Decode(cctx.Args().First())
+		if err != nil {
+			return fmt.Errorf("parse piece cid: %w", err)
+		}
+		miner, err := address.NewFromString(cctx.String("miner"))
+		if err != nil {
+			return fmt.Errorf("parse miner address: %w", err)
+		}
+
+		nodeApi, closer, err := NewMarketExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := ReqContext(cctx)
+
+		url, err := nodeApi.PieceStorageSignUrl(ctx, miner, pieceCid, cctx.Duration("expire"))
+		if err != nil {
+			return err
+		}
+		fmt.Println(url)
+		return nil
+	},
+}
+
+func formatTime(t time.Time) string {
+	if t.IsZero() {
+		return "-"
+	}
+	return t.Format(time.RFC3339)
+}
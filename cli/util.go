@@ -5,6 +5,7 @@ import (
 	"fmt"
 	"io"
 	"io/ioutil"
+	"net/http"
 	"os"
 	"os/signal"
 	"path"
@@ -22,6 +23,7 @@ import (
 
 	datatransfer "github.com/filecoin-project/go-data-transfer"
 	"github.com/filecoin-project/go-jsonrpc"
+	"github.com/filecoin-project/venus-market/v2/api"
 	"github.com/filecoin-project/venus-market/v2/cli/tablewriter"
 	"github.com/filecoin-project/venus-market/v2/config"
 	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
@@ -95,27 +97,60 @@ func NewMarketNode(cctx *cli.Context) (marketapi.IMarket, jsonrpc.ClientCloser,
 	return marketapi.NewIMarketRPC(cctx.Context, addr, apiInfo.AuthHeader())
 }
 
+// NewMarketExtNode connect to the market apis which are not part of marketapi.IMarket
+func NewMarketExtNode(cctx *cli.Context) (api.IMarketExt, jsonrpc.ClientCloser, error) {
+	addr, header, err := repoDialArgs(cctx)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	var res api.IMarketExtStruct
+	closer, err := jsonrpc.NewMergeClient(cctx.Context, addr, API_NAMESPACE_VENUS_MARKET, []interface{}{&res.Internal}, header)
+	return &res, closer, err
+}
+
 func NewMarketClientNode(cctx *cli.Context) (clientapi.IMarketClient, jsonrpc.ClientCloser, error) {
-	homePath, err := homedir.Expand(cctx.String("repo"))
+	addr, header, err := repoDialArgs(cctx)
 	if err != nil {
 		return nil, nil, err
 	}
-	apiUrl, err := ioutil.ReadFile(path.Join(homePath, "api"))
+
+	return clientapi.NewIMarketClientRPC(cctx.Context, addr, header)
+}
+
+// NewMarketClientExtNode connect to the market client apis which are not part of clientapi.IMarketClient
+func NewMarketClientExtNode(cctx *cli.Context) (api.IMarketClientExt, jsonrpc.ClientCloser, error) {
+	addr, header, err := repoDialArgs(cctx)
 	if err != nil {
 		return nil, nil, err
 	}
 
+	var res api.IMarketClientExtStruct
+	closer, err := jsonrpc.NewMergeClient(cctx.Context, addr, API_NAMESPACE_MARKET_CLIENT, []interface{}{&res.Internal}, header)
+	return &res, closer, err
+}
+
+// repoDialArgs read the api address and token saved in repo by the running daemon
+func repoDialArgs(cctx *cli.Context) (string, http.Header, error) {
+	homePath, err := homedir.Expand(cctx.String("repo"))
+	if err != nil {
+		return "", nil, err
+	}
+	apiUrl, err := ioutil.ReadFile(path.Join(homePath, "api"))
+	if err != nil {
+		return "", nil, err
+	}
+
 	token, err := ioutil.ReadFile(path.Join(homePath, "token"))
 	if err != nil {
-		return nil, nil, err
+		return "", nil, err
 	}
 	apiInfo := apiinfo.NewAPIInfo(string(apiUrl), string(token))
 	addr, err := apiInfo.DialArgs("v0")
 	if err != nil {
-		return nil, nil, err
+		return "", nil, err
 	}
-
-	return clientapi.NewIMarketClientRPC(cctx.Context, addr, apiInfo.AuthHeader())
+	return addr, apiInfo.AuthHeader(), nil
 }
 
 func NewFullNode(cctx *cli.Context) (v1api.FullNode, jsonrpc.ClientCloser, error) {
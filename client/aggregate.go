@@ -0,0 +1,292 @@
+package client
+
+import (
+	"bufio"
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"sort"
+
+	"github.com/filecoin-project/go-fil-markets/stores"
+	"github.com/ipfs/go-blockservice"
+	"github.com/ipfs/go-cid"
+	offline "github.com/ipfs/go-ipfs-exchange-offline"
+	format "github.com/ipfs/go-ipld-format"
+	"github.com/ipfs/go-merkledag"
+	ft "github.com/ipfs/go-unixfs"
+	"github.com/ipld/go-car"
+	"github.com/ipld/go-car/util"
+
+	"github.com/filecoin-project/venus-market/v2/imports"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+)
+
+// ClientAggregate packs the data of many imports into one CAR whose root is a UnixFS directory linking to each of
+// them, so that small files can be stored in a single deal. The CAR is registered as a new import, and a manifest
+// recording where each file lies in the CAR is kept along with it.
+func (a *API) ClientAggregate(ctx context.Context, ids []types.ImportID) (res *types2.AggregateManifest, err error) {
+	if len(ids) == 0 {
+		return nil, fmt.Errorf("no imports to aggregate")
+	}
+
+	imgr := a.importManager()
+	manifest := &types2.AggregateManifest{}
+	dir := ft.EmptyDirNode()
+	cidBuilder, err := unixFSCidBuilder()
+	if err != nil {
+		return nil, err
+	}
+	dir.SetCidBuilder(cidBuilder)
+
+	var sources []*aggregateSource
+	defer func() {
+		for _, src := range sources {
+			_ = src.bs.Close()
+		}
+	}()
+
+	names := make(map[string]struct{}, len(ids))
+	for _, id := range ids {
+		info, err := imgr.Info(ctx, id)
+		if err != nil {
+			return nil, fmt.Errorf("failed to get import %d: %w", id, err)
+		}
+		root, err := cid.Parse(info.Labels[imports.LRootCid])
+		if err != nil {
+			return nil, fmt.Errorf("import %d has no valid root: %w", id, err)
+		}
+		bs, err := stores.ReadOnlyFilestore(info.Labels[imports.LCARPath])
+		if err != nil {
+			return nil, fmt.Errorf("failed to open car of import %d: %w", id, err)
+		}
+		src := &aggregateSource{
+			bs:  bs,
+			dag: merkledag.NewDAGService(blockservice.New(bs, offline.Exchange(bs))),
+		}
+		sources = append(sources, src)
+
+		nd, err := src.dag.Get(ctx, root)
+		if err != nil {
+			return nil, fmt.Errorf("failed to get root of import %d: %w", id, err)
+		}
+		size, err := nd.Size()
+		if err != nil {
+			return nil, err
+		}
+
+		filePath := info.Labels[imports.LFileName]
+		name := filepath.Base(filePath)
+		if len(filePath) == 0 {
+			name = root.String()
+		}
+		if _, ok := names[name]; ok {
+			name = fmt.Sprintf("%d-%s", id, name)
+		}
+		names[name] = struct{}{}
+
+		if err := dir.AddRawLink(name, &format.Link{Size: size, Cid: root}); err != nil {
+			return nil, fmt.Errorf("failed to link import %d: %w", id, err)
+		}
+		manifest.Files = append(manifest.Files, types2.AggregateFile{
+			ImportID: id,
+			Name:     name,
+			FilePath: filePath,
+			Root:     root,
+		})
+	}
+	manifest.Root = dir.Cid()
+	// dag-pb sorts links of the directory by name, files are written in the same order so that the car is
+	// identical to the one traversed from root, which graphsync transfers and the provider computes commP of
+	sort.Stable(aggregateFiles{manifest: manifest, sources: sources})
+
+	id, err := imgr.CreateImport(ctx)
+	if err != nil {
+		return nil, fmt.Errorf("failed to create import: %w", err)
+	}
+	carPath, err := imgr.AllocateCAR(ctx, id)
+	if err != nil {
+		return nil, fmt.Errorf("failed to create car path for import: %w", err)
+	}
+	manifestPath := carPath + ".manifest.json"
+
+	// remove the import if something went wrong.
+	defer func() {
+		if err != nil {
+			_ = os.Remove(carPath)
+			_ = os.Remove(manifestPath)
+			_ = imgr.Remove(ctx, id)
+		}
+	}()
+
+	if err = writeAggregateCar(ctx, carPath, dir, sources, manifest); err != nil {
+		return nil, fmt.Errorf("failed to write aggregated car: %w", err)
+	}
+
+	commP, err := a.ClientCalcCommP(ctx, carPath)
+	if err != nil {
+		return nil, err
+	}
+	manifest.ImportID = id
+	manifest.CARPath = carPath
+	manifest.PieceCID = commP.Root
+	manifest.PieceSize = commP.Size
+
+	data, err := json.MarshalIndent(manifest, "", "  ")
+	if err != nil {
+		return nil, err
+	}
+	if err = ioutil.WriteFile(manifestPath, data, 0644); err != nil {
+		return nil, fmt.Errorf("failed to save manifest: %w", err)
+	}
+
+	for key, value := range map[imports.LabelKey]imports.LabelValue{
+		imports.LSource:       "aggregate",
+		imports.LRootCid:      manifest.Root.String(),
+		imports.LManifestPath: manifestPath,
+	} {
+		if err = imgr.AddLabel(ctx, id, key, value); err != nil {
+			return nil, err
+		}
+	}
+
+	return manifest, nil
+}
+
+// ClientGetAggregateManifest returns the manifest of an aggregated CAR by its root
+func (a *API) ClientGetAggregateManifest(ctx context.Context, root cid.Cid) (*types2.AggregateManifest, error) {
+	imgr := a.importManager()
+	ids, err := imgr.List(ctx)
+	if err != nil {
+		return nil, fmt.Errorf("failed to fetch imports: %w", err)
+	}
+
+	for _, id := range ids {
+		info, err := imgr.Info(ctx, id)
+		if err != nil {
+			continue
+		}
+		manifestPath := info.Labels[imports.LManifestPath]
+		if len(manifestPath) == 0 || info.Labels[imports.LRootCid] != root.String() {
+			continue
+		}
+
+		data, err := ioutil.ReadFile(manifestPath)
+		if err != nil {
+			return nil, fmt.Errorf("failed to read manifest of import %d: %w", id, err)
+		}
+		var manifest types2.AggregateManifest
+		if err := json.Unmarshal(data, &manifest); err != nil {
+			return nil, fmt.Errorf("failed to parse manifest of import %d: %w", id, err)
+		}
+		return &manifest, nil
+	}
+
+	return nil, fmt.Errorf("no aggregated import found for root %s", root)
+}
+
+type aggregateSource struct {
+	bs  stores.ClosableBlockstore
+	dag format.DAGService
+}
+
+// aggregateFiles sorts files of manifest and their sources together by name
+type aggregateFiles struct {
+	manifest *types2.AggregateManifest
+	sources  []*aggregateSource
+}
+
+func (a aggregateFiles) Len() int {
+	return len(a.manifest.Files)
+}
+
+func (a aggregateFiles) Less(i, j int) bool {
+	return a.manifest.Files[i].Name < a.manifest.Files[j].Name
+}
+
+func (a aggregateFiles) Swap(i, j int) {
+	a.manifest.Files[i], a.manifest.Files[j] = a.manifest.Files[j], a.manifest.Files[i]
+	a.sources[i], a.sources[j] = a.sources[j], a.sources[i]
+}
+
+// countWriter counts bytes written through it
+type countWriter struct {
+	w io.Writer
+	n uint64
+}
+
+func (w *countWriter) Write(p []byte) (int, error) {
+	n, err := w.w.Write(p)
+	w.n += uint64(n)
+	return n, err
+}
+
+// writeAggregateCar writes a CARv1 which contains the directory node followed by the DAG of each file in the
+// order of manifest, which must be the order of links in the directory, and fills the offset and length of each file into manifest
+func writeAggregateCar(ctx context.Context, path string, dir format.Node, sources []*aggregateSource, manifest *types2.AggregateManifest) error {
+	f, err := os.OpenFile(path, os.O_WRONLY|os.O_TRUNC, 0644)
+	if err != nil {
+		return err
+	}
+	defer f.Close() //nolint:errcheck
+
+	buf := bufio.NewWriter(f)
+	w := &countWriter{w: buf}
+	if err := car.WriteHeader(&car.CarHeader{Roots: []cid.Cid{dir.Cid()}, Version: 1}, w); err != nil {
+		return err
+	}
+	if err := util.LdWrite(w, dir.Cid().Bytes(), dir.RawData()); err != nil {
+		return err
+	}
+
+	written := cid.NewSet()
+	var walk func(dag format.DAGService, c cid.Cid) error
+	walk = func(dag format.DAGService, c cid.Cid) error {
+		if !written.Visit(c) {
+			return nil
+		}
+		nd, err := dag.Get(ctx, c)
+		if err != nil {
+			return err
+		}
+		if err := util.LdWrite(w, c.Bytes(), nd.RawData()); err != nil {
+			return err
+		}
+		for _, link := range nd.Links() {
+			if err := walk(dag, link.Cid); err != nil {
+				return err
+			}
+		}
+		return nil
+	}
+
+	for i := range manifest.Files {
+		file := &manifest.Files[i]
+		if written.Has(file.Root) {
+			// the same data has been aggregated
+			for _, prev := range manifest.Files[:i] {
+				if prev.Root.Equals(file.Root) {
+					file.Offset, file.Length = prev.Offset, prev.Length
+					break
+				}
+			}
+			continue
+		}
+
+		file.Offset = w.n
+		if err := walk(sources[i].dag, file.Root); err != nil {
+			return fmt.Errorf("failed to write dag of import %d: %w", file.ImportID, err)
+		}
+		file.Length = w.n - file.Offset
+	}
+	manifest.CARSize = w.n
+
+	if err := buf.Flush(); err != nil {
+		return err
+	}
+	return f.Close()
+}
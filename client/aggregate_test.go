@@ -0,0 +1,135 @@
+package client
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"io"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"testing"
+
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-blockservice"
+	"github.com/ipfs/go-datastore"
+	dssync "github.com/ipfs/go-datastore/sync"
+	blockstore "github.com/ipfs/go-ipfs-blockstore"
+	offline "github.com/ipfs/go-ipfs-exchange-offline"
+	files "github.com/ipfs/go-ipfs-files"
+	"github.com/ipfs/go-merkledag"
+	unixfile "github.com/ipfs/go-unixfs/file"
+	"github.com/ipld/go-car"
+	"github.com/ipld/go-car/util"
+	selectorparse "github.com/ipld/go-ipld-prime/traversal/selector/parse"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/imports"
+	"github.com/filecoin-project/venus-market/v2/storageprovider"
+)
+
+func TestClientAggregate(t *testing.T) {
+	ctx := context.Background()
+	ds := dssync.MutexWrap(datastore.NewMapDatastore())
+	dir := t.TempDir()
+	im := imports.NewManager(ctx, ds, dir)
+
+	a := &API{
+		Imports:                   im,
+		StorageBlockstoreAccessor: storageprovider.NewImportsBlockstoreAccessor(im),
+	}
+
+	var ids []types.ImportID
+	contents := map[string][]byte{}
+	// not in the order of name, which is the order of links in the directory
+	for _, name := range []string{"payload2.txt", "payload.txt"} {
+		b, err := testdata.ReadFile("testdata/" + name)
+		require.NoError(t, err)
+		path := filepath.Join(dir, name)
+		require.NoError(t, ioutil.WriteFile(path, b, 0644))
+		contents[name] = b
+
+		res, err := a.ClientImport(ctx, types.FileRef{Path: path})
+		require.NoError(t, err)
+		ids = append(ids, res.ImportID)
+	}
+
+	manifest, err := a.ClientAggregate(ctx, ids)
+	require.NoError(t, err)
+	require.Len(t, manifest.Files, 2)
+	assert.Equal(t, "payload.txt", manifest.Files[0].Name)
+	assert.Equal(t, "payload2.txt", manifest.Files[1].Name)
+
+	carData, err := ioutil.ReadFile(manifest.CARPath)
+	require.NoError(t, err)
+	assert.EqualValues(t, len(carData), manifest.CARSize)
+
+	bs := blockstore.NewBlockstore(datastore.NewMapDatastore())
+	header, err := car.LoadCar(ctx, bs, bytes.NewReader(carData))
+	require.NoError(t, err)
+	require.Equal(t, manifest.Root, header.Roots[0])
+
+	// the car is the same as the one traversed from root, so is the commP
+	traversalPath := filepath.Join(dir, "traversal.car")
+	traversal, err := os.Create(traversalPath)
+	require.NoError(t, err)
+	sc := car.NewSelectiveCar(ctx, bs, []car.Dag{{Root: manifest.Root, Selector: selectorparse.CommonSelector_ExploreAllRecursively}})
+	require.NoError(t, sc.Write(traversal))
+	require.NoError(t, traversal.Close())
+	traversalData, err := ioutil.ReadFile(traversalPath)
+	require.NoError(t, err)
+	assert.Equal(t, traversalData, carData)
+
+	commP, err := a.ClientCalcCommP(ctx, traversalPath)
+	require.NoError(t, err)
+	assert.Equal(t, commP.Root, manifest.PieceCID)
+	assert.Equal(t, commP.Size, manifest.PieceSize)
+
+	// the offset of each file points to the first block of its dag
+	for _, file := range manifest.Files {
+		require.Greater(t, file.Length, uint64(0))
+		section := carData[file.Offset : file.Offset+file.Length]
+		c, _, err := util.ReadNode(bufio.NewReader(bytes.NewReader(section)))
+		require.NoError(t, err)
+		assert.Equal(t, file.Root, c)
+	}
+	last := manifest.Files[len(manifest.Files)-1]
+	assert.Equal(t, manifest.CARSize, last.Offset+last.Length)
+
+	// each file can be read from the car by its name under the root
+	dag := merkledag.NewDAGService(blockservice.New(bs, offline.Exchange(bs)))
+	rootNd, err := dag.Get(ctx, manifest.Root)
+	require.NoError(t, err)
+	for _, file := range manifest.Files {
+		lnk, _, err := rootNd.ResolveLink([]string{file.Name})
+		require.NoError(t, err)
+		assert.Equal(t, file.Root, lnk.Cid)
+
+		nd, err := dag.Get(ctx, lnk.Cid)
+		require.NoError(t, err)
+		f, err := unixfile.NewUnixfsFile(ctx, dag, nd)
+		require.NoError(t, err)
+		data, err := io.ReadAll(files.ToFile(f))
+		require.NoError(t, err)
+		assert.Equal(t, contents[file.Name], data)
+	}
+
+	// the aggregated car is an import, and its manifest can be found by root
+	list, err := a.ClientListImports(ctx)
+	require.NoError(t, err)
+	assert.Len(t, list, 3)
+
+	found, err := a.ClientGetAggregateManifest(ctx, manifest.Root)
+	require.NoError(t, err)
+	assert.Equal(t, manifest, found)
+
+	require.NoError(t, a.ClientRemoveImport(ctx, manifest.ImportID))
+	_, err = os.Stat(manifest.CARPath + ".manifest.json")
+	assert.True(t, os.IsNotExist(err))
+	_, err = a.ClientGetAggregateManifest(ctx, manifest.Root)
+	assert.Error(t, err)
+
+	_, err = a.ClientAggregate(ctx, nil)
+	assert.Error(t, err)
+}
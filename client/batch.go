@@ -0,0 +1,126 @@
+package client
+
+import (
+	"context"
+	"fmt"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus-auth/log"
+	"github.com/ipfs/go-cid"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+)
+
+// ClientStartDeals starts a batch of storage deals. The escrow needed by all the deals of a wallet is reserved
+// up front, so that the market balance is topped up by one message instead of one per deal. The release of the
+// reservation is queued before the deals are proposed, so it is taken over by the reservations the deals make for
+// themselves once the top up message is on chain. A deal which fails doesn't stop the others, its error is
+// reported in the result.
+func (a *API) ClientStartDeals(ctx context.Context, params []types.StartDealParams) ([]types2.StartDealResult, error) {
+	results := make([]types2.StartDealResult, len(params))
+	for i := range params {
+		p := &params[i]
+		results[i] = types2.StartDealResult{
+			Index:              i,
+			Miner:              p.Miner,
+			Wallet:             p.Wallet,
+			Escrow:             big.Zero(),
+			ClientCollateral:   big.Zero(),
+			ProviderCollateral: big.Zero(),
+		}
+		if p.Data == nil {
+			results[i].Error = "no data to store"
+			continue
+		}
+		results[i].Root = p.Data.Root
+
+		start, end, err := a.dealEpochs(ctx, p)
+		if err != nil {
+			results[i].Error = err.Error()
+			continue
+		}
+		// make the deal start at the epoch the escrow is calculated for
+		p.DealStartEpoch = start
+		results[i].StartEpoch = start
+		results[i].EndEpoch = end
+		// deals are always proposed without client collateral, the field is kept so the escrow adds up
+		results[i].Escrow = big.Add(dealEscrow(p.EpochPrice, start, end), results[i].ClientCollateral)
+	}
+
+	escrows := batchEscrow(results)
+	for wallet, amt := range escrows {
+		msgCid, err := a.FundMgr.Reserve(ctx, wallet, wallet, amt)
+		for i := range results {
+			if results[i].Wallet != wallet || len(results[i].Error) > 0 {
+				continue
+			}
+			if err != nil {
+				results[i].Error = fmt.Sprintf("reserve funds for batch: %s", err)
+			} else if msgCid != cid.Undef {
+				results[i].AddFundsCid = &msgCid
+			}
+		}
+		if err != nil {
+			delete(escrows, wallet)
+		}
+	}
+
+	// the release is queued before the deals reserve their own share, so they are covered by the released
+	// amount rather than topping up the balance again
+	for wallet, amt := range escrows {
+		errCh := a.FundMgr.ReleaseAsync(wallet, amt)
+		go func(wallet address.Address, amt abi.TokenAmount) {
+			if err := <-errCh; err != nil {
+				log.Errorf("failed to release %s reserved for deals of %s: %s", amt, wallet, err)
+			}
+		}(wallet, amt)
+	}
+
+	for i := range params {
+		if len(results[i].Error) > 0 {
+			continue
+		}
+		proposalCid, err := a.dealStarter(ctx, &params[i], false)
+		if err != nil {
+			results[i].Error = err.Error()
+			continue
+		}
+		results[i].ProposalCid = proposalCid
+
+		// the provider collateral is decided when proposing if it is not set in params, read it back from the proposal
+		if deal, err := a.SMDealClient.GetLocalDeal(ctx, *proposalCid); err == nil {
+			results[i].ProviderCollateral = deal.Proposal.ProviderCollateral
+		} else if !params[i].ProviderCollateral.Nil() {
+			results[i].ProviderCollateral = params[i].ProviderCollateral
+		}
+	}
+
+	return results, nil
+}
+
+// dealEscrow returns the storage fee of a deal which client locks in escrow
+func dealEscrow(price abi.TokenAmount, start, end abi.ChainEpoch) abi.TokenAmount {
+	if price.Nil() {
+		return big.Zero()
+	}
+	return big.Mul(price, big.NewInt(int64(end-start)))
+}
+
+// batchEscrow sums up the escrow of deals by wallet, deals failed already and wallets need nothing are skipped
+func batchEscrow(results []types2.StartDealResult) map[address.Address]abi.TokenAmount {
+	escrows := make(map[address.Address]abi.TokenAmount)
+	for _, res := range results {
+		if len(res.Error) > 0 || res.Escrow.IsZero() {
+			continue
+		}
+		if amt, ok := escrows[res.Wallet]; ok {
+			escrows[res.Wallet] = big.Add(amt, res.Escrow)
+		} else {
+			escrows[res.Wallet] = res.Escrow
+		}
+	}
+	return escrows
+}
@@ -0,0 +1,31 @@
+package client
+
+import (
+	"testing"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+func TestBatchEscrow(t *testing.T) {
+	w1, err := address.NewIDAddress(1000)
+	require.NoError(t, err)
+	w2, err := address.NewIDAddress(1001)
+	require.NoError(t, err)
+
+	assert.Equal(t, big.NewInt(300), dealEscrow(abi.NewTokenAmount(3), 100, 200))
+	assert.Equal(t, big.Zero(), dealEscrow(abi.TokenAmount{}, 100, 200))
+
+	escrows := batchEscrow([]types2.StartDealResult{
+		{Wallet: w1, Escrow: big.NewInt(100)},
+		{Wallet: w1, Escrow: big.NewInt(200)},
+		{Wallet: w1, Escrow: big.NewInt(400), Error: "failed"},
+		{Wallet: w2, Escrow: big.Zero()},
+	})
+	assert.Equal(t, map[address.Address]abi.TokenAmount{w1: big.NewInt(300)}, escrows)
+}
@@ -59,6 +59,7 @@ import (
 	textselector "github.com/ipld/go-ipld-selector-text-lite"
 
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/fundmgr"
 	"github.com/filecoin-project/venus-market/v2/imports"
 	"github.com/filecoin-project/venus-market/v2/retrievalprovider"
 	"github.com/filecoin-project/venus-market/v2/storageprovider"
@@ -95,6 +96,8 @@ type API struct {
 	DataTransfer marketNetwork.ClientDataTransfer
 	Host         host.Host
 	Cfg          *config.MarketClientConfig
+	FundMgr      *fundmgr.FundManager
+	Renewals     *RenewalStore
 }
 
 func calcDealExpiration(minDuration uint64, md *dline.Info, startEpoch abi.ChainEpoch) abi.ChainEpoch {
@@ -163,24 +166,13 @@ func (a *API) dealStarter(ctx context.Context, params *types.StartDealParams, is
 		return nil, fmt.Errorf("failed getting peer ID: %w", err)
 	}
 
-	md, err := a.Full.StateMinerProvingDeadline(ctx, params.Miner, vTypes.EmptyTSK)
-	if err != nil {
-		return nil, fmt.Errorf("failed getting miner's deadline info: %w", err)
-	}
-
 	if uint64(params.Data.PieceSize.Padded()) > uint64(mi.SectorSize) {
 		return nil, errors.New("data doesn't fit in a sector")
 	}
 
-	dealStart := params.DealStartEpoch
-	if dealStart <= 0 { // unset, or explicitly 'epoch undefined'
-		ts, err := a.Full.ChainHead(ctx)
-		if err != nil {
-			return nil, fmt.Errorf("failed getting chain height: %w", err)
-		}
-
-		blocksPerHour := 60 * 60 / int(constants.MainNetBlockDelaySecs)
-		dealStart = ts.Height() + abi.ChainEpoch(int(dealStartBufferHours)*blocksPerHour) // TODO: Get this from storage ask
+	dealStart, dealEnd, err := a.dealEpochs(ctx, params)
+	if err != nil {
+		return nil, err
 	}
 
 	networkVersion, err := a.Full.StateNetworkVersion(ctx, vTypes.EmptyTSK)
@@ -202,7 +194,7 @@ func (a *API) dealStarter(ctx context.Context, params *types.StartDealParams, is
 			Info:          &providerInfo,
 			Data:          params.Data,
 			StartEpoch:    dealStart,
-			EndEpoch:      calcDealExpiration(params.MinBlocksDuration, md, dealStart),
+			EndEpoch:      dealEnd,
 			Price:         params.EpochPrice,
 			Collateral:    params.ProviderCollateral,
 			Rt:            st,
@@ -233,7 +225,7 @@ func (a *API) dealStarter(ctx context.Context, params *types.StartDealParams, is
 		Provider:             params.Miner,
 		Label:                label,
 		StartEpoch:           dealStart,
-		EndEpoch:             calcDealExpiration(params.MinBlocksDuration, md, dealStart),
+		EndEpoch:             dealEnd,
 		StoragePricePerEpoch: big.Zero(),
 		ProviderCollateral:   params.ProviderCollateral,
 		ClientCollateral:     big.Zero(),
@@ -308,6 +300,27 @@ func (a *API) dealStarter(ctx context.Context, params *types.StartDealParams, is
 	return &resp.Response.Proposal, nil
 }
 
+// dealEpochs returns the start and end epoch of a deal, the deal is aligned to the proving period of the miner
+func (a *API) dealEpochs(ctx context.Context, params *types.StartDealParams) (abi.ChainEpoch, abi.ChainEpoch, error) {
+	md, err := a.Full.StateMinerProvingDeadline(ctx, params.Miner, vTypes.EmptyTSK)
+	if err != nil {
+		return 0, 0, fmt.Errorf("failed getting miner's deadline info: %w", err)
+	}
+
+	dealStart := params.DealStartEpoch
+	if dealStart <= 0 { // unset, or explicitly 'epoch undefined'
+		ts, err := a.Full.ChainHead(ctx)
+		if err != nil {
+			return 0, 0, fmt.Errorf("failed getting chain height: %w", err)
+		}
+
+		blocksPerHour := 60 * 60 / int(constants.MainNetBlockDelaySecs)
+		dealStart = ts.Height() + abi.ChainEpoch(int(dealStartBufferHours)*blocksPerHour) // TODO: Get this from storage ask
+	}
+
+	return dealStart, calcDealExpiration(params.MinBlocksDuration, md, dealStart), nil
+}
+
 func (a *API) ClientListDeals(ctx context.Context) ([]types.DealInfo, error) {
 	deals, err := a.SMDealClient.ListLocalDeals(ctx)
 	if err != nil {
@@ -581,6 +594,9 @@ func (a *API) ClientRemoveImport(ctx context.Context, id types.ImportID) error {
 	if path != "" && owner == imports.CAROwnerImportMgr {
 		_ = os.Remove(path)
 	}
+	if manifest := info.Labels[imports.LManifestPath]; manifest != "" {
+		_ = os.Remove(manifest)
+	}
 
 	return a.importManager().Remove(ctx, id)
 }
@@ -220,6 +220,8 @@ func RetrievalClientJournaler(j journal.Journal, evtType journal.EventType) func
 	}
 }
 
+var StartDealRenewerKey = builder.NextInvoke()
+
 var MarketClientOpts = builder.Options(
 	// Markets (common)
 	builder.Override(new(*discoveryimpl.Local), NewLocalDiscovery),
@@ -232,4 +234,7 @@ var MarketClientOpts = builder.Options(
 	builder.Override(new(retrievalmarket.BlockstoreAccessor), RetrievalBlockstoreAccessor),
 	builder.Override(new(retrievalmarket.RetrievalClient), RetrievalClient),
 	builder.Override(new(storagemarket.StorageClient), StorageClient),
+
+	builder.Override(new(*RenewalStore), NewRenewalStore),
+	builder.Override(StartDealRenewerKey, NewDealRenewer),
 )
@@ -0,0 +1,255 @@
+package client
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus-auth/log"
+	"github.com/filecoin-project/venus/pkg/constants"
+	vTypes "github.com/filecoin-project/venus/venus-shared/types"
+	"github.com/ipfs-force-community/venus-common-utils/metrics"
+	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-datastore"
+	"github.com/ipfs/go-datastore/query"
+	"go.uber.org/fx"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/badger"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+)
+
+// RenewalStore records the lineage of renewed deals, keyed by the proposal of the replacement
+type RenewalStore struct {
+	ds datastore.Batching
+}
+
+func NewRenewalStore(ds badger.ClientRenewalDS) *RenewalStore {
+	return &RenewalStore{ds: ds}
+}
+
+func (s *RenewalStore) Save(ctx context.Context, renewal *types2.DealRenewal) error {
+	data, err := json.Marshal(renewal)
+	if err != nil {
+		return err
+	}
+	return s.ds.Put(ctx, datastore.NewKey(renewal.Replacement.String()), data)
+}
+
+func (s *RenewalStore) List(ctx context.Context) ([]types2.DealRenewal, error) {
+	res, err := s.ds.Query(ctx, query.Query{})
+	if err != nil {
+		return nil, err
+	}
+	defer res.Close() //nolint:errcheck
+
+	var out []types2.DealRenewal
+	for r := range res.Next() {
+		if r.Error != nil {
+			return nil, r.Error
+		}
+		var renewal types2.DealRenewal
+		if err := json.Unmarshal(r.Value, &renewal); err != nil {
+			return nil, fmt.Errorf("failed to parse renewal %s: %w", r.Key, err)
+		}
+		out = append(out, renewal)
+	}
+	return out, nil
+}
+
+// ClientListDealRenewals lists the deals proposed to replace expiring deals
+func (a *API) ClientListDealRenewals(ctx context.Context) ([]types2.DealRenewal, error) {
+	return a.Renewals.List(ctx)
+}
+
+// DealRenewer proposes replacement deals for active deals ending within the configured window, with the miner
+// of the original deal or the fallback miners, so that the data stays stored after the original deals expire
+type DealRenewer struct {
+	api      *API
+	renewals *RenewalStore
+	cfg      config.Renewal
+}
+
+func NewDealRenewer(mctx metrics.MetricsCtx, lc fx.Lifecycle, a API, cfg *config.MarketClientConfig) *DealRenewer {
+	renewer := &DealRenewer{api: &a, renewals: a.Renewals, cfg: cfg.Renewal}
+	if !renewer.cfg.Enable {
+		return renewer
+	}
+
+	ctx := metrics.LifecycleCtx(mctx, lc)
+	lc.Append(fx.Hook{
+		OnStart: func(context.Context) error {
+			go renewer.Start(ctx)
+			return nil
+		},
+	})
+	return renewer
+}
+
+func (r *DealRenewer) Start(ctx context.Context) {
+	interval := time.Duration(r.cfg.Interval)
+	if interval <= 0 {
+		interval = time.Hour
+	}
+	r.renewDeals(ctx)
+	ticker := time.NewTicker(interval)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-ticker.C:
+			r.renewDeals(ctx)
+		case <-ctx.Done():
+			log.Warnf("exit deal renewer by context")
+			return
+		}
+	}
+}
+
+func (r *DealRenewer) renewDeals(ctx context.Context) {
+	deals, err := r.api.ClientListDeals(ctx)
+	if err != nil {
+		log.Errorf("list deals to renew: %s", err)
+		return
+	}
+	renewals, err := r.renewals.List(ctx)
+	if err != nil {
+		log.Errorf("list renewals of deals: %s", err)
+		return
+	}
+	head, err := r.api.Full.ChainHead(ctx)
+	if err != nil {
+		log.Errorf("get chain head: %s", err)
+		return
+	}
+	window := abi.ChainEpoch(time.Duration(r.cfg.Window) / (time.Duration(constants.MainNetBlockDelaySecs) * time.Second))
+
+	for _, deal := range dealsToRenew(deals, renewals) {
+		md, err := r.api.Full.StateMarketStorageDeal(ctx, deal.DealID, vTypes.EmptyTSK)
+		if err != nil {
+			log.Errorf("get market deal %d: %s", deal.DealID, err)
+			continue
+		}
+		end := md.Proposal.EndEpoch
+		if end <= head.Height() || end-head.Height() > window {
+			continue
+		}
+
+		wallet, err := r.api.Full.StateAccountKey(ctx, md.Proposal.Client, vTypes.EmptyTSK)
+		if err != nil {
+			log.Errorf("resolve client %s of deal %d: %s", md.Proposal.Client, deal.DealID, err)
+			continue
+		}
+		renewal, err := r.renew(ctx, deal, wallet)
+		if err != nil {
+			log.Errorf("renew deal %s ending at %d: %s", deal.ProposalCid, end, err)
+			continue
+		}
+		renewal.EndEpoch = end
+		if err := r.renewals.Save(ctx, renewal); err != nil {
+			log.Errorf("save renewal of deal %s: %s", deal.ProposalCid, err)
+			continue
+		}
+		log.Infof("deal %s ending at %d is renewed by %s with %s", deal.ProposalCid, end, renewal.Replacement, renewal.Miner)
+	}
+}
+
+// renew proposes a deal of the same data with the miner of the original deal, or else with the fallback miners,
+// a miner rejecting the deal after it is proposed is also skipped for the next one
+func (r *DealRenewer) renew(ctx context.Context, deal types.DealInfo, wallet address.Address) (*types2.DealRenewal, error) {
+	if deal.DataRef == nil {
+		return nil, fmt.Errorf("no data reference")
+	}
+	// deal info doesn't carry the retrieval option, it is read from the local deal
+	local, err := r.api.SMDealClient.GetLocalDeal(ctx, deal.ProposalCid)
+	if err != nil {
+		return nil, fmt.Errorf("get local deal: %w", err)
+	}
+	duration := r.cfg.DealDuration
+	if duration == 0 {
+		duration = deal.Duration
+	}
+	params := types2.ReplicationParams{
+		Deal: types.StartDealParams{
+			Data:              deal.DataRef,
+			Wallet:            wallet,
+			MinBlocksDuration: duration,
+			DealStartEpoch:    -1,
+			FastRetrieval:     local.FastRetrieval,
+			VerifiedDeal:      deal.Verified,
+		},
+		Replicas: 1,
+		Miners:   []address.Address{deal.Provider},
+	}
+	if maxPrice := big.Int(r.cfg.MaxPrice); !maxPrice.Nil() && !maxPrice.IsZero() {
+		params.MaxPrice = maxPrice
+	}
+
+	res, err := r.api.ClientReplicateDeal(ctx, params)
+	if err == nil && res.Replicas == 0 {
+		var fallback []address.Address
+		for _, miner := range config.ConvertConfigAddress(r.cfg.FallbackMiners) {
+			if miner != deal.Provider {
+				fallback = append(fallback, miner)
+			}
+		}
+		if len(fallback) > 0 {
+			params.Miners = fallback
+			res, err = r.api.ClientReplicateDeal(ctx, params)
+		}
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	// deals rejected or failed after proposing are skipped by replication, the accepted one has no error
+	for _, d := range res.Deals {
+		if d.ProposalCid != nil && len(d.Error) == 0 {
+			return &types2.DealRenewal{
+				Original:     deal.ProposalCid,
+				Replacement:  *d.ProposalCid,
+				Miner:        d.Miner,
+				Root:         deal.DataRef.Root,
+				CreationTime: time.Now(),
+			}, nil
+		}
+	}
+	if len(res.Deals) > 0 {
+		return nil, fmt.Errorf("no miner accepts the renewal, last error: %s", res.Deals[len(res.Deals)-1].Error)
+	}
+	return nil, fmt.Errorf("no miner accepts the renewal")
+}
+
+// dealsToRenew returns the active deals which have no replacement, or whose replacements have all failed
+func dealsToRenew(deals []types.DealInfo, renewals []types2.DealRenewal) []types.DealInfo {
+	states := make(map[cid.Cid]storagemarket.StorageDealStatus, len(deals))
+	for _, deal := range deals {
+		states[deal.ProposalCid] = deal.State
+	}
+
+	renewed := make(map[cid.Cid]struct{})
+	for _, renewal := range renewals {
+		state, ok := states[renewal.Replacement]
+		if ok && dealFailed(state) {
+			continue
+		}
+		renewed[renewal.Original] = struct{}{}
+	}
+
+	var out []types.DealInfo
+	for _, deal := range deals {
+		if deal.State != storagemarket.StorageDealActive || deal.DealID == 0 {
+			continue
+		}
+		if _, ok := renewed[deal.ProposalCid]; ok {
+			continue
+		}
+		out = append(out, deal)
+	}
+	return out
+}
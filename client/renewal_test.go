@@ -0,0 +1,53 @@
+package client
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-datastore"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+func TestDealsToRenew(t *testing.T) {
+	ctx := context.Background()
+	cids := shared_testutil.GenerateCids(6)
+
+	deal := func(c cid.Cid, state storagemarket.StorageDealStatus, dealID abi.DealID) types.DealInfo {
+		return types.DealInfo{ProposalCid: c, State: state, DealID: dealID}
+	}
+	deals := []types.DealInfo{
+		deal(cids[0], storagemarket.StorageDealActive, 1),       // not renewed
+		deal(cids[1], storagemarket.StorageDealActive, 2),       // renewed by cids[4]
+		deal(cids[2], storagemarket.StorageDealActive, 3),       // renewed by cids[5], which failed
+		deal(cids[3], storagemarket.StorageDealTransferring, 0), // not active
+		deal(cids[4], storagemarket.StorageDealTransferring, 0),
+		deal(cids[5], storagemarket.StorageDealError, 0),
+	}
+
+	store := NewRenewalStore(datastore.NewMapDatastore())
+	for _, renewal := range []types2.DealRenewal{
+		{Original: cids[1], Replacement: cids[4], Root: cids[1], CreationTime: time.Now()},
+		{Original: cids[2], Replacement: cids[5], Root: cids[2], CreationTime: time.Now()},
+	} {
+		renewal := renewal
+		require.NoError(t, store.Save(ctx, &renewal))
+	}
+	renewals, err := store.List(ctx)
+	require.NoError(t, err)
+	require.Len(t, renewals, 2)
+
+	var renew []cid.Cid
+	for _, d := range dealsToRenew(deals, renewals) {
+		renew = append(renew, d.ProposalCid)
+	}
+	assert.ElementsMatch(t, []cid.Cid{cids[0], cids[2]}, renew)
+}
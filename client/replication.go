@@ -0,0 +1,346 @@
+package client
+
+import (
+	"context"
+	"fmt"
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus-auth/log"
+	vTypes "github.com/filecoin-project/venus/venus-shared/types"
+	"github.com/ipfs/go-cid"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+)
+
+const (
+	replicaQueryParallel = 50
+	replicaQueryTimeout  = 10 * time.Second
+)
+
+var (
+	// replicaAcceptTimeout is the max time to wait for a proposed replica to be accepted, a replica still in
+	// progress after it is counted as proposed
+	replicaAcceptTimeout = time.Hour
+	// replicaCheckInterval is the interval to check the state of a proposed replica
+	replicaCheckInterval = 10 * time.Second
+)
+
+// ClientReplicateDeal stores data with many distinct miners. The asks of the candidate miners are queried, miners
+// which ask too much, don't accept the size of the piece or have failed too many past deals with the client are
+// filtered out, and the rest are tried from the cheapest one until enough deals are accepted. Each proposed deal
+// is followed until the miner accepts it or it fails, a miner rejecting the proposal or failing the deal is skipped
+// for the next candidate.
+func (a *API) ClientReplicateDeal(ctx context.Context, params types2.ReplicationParams) (*types2.ReplicationResult, error) {
+	if params.Replicas <= 0 {
+		return nil, fmt.Errorf("replicas must be positive")
+	}
+	if params.Deal.Data == nil {
+		return nil, fmt.Errorf("no data to store")
+	}
+
+	pieceSize, err := a.replicaPieceSize(ctx, params.Deal.Data)
+	if err != nil {
+		return nil, err
+	}
+	if params.Deal.VerifiedDeal {
+		dcap, err := a.Full.StateVerifiedClientStatus(ctx, params.Deal.Wallet, vTypes.EmptyTSK)
+		if err != nil {
+			return nil, fmt.Errorf("failed to get datacap of %s: %w", params.Deal.Wallet, err)
+		}
+		if dcap == nil || dcap.LessThan(big.NewIntUnsigned(uint64(pieceSize))) {
+			return nil, fmt.Errorf("wallet %s has not enough datacap for piece of %d bytes", params.Deal.Wallet, pieceSize)
+		}
+	}
+
+	miners := uniqueMiners(params.Miners)
+	if len(miners) == 0 {
+		if miners, err = a.minersWithMinPower(ctx); err != nil {
+			return nil, err
+		}
+	}
+
+	deals, err := a.ClientListDeals(ctx)
+	if err != nil {
+		return nil, fmt.Errorf("failed to list past deals: %w", err)
+	}
+	reliability := minerReliability(deals)
+
+	asks := a.queryAsks(ctx, miners)
+	candidates := make([]types2.ReplicaCandidate, 0, len(asks))
+	for _, ask := range asks {
+		candidates = append(candidates, newReplicaCandidate(ask, pieceSize, params.Deal.VerifiedDeal, reliability[ask.Miner]))
+	}
+
+	res := &types2.ReplicationResult{}
+	res.Candidates, res.Rejected = planReplicas(candidates, pieceSize, &params)
+
+	for _, cand := range res.Candidates {
+		if res.Replicas >= params.Replicas {
+			break
+		}
+
+		p := params.Deal
+		data := *params.Deal.Data
+		p.Data = &data
+		p.Miner = cand.Miner
+		p.EpochPrice = cand.EpochPrice
+
+		dealRes := types2.StartDealResult{
+			Index:  len(res.Deals),
+			Miner:  p.Miner,
+			Wallet: p.Wallet,
+			Root:   data.Root,
+			Escrow: big.Zero(),
+		}
+		if dealRes.ProposalCid, err = a.replicateTo(ctx, &p, &dealRes); err != nil {
+			log.Warnf("failed to propose replica of %s to %s: %s", data.Root, p.Miner, err)
+			dealRes.Error = err.Error()
+		} else if err = a.waitDealAccepted(ctx, *dealRes.ProposalCid); err != nil {
+			if ctx.Err() != nil {
+				return nil, ctx.Err()
+			}
+			log.Warnf("replica of %s is not accepted by %s: %s", data.Root, p.Miner, err)
+			dealRes.Error = err.Error()
+		} else {
+			res.Replicas++
+		}
+		res.Deals = append(res.Deals, dealRes)
+	}
+
+	return res, nil
+}
+
+func (a *API) replicateTo(ctx context.Context, p *types.StartDealParams, res *types2.StartDealResult) (*cid.Cid, error) {
+	start, end, err := a.dealEpochs(ctx, p)
+	if err != nil {
+		return nil, err
+	}
+	p.DealStartEpoch = start
+	res.StartEpoch = start
+	res.EndEpoch = end
+	res.Escrow = dealEscrow(p.EpochPrice, start, end)
+
+	return a.dealStarter(ctx, p, false)
+}
+
+// waitDealAccepted follows the state of a proposed deal until the miner accepts it or it fails, a deal still in
+// progress after replicaAcceptTimeout is taken as accepted
+func (a *API) waitDealAccepted(ctx context.Context, proposalCid cid.Cid) error {
+	timeoutCtx, cancel := context.WithTimeout(ctx, replicaAcceptTimeout)
+	defer cancel()
+	ticker := time.NewTicker(replicaCheckInterval)
+	defer ticker.Stop()
+	for {
+		deal, err := a.SMDealClient.GetLocalDeal(timeoutCtx, proposalCid)
+		if err != nil {
+			log.Warnf("failed to get state of deal %s: %s", proposalCid, err)
+		} else if dealFailed(deal.State) {
+			return fmt.Errorf("deal %s failed in state %s: %s", proposalCid, storagemarket.DealStates[deal.State], deal.Message)
+		} else if dealAccepted(deal.State) {
+			return nil
+		}
+
+		select {
+		case <-ticker.C:
+		case <-timeoutCtx.Done():
+			if ctx.Err() != nil {
+				return ctx.Err()
+			}
+			log.Warnf("deal %s is still in progress after %s", proposalCid, replicaAcceptTimeout)
+			return nil
+		}
+	}
+}
+
+// replicaPieceSize returns the padded size of the piece the data is stored in
+func (a *API) replicaPieceSize(ctx context.Context, data *storagemarket.DataRef) (abi.PaddedPieceSize, error) {
+	if data.PieceCid != nil {
+		if data.PieceSize == 0 {
+			return 0, fmt.Errorf("must specify piece size when manually setting piece cid")
+		}
+		return data.PieceSize.Padded(), nil
+	}
+
+	size, err := a.ClientDealSize(ctx, data.Root)
+	if err != nil {
+		return 0, fmt.Errorf("failed to calculate size of %s: %w", data.Root, err)
+	}
+	return size.PieceSize, nil
+}
+
+// minersWithMinPower lists the miners which have reached the minimum power of consensus
+func (a *API) minersWithMinPower(ctx context.Context) ([]address.Address, error) {
+	miners, err := a.Full.StateListMiners(ctx, vTypes.EmptyTSK)
+	if err != nil {
+		return nil, fmt.Errorf("failed to list miners: %w", err)
+	}
+
+	var lk sync.Mutex
+	var out []address.Address
+	forEachParallel(miners, func(miner address.Address) {
+		power, err := a.Full.StateMinerPower(ctx, miner, vTypes.EmptyTSK)
+		if err != nil || !power.HasMinPower {
+			return
+		}
+		lk.Lock()
+		out = append(out, miner)
+		lk.Unlock()
+	})
+	return out, nil
+}
+
+// queryAsks queries the asks of miners, the miners not responding in time are ignored
+func (a *API) queryAsks(ctx context.Context, miners []address.Address) []*storagemarket.StorageAsk {
+	var lk sync.Mutex
+	var asks []*storagemarket.StorageAsk
+	forEachParallel(miners, func(miner address.Address) {
+		ctx, cancel := context.WithTimeout(ctx, replicaQueryTimeout)
+		defer cancel()
+
+		mi, err := a.Full.StateMinerInfo(ctx, miner, vTypes.EmptyTSK)
+		if err != nil || mi.PeerId == nil {
+			return
+		}
+		ask, err := a.ClientQueryAsk(ctx, *mi.PeerId, miner)
+		if err != nil {
+			log.Debugf("failed to query ask of %s: %s", miner, err)
+			return
+		}
+		lk.Lock()
+		asks = append(asks, ask)
+		lk.Unlock()
+	})
+	return asks
+}
+
+func uniqueMiners(miners []address.Address) []address.Address {
+	seen := make(map[address.Address]struct{}, len(miners))
+	out := make([]address.Address, 0, len(miners))
+	for _, miner := range miners {
+		if _, ok := seen[miner]; !ok {
+			seen[miner] = struct{}{}
+			out = append(out, miner)
+		}
+	}
+	return out
+}
+
+func forEachParallel(miners []address.Address, fn func(address.Address)) {
+	var wg sync.WaitGroup
+	throttle := make(chan struct{}, replicaQueryParallel)
+	for _, miner := range miners {
+		throttle <- struct{}{}
+		wg.Add(1)
+		go func(miner address.Address) {
+			defer func() {
+				<-throttle
+				wg.Done()
+			}()
+			fn(miner)
+		}(miner)
+	}
+	wg.Wait()
+}
+
+type dealOutcomes struct {
+	finished int
+	failed   int
+}
+
+// minerReliability counts the finished deals with each miner, deals still in progress tell nothing about the miner
+func minerReliability(deals []types.DealInfo) map[address.Address]dealOutcomes {
+	out := make(map[address.Address]dealOutcomes)
+	for _, deal := range deals {
+		outcome := out[deal.Provider]
+		switch {
+		case deal.State == storagemarket.StorageDealActive || deal.State == storagemarket.StorageDealExpired:
+			outcome.finished++
+		case dealFailed(deal.State):
+			outcome.finished++
+			outcome.failed++
+		default:
+			continue
+		}
+		out[deal.Provider] = outcome
+	}
+	return out
+}
+
+// dealFailed tells whether a deal in the state has failed
+func dealFailed(state storagemarket.StorageDealStatus) bool {
+	switch state {
+	case storagemarket.StorageDealProposalRejected, storagemarket.StorageDealSlashed,
+		storagemarket.StorageDealFailing, storagemarket.StorageDealError:
+		return true
+	}
+	return false
+}
+
+// dealAccepted tells whether a deal in the state has been accepted by the miner
+func dealAccepted(state storagemarket.StorageDealStatus) bool {
+	switch state {
+	case storagemarket.StorageDealProposalAccepted, storagemarket.StorageDealAwaitingPreCommit,
+		storagemarket.StorageDealSealing, storagemarket.StorageDealActive, storagemarket.StorageDealExpired:
+		return true
+	}
+	return false
+}
+
+func newReplicaCandidate(ask *storagemarket.StorageAsk, pieceSize abi.PaddedPieceSize, verified bool, outcome dealOutcomes) types2.ReplicaCandidate {
+	price := ask.Price
+	if verified {
+		price = ask.VerifiedPrice
+	}
+	cand := types2.ReplicaCandidate{
+		Miner:         ask.Miner,
+		Price:         price,
+		MinPieceSize:  ask.MinPieceSize,
+		MaxPieceSize:  ask.MaxPieceSize,
+		FinishedDeals: outcome.finished,
+		Reliability:   1,
+	}
+	if !price.Nil() {
+		cand.EpochPrice = big.Div(big.Mul(price, big.NewInt(int64(pieceSize))), big.NewInt(1<<30))
+	}
+	if outcome.finished > 0 {
+		cand.Reliability = float64(outcome.finished-outcome.failed) / float64(outcome.finished)
+	}
+	return cand
+}
+
+// planReplicas filters out the candidates not satisfying params, the accepted ones are ranked by price, then by
+// reliability
+func planReplicas(candidates []types2.ReplicaCandidate, pieceSize abi.PaddedPieceSize, params *types2.ReplicationParams) (accepted, rejected []types2.ReplicaCandidate) {
+	for _, cand := range candidates {
+		switch {
+		case cand.Price.Nil():
+			cand.Rejected = "no price for verified deals"
+		case !params.MaxPrice.Nil() && cand.Price.GreaterThan(params.MaxPrice):
+			cand.Rejected = fmt.Sprintf("price %s higher than %s", vTypes.FIL(cand.Price), vTypes.FIL(params.MaxPrice))
+		case pieceSize < cand.MinPieceSize || (cand.MaxPieceSize > 0 && pieceSize > cand.MaxPieceSize):
+			cand.Rejected = fmt.Sprintf("piece size %d out of range [%d, %d]", pieceSize, cand.MinPieceSize, cand.MaxPieceSize)
+		case cand.Reliability < params.MinReliability:
+			cand.Rejected = fmt.Sprintf("reliability %.2f lower than %.2f", cand.Reliability, params.MinReliability)
+		}
+		if len(cand.Rejected) > 0 {
+			rejected = append(rejected, cand)
+		} else {
+			accepted = append(accepted, cand)
+		}
+	}
+
+	sort.SliceStable(accepted, func(i, j int) bool {
+		if !accepted[i].Price.Equals(accepted[j].Price) {
+			return accepted[i].Price.LessThan(accepted[j].Price)
+		}
+		return accepted[i].Reliability > accepted[j].Reliability
+	})
+	return accepted, rejected
+}
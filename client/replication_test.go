@@ -0,0 +1,143 @@
+package client
+
+import (
+	"context"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	types "github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+func TestPlanReplicas(t *testing.T) {
+	miner := func(id uint64) address.Address {
+		addr, err := address.NewIDAddress(id)
+		require.NoError(t, err)
+		return addr
+	}
+
+	deals := []types.DealInfo{
+		{Provider: miner(1001), State: storagemarket.StorageDealActive},
+		{Provider: miner(1001), State: storagemarket.StorageDealExpired},
+		{Provider: miner(1002), State: storagemarket.StorageDealActive},
+		{Provider: miner(1002), State: storagemarket.StorageDealError},
+		{Provider: miner(1002), State: storagemarket.StorageDealSlashed},
+		{Provider: miner(1002), State: storagemarket.StorageDealTransferring},
+		{Provider: miner(1003), State: storagemarket.StorageDealSealing},
+	}
+	outcomes := minerReliability(deals)
+	assert.Equal(t, dealOutcomes{finished: 2}, outcomes[miner(1001)])
+	assert.Equal(t, dealOutcomes{finished: 3, failed: 2}, outcomes[miner(1002)])
+	_, ok := outcomes[miner(1003)]
+	assert.False(t, ok)
+
+	pieceSize := abi.PaddedPieceSize(1 << 30)
+	ask := func(id uint64, price, verifiedPrice int64, minSize, maxSize abi.PaddedPieceSize) *storagemarket.StorageAsk {
+		return &storagemarket.StorageAsk{
+			Miner:         miner(id),
+			Price:         big.NewInt(price),
+			VerifiedPrice: big.NewInt(verifiedPrice),
+			MinPieceSize:  minSize,
+			MaxPieceSize:  maxSize,
+		}
+	}
+	asks := []*storagemarket.StorageAsk{
+		ask(1001, 20, 0, 256, 32<<30),
+		ask(1002, 10, 0, 256, 32<<30),  // unreliable
+		ask(1003, 10, 0, 256, 32<<30),  // no finished deals
+		ask(1004, 100, 0, 256, 32<<30), // too expensive
+		ask(1005, 5, 0, 2<<30, 32<<30), // piece too small
+		ask(1006, 5, 0, 256, 512<<20),  // piece too large
+		ask(1007, 20, 10, 256, 32<<30), // no finished deals
+		ask(1008, 1, 50, 256, 32<<30),  // cheap, but expensive for verified deals
+	}
+
+	plan := func(verified bool, params *types2.ReplicationParams) ([]types2.ReplicaCandidate, []types2.ReplicaCandidate) {
+		candidates := make([]types2.ReplicaCandidate, 0, len(asks))
+		for _, ask := range asks {
+			candidates = append(candidates, newReplicaCandidate(ask, pieceSize, verified, outcomes[ask.Miner]))
+		}
+		return planReplicas(candidates, pieceSize, params)
+	}
+	minersOf := func(candidates []types2.ReplicaCandidate) []address.Address {
+		var out []address.Address
+		for _, cand := range candidates {
+			out = append(out, cand.Miner)
+		}
+		return out
+	}
+
+	accepted, rejected := plan(false, &types2.ReplicationParams{MaxPrice: big.NewInt(50), MinReliability: 0.5})
+	assert.Equal(t, []address.Address{miner(1008), miner(1003), miner(1001), miner(1007)}, minersOf(accepted))
+	assert.Equal(t, []address.Address{miner(1002), miner(1004), miner(1005), miner(1006)}, minersOf(rejected))
+	for _, cand := range rejected {
+		assert.NotEmpty(t, cand.Rejected)
+	}
+	// price per epoch of a 1GiB piece is the price per GiB
+	assert.Equal(t, big.NewInt(1), accepted[0].EpochPrice)
+	assert.Equal(t, float64(1), accepted[2].Reliability)
+	assert.Equal(t, 2, accepted[2].FinishedDeals)
+
+	// no limit on price and reliability
+	accepted, _ = plan(false, &types2.ReplicationParams{MaxPrice: big.Int{}})
+	assert.Len(t, accepted, 6)
+
+	// verified price is used for verified deals
+	accepted, _ = plan(true, &types2.ReplicationParams{MaxPrice: big.NewInt(20), MinReliability: 0.5})
+	assert.Equal(t, []address.Address{miner(1001), miner(1003), miner(1004), miner(1007)}, minersOf(accepted))
+}
+
+// stateStorageClient moves each deal through the given states, one state per query
+type stateStorageClient struct {
+	storagemarket.StorageClient
+
+	lk     sync.Mutex
+	states map[cid.Cid][]storagemarket.StorageDealStatus
+}
+
+func (c *stateStorageClient) GetLocalDeal(ctx context.Context, proposalCid cid.Cid) (storagemarket.ClientDeal, error) {
+	c.lk.Lock()
+	defer c.lk.Unlock()
+	states := c.states[proposalCid]
+	deal := storagemarket.ClientDeal{ProposalCid: proposalCid, State: states[0]}
+	if len(states) > 1 {
+		c.states[proposalCid] = states[1:]
+	}
+	return deal, nil
+}
+
+func TestWaitDealAccepted(t *testing.T) {
+	ctx := context.Background()
+	defer func(timeout, interval time.Duration) {
+		replicaAcceptTimeout, replicaCheckInterval = timeout, interval
+	}(replicaAcceptTimeout, replicaCheckInterval)
+	replicaCheckInterval = time.Millisecond
+
+	cids := shared_testutil.GenerateCids(3)
+	a := &API{SMDealClient: &stateStorageClient{states: map[cid.Cid][]storagemarket.StorageDealStatus{
+		cids[0]: {storagemarket.StorageDealReserveClientFunds, storagemarket.StorageDealTransferring, storagemarket.StorageDealProposalAccepted},
+		cids[1]: {storagemarket.StorageDealCheckForAcceptance, storagemarket.StorageDealFailing},
+		cids[2]: {storagemarket.StorageDealTransferring},
+	}}}
+
+	assert.NoError(t, a.waitDealAccepted(ctx, cids[0]))
+	assert.Error(t, a.waitDealAccepted(ctx, cids[1]))
+
+	// still in progress after timeout
+	replicaAcceptTimeout = time.Millisecond * 20
+	assert.NoError(t, a.waitDealAccepted(ctx, cids[2]))
+
+	cancelCtx, cancel := context.WithCancel(ctx)
+	cancel()
+	assert.ErrorIs(t, a.waitDealAccepted(cancelCtx, cids[2]), context.Canceled)
+}
@@ -3,14 +3,18 @@ package main
 import (
 	"errors"
 	"fmt"
+	"os"
 	"path/filepath"
 	"sort"
 	"strconv"
+	"text/tabwriter"
 
 	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-cidutil/cidenc"
 	"github.com/urfave/cli/v2"
 
 	cli2 "github.com/filecoin-project/venus-market/v2/cli"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 	"github.com/filecoin-project/venus/venus-shared/types"
 	"github.com/filecoin-project/venus/venus-shared/types/market/client"
 )
@@ -26,6 +30,8 @@ var dataCmd = &cli.Command{
 		dataStatCmd,
 		dataCommPCmd,
 		dataGenerateCarCmd,
+		dataAggregateCmd,
+		dataManifestCmd,
 	},
 }
 
@@ -256,3 +262,106 @@ var dataGenerateCarCmd = &cli.Command{
 		return api.ClientGenCar(ctx, ref, op)
 	},
 }
+
+var dataAggregateCmd = &cli.Command{
+	Name:      "aggregate",
+	Usage:     "Pack many imports into one CAR, so that small files can be stored in a single deal",
+	ArgsUsage: "<import ID...>",
+	Flags: []cli.Flag{
+		&cli.BoolFlag{
+			Name:    "quiet",
+			Aliases: []string{"q"},
+			Usage:   "Output root CID only",
+		},
+		&cli2.CidBaseFlag,
+	},
+	Action: func(cctx *cli.Context) error {
+		if cctx.NArg() < 2 {
+			return fmt.Errorf("must specify at least two import IDs")
+		}
+
+		api, closer, err := cli2.NewMarketClientExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := cli2.ReqContext(cctx)
+
+		var ids []client.ImportID
+		for i, s := range cctx.Args().Slice() {
+			id, err := strconv.ParseUint(s, 10, 64)
+			if err != nil {
+				return fmt.Errorf("parsing %d-th import ID: %w", i, err)
+			}
+			ids = append(ids, client.ImportID(id))
+		}
+
+		manifest, err := api.ClientAggregate(ctx, ids)
+		if err != nil {
+			return err
+		}
+
+		encoder, err := cli2.GetCidEncoder(cctx)
+		if err != nil {
+			return err
+		}
+
+		if cctx.Bool("quiet") {
+			fmt.Println(encoder.Encode(manifest.Root))
+			return nil
+		}
+		return printAggregateManifest(encoder, manifest)
+	},
+}
+
+var dataManifestCmd = &cli.Command{
+	Name:      "manifest",
+	Usage:     "Print the manifest of an aggregated CAR",
+	ArgsUsage: "<root cid>",
+	Flags: []cli.Flag{
+		&cli2.CidBaseFlag,
+	},
+	Action: func(cctx *cli.Context) error {
+		if cctx.NArg() != 1 {
+			return fmt.Errorf("must specify root cid of the aggregated CAR")
+		}
+
+		root, err := cid.Parse(cctx.Args().First())
+		if err != nil {
+			return fmt.Errorf("parsing root cid: %w", err)
+		}
+
+		api, closer, err := cli2.NewMarketClientExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+		ctx := cli2.ReqContext(cctx)
+
+		manifest, err := api.ClientGetAggregateManifest(ctx, root)
+		if err != nil {
+			return err
+		}
+
+		encoder, err := cli2.GetCidEncoder(cctx)
+		if err != nil {
+			return err
+		}
+		return printAggregateManifest(encoder, manifest)
+	},
+}
+
+func printAggregateManifest(encoder cidenc.Encoder, manifest *types2.AggregateManifest) error {
+	fmt.Printf("Import %d, Root %s\n", manifest.ImportID, encoder.Encode(manifest.Root))
+	fmt.Printf("CAR: %s (%s)\n", manifest.CARPath, types.SizeStr(types.NewInt(manifest.CARSize)))
+	fmt.Printf("Piece CID: %s, Piece size: %d\n", encoder.Encode(manifest.PieceCID), manifest.PieceSize)
+	fmt.Println()
+
+	w := tabwriter.NewWriter(os.Stdout, 2, 4, 2, ' ', 0)
+	_, _ = fmt.Fprintf(w, "Name\tImport\tRoot\tOffset\tLength\tPath\n")
+	for _, file := range manifest.Files {
+		_, _ = fmt.Fprintf(w, "%s\t%d\t%s\t%d\t%d\t%s\n", file.Name, file.ImportID, encoder.Encode(file.Root),
+			file.Offset, file.Length, file.FilePath)
+	}
+	return w.Flush()
+}
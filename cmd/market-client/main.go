@@ -17,6 +17,7 @@ import (
 
 	"github.com/filecoin-project/go-address"
 
+	"github.com/filecoin-project/venus-market/v2/api"
 	"github.com/filecoin-project/venus-market/v2/api/impl"
 	cli2 "github.com/filecoin-project/venus-market/v2/cli"
 	"github.com/filecoin-project/venus-market/v2/client"
@@ -265,5 +266,13 @@ func marketClient(cctx *cli.Context) error {
 	var marketCli clientapi.IMarketClientStruct
 	permission.PermissionProxy((clientapi.IMarketClient)(resAPI), &marketCli)
 
-	return rpc.ServeRPC(ctx, cfg, &cfg.API, mux.NewRouter(), 1000, cli2.API_NAMESPACE_MARKET_CLIENT, nil, &marketCli, finishCh)
+	var extCli api.IMarketClientExtStruct
+	permission.PermissionProxy(api.IMarketClientExt(resAPI), &extCli)
+
+	localJwtClient, err := rpc.NewLocalJwtClient(cfg, &cfg.API)
+	if err != nil {
+		return fmt.Errorf("create local jwt client failed:%w", err)
+	}
+
+	return rpc.ServeRPC(ctx, cfg, &cfg.API, mux.NewRouter(), 1000, cli2.API_NAMESPACE_MARKET_CLIENT, localJwtClient, nil, []interface{}{&marketCli, &extCli}, finishCh)
 }
@@ -42,6 +42,7 @@ import (
 
 	cli2 "github.com/filecoin-project/venus-market/v2/cli"
 	"github.com/filecoin-project/venus-market/v2/cli/tablewriter"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 	"github.com/filecoin-project/venus/venus-shared/types/market/client"
 )
 
@@ -52,6 +53,7 @@ var storageCmd = &cli.Command{
 	Subcommands: []*cli.Command{
 		storageAsksCmd,
 		storageDealsCmd,
+		storageBatchCmd,
 	},
 }
 
@@ -378,6 +380,7 @@ var storageDealsCmd = &cli.Command{
 		storageDealsStatsCmd,
 		storageDealsGetCmd,
 		storageDealsInspectCmd,
+		storageDealsReplicateCmd,
 	},
 }
 
@@ -1080,6 +1083,10 @@ var storageDealsListCmd = &cli.Command{
 			Name:  "watch",
 			Usage: "watch deal updates in real-time, rather than a one time list",
 		},
+		&cli.BoolFlag{
+			Name:  "show-renewals",
+			Usage: "show which deal renews which, the deals renewed by the renewal service",
+		},
 	},
 	Action: func(cctx *cli.Context) error {
 		if cctx.IsSet("color") {
@@ -1108,6 +1115,21 @@ var storageDealsListCmd = &cli.Command{
 			return err
 		}
 
+		var lineage *dealLineage
+		if cctx.Bool("show-renewals") {
+			extAPI, extCloser, err := cli2.NewMarketClientExtNode(cctx)
+			if err != nil {
+				return err
+			}
+			defer extCloser()
+
+			renewals, err := extAPI.ClientListDealRenewals(ctx)
+			if err != nil {
+				return err
+			}
+			lineage = newDealLineage(renewals)
+		}
+
 		if watch {
 			updates, err := api.ClientGetDealUpdates(ctx)
 			if err != nil {
@@ -1118,7 +1140,7 @@ var storageDealsListCmd = &cli.Command{
 				tm.Clear()
 				tm.MoveCursor(1, 1)
 
-				err = outputClientStorageDeals(ctx, tm.Screen, fapi, localDeals, verbose, showFailed)
+				err = outputClientStorageDeals(ctx, tm.Screen, fapi, localDeals, lineage, verbose, showFailed)
 				if err != nil {
 					return err
 				}
@@ -1144,7 +1166,7 @@ var storageDealsListCmd = &cli.Command{
 			}
 		}
 
-		return outputClientStorageDeals(ctx, cctx.App.Writer, fapi, localDeals, verbose, showFailed)
+		return outputClientStorageDeals(ctx, cctx.App.Writer, fapi, localDeals, lineage, verbose, showFailed)
 	},
 }
 
@@ -1191,7 +1213,38 @@ func dealFromDealInfo(ctx context.Context, full v1api.FullNode, head *types.TipS
 	}
 }
 
-func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.FullNode, localDeals []client.DealInfo, verbose bool, showFailed bool) error {
+// dealLineage links renewed deals with the deals replacing them
+type dealLineage struct {
+	renews    map[cid.Cid]cid.Cid
+	renewedBy map[cid.Cid][]cid.Cid
+}
+
+func newDealLineage(renewals []types2.DealRenewal) *dealLineage {
+	lineage := &dealLineage{
+		renews:    make(map[cid.Cid]cid.Cid, len(renewals)),
+		renewedBy: make(map[cid.Cid][]cid.Cid, len(renewals)),
+	}
+	for _, renewal := range renewals {
+		lineage.renews[renewal.Replacement] = renewal.Original
+		lineage.renewedBy[renewal.Original] = append(lineage.renewedBy[renewal.Original], renewal.Replacement)
+	}
+	return lineage
+}
+
+// columns returns the deal the proposal renews and the deals renewing it, empty if none
+func (l *dealLineage) columns(proposal cid.Cid, format func(cid.Cid) string) (string, string) {
+	var renews string
+	if original, ok := l.renews[proposal]; ok {
+		renews = format(original)
+	}
+	var renewedBy []string
+	for _, replacement := range l.renewedBy[proposal] {
+		renewedBy = append(renewedBy, format(replacement))
+	}
+	return renews, strings.Join(renewedBy, ",")
+}
+
+func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.FullNode, localDeals []client.DealInfo, lineage *dealLineage, verbose bool, showFailed bool) error {
 	sort.Slice(localDeals, func(i, j int) bool {
 		return localDeals[i].CreationTime.Before(localDeals[j].CreationTime)
 	})
@@ -1210,7 +1263,11 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 
 	if verbose {
 		w := tabwriter.NewWriter(out, 2, 4, 2, ' ', 0)
-		fmt.Fprintf(w, "Created\tDealCid\tDealId\tProvider\tState\tOn Chain?\tSlashed?\tPieceCID\tDataCID\tSize\tPrice\tDuration\tTransferChannelID\tTransferStatus\tVerified\tMessage\n")
+		header := "Created\tDealCid\tDealId\tProvider\tState\tOn Chain?\tSlashed?\tPieceCID\tDataCID\tSize\tPrice\tDuration\tTransferChannelID\tTransferStatus\tVerified\tMessage"
+		if lineage != nil {
+			header += "\tRenews\tRenewedBy"
+		}
+		fmt.Fprintln(w, header)
 		for _, d := range deals {
 			onChain := "N"
 			if d.OnChainDealState.SectorStartEpoch != -1 {
@@ -1238,7 +1295,7 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 				//	transferPct = fmt.Sprintf("%d%%", pct)
 				//}
 			}
-			fmt.Fprintf(w, "%s\t%s\t%d\t%s\t%s\t%s\t%s\t%s\t%s\t%s\t%s\t%d\t%s\t%s\t%v\t%s\n",
+			fmt.Fprintf(w, "%s\t%s\t%d\t%s\t%s\t%s\t%s\t%s\t%s\t%s\t%s\t%d\t%s\t%s\t%v\t%s",
 				d.LocalDeal.CreationTime.Format(time.Stamp),
 				d.LocalDeal.ProposalCid,
 				d.LocalDeal.DealID,
@@ -1255,6 +1312,11 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 				transferStatus,
 				d.LocalDeal.Verified,
 				d.LocalDeal.Message)
+			if lineage != nil {
+				renews, renewedBy := lineage.columns(d.LocalDeal.ProposalCid, cid.Cid.String)
+				fmt.Fprintf(w, "\t%s\t%s", renews, renewedBy)
+			}
+			fmt.Fprintln(w)
 		}
 		return w.Flush()
 	}
@@ -1271,6 +1333,8 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 		tablewriter.Col("Price"),
 		tablewriter.Col("Duration"),
 		tablewriter.Col("Verified"),
+		tablewriter.Col("Renews"),
+		tablewriter.Col("RenewedBy"),
 		tablewriter.NewLineCol("Message"))
 
 	for _, d := range deals {
@@ -1291,7 +1355,7 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 
 		price := types.FIL(types.BigMul(d.LocalDeal.PricePerEpoch, types.NewInt(d.LocalDeal.Duration)))
 
-		w.Write(map[string]interface{}{
+		row := map[string]interface{}{
 			"DealCid":   propcid,
 			"DealId":    d.LocalDeal.DealID,
 			"Provider":  d.LocalDeal.Provider,
@@ -1305,7 +1369,19 @@ func outputClientStorageDeals(ctx context.Context, out io.Writer, full v1api.Ful
 			"Verified":  d.LocalDeal.Verified,
 			"Duration":  d.LocalDeal.Duration,
 			"Message":   d.LocalDeal.Message,
-		})
+		}
+		if lineage != nil {
+			renews, renewedBy := lineage.columns(d.LocalDeal.ProposalCid, func(c cid.Cid) string {
+				return ellipsis(c.String(), 8)
+			})
+			if len(renews) > 0 {
+				row["Renews"] = renews
+			}
+			if len(renewedBy) > 0 {
+				row["RenewedBy"] = renewedBy
+			}
+		}
+		w.Write(row)
 	}
 
 	return w.Flush(out)
@@ -0,0 +1,222 @@
+package main
+
+import (
+	"encoding/csv"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"io/ioutil"
+	"os"
+	"strconv"
+	"strings"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus/venus-shared/types"
+	"github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+	"github.com/urfave/cli/v2"
+
+	cli2 "github.com/filecoin-project/venus-market/v2/cli"
+)
+
+var storageBatchCmd = &cli.Command{
+	Name:  "batch",
+	Usage: "Start storage deals listed in a csv manifest",
+	Description: `The manifest is a csv file whose first line is the header, columns:
+  miner, data_cid, price, duration        required, same as the args of 'storage deals init'
+  wallet                                  address to fund the deal with, default address if empty
+  piece_cid, piece_size                   manually specified piece, makes a manual transfer deal
+  start_epoch, verified_deal, fast_retrieval, provider_collateral
+                                          same as the flags of 'storage deals init'
+Escrow of all deals is reserved once for each wallet, and the outcome of each deal is written into the result file in json.`,
+	Flags: []cli.Flag{
+		&cli.StringFlag{
+			Name:     "manifest",
+			Usage:    "path of the csv manifest",
+			Required: true,
+		},
+		&cli.StringFlag{
+			Name:  "output",
+			Usage: "path of the result file, default to <manifest>.result.json",
+		},
+	},
+	Action: func(cctx *cli.Context) error {
+		api, closer, err := cli2.NewMarketClientNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+
+		extAPI, extCloser, err := cli2.NewMarketClientExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer extCloser()
+		ctx := cli2.ReqContext(cctx)
+
+		manifest := cctx.String("manifest")
+		f, err := os.Open(manifest)
+		if err != nil {
+			return err
+		}
+		defer f.Close() //nolint:errcheck
+
+		defWallet, err := api.DefaultAddress(ctx)
+		if err != nil {
+			return err
+		}
+		params, err := parseDealManifest(f, defWallet)
+		if err != nil {
+			return fmt.Errorf("parse manifest %s: %w", manifest, err)
+		}
+
+		results, err := extAPI.ClientStartDeals(ctx, params)
+		if err != nil {
+			return err
+		}
+
+		output := cctx.String("output")
+		if len(output) == 0 {
+			output = manifest + ".result.json"
+		}
+		data, err := json.MarshalIndent(results, "", "  ")
+		if err != nil {
+			return err
+		}
+		if err := ioutil.WriteFile(output, data, 0644); err != nil {
+			return err
+		}
+
+		var failed int
+		for _, res := range results {
+			if len(res.Error) > 0 {
+				failed++
+				fmt.Printf("deal %d with %s failed: %s\n", res.Index, res.Miner, res.Error)
+			}
+		}
+		fmt.Printf("%d deals started, %d failed, result is written to %s\n", len(results)-failed, failed, output)
+		return nil
+	},
+}
+
+// parseDealManifest parses deals from a csv manifest, the columns are described in the usage of storageBatchCmd
+func parseDealManifest(r io.Reader, defWallet address.Address) ([]client.StartDealParams, error) {
+	records, err := csv.NewReader(r).ReadAll()
+	if err != nil {
+		return nil, err
+	}
+	if len(records) < 2 {
+		return nil, errors.New("no deals in manifest")
+	}
+
+	columns := make(map[string]int, len(records[0]))
+	for i, name := range records[0] {
+		columns[strings.TrimSpace(name)] = i
+	}
+	for _, name := range []string{"miner", "data_cid", "price", "duration"} {
+		if _, ok := columns[name]; !ok {
+			return nil, fmt.Errorf("missing column %s", name)
+		}
+	}
+
+	params := make([]client.StartDealParams, 0, len(records)-1)
+	for line, record := range records[1:] {
+		p, err := parseDealRecord(record, columns, defWallet)
+		if err != nil {
+			return nil, fmt.Errorf("line %d: %w", line+2, err)
+		}
+		params = append(params, *p)
+	}
+	return params, nil
+}
+
+func parseDealRecord(record []string, columns map[string]int, defWallet address.Address) (*client.StartDealParams, error) {
+	get := func(name string) string {
+		if idx, ok := columns[name]; ok && idx < len(record) {
+			return strings.TrimSpace(record[idx])
+		}
+		return ""
+	}
+
+	data, err := cid.Parse(get("data_cid"))
+	if err != nil {
+		return nil, fmt.Errorf("invalid data_cid: %w", err)
+	}
+	miner, err := address.NewFromString(get("miner"))
+	if err != nil {
+		return nil, fmt.Errorf("invalid miner: %w", err)
+	}
+	price, err := types.ParseFIL(get("price"))
+	if err != nil {
+		return nil, fmt.Errorf("invalid price: %w", err)
+	}
+	dur, err := strconv.ParseInt(get("duration"), 10, 32)
+	if err != nil {
+		return nil, fmt.Errorf("invalid duration: %w", err)
+	}
+	if abi.ChainEpoch(dur) < MinDealDuration {
+		return nil, fmt.Errorf("minimum deal duration is %d blocks", MinDealDuration)
+	}
+	if abi.ChainEpoch(dur) > MaxDealDuration {
+		return nil, fmt.Errorf("maximum deal duration is %d blocks", MaxDealDuration)
+	}
+
+	p := &client.StartDealParams{
+		Data: &storagemarket.DataRef{
+			TransferType: storagemarket.TTGraphsync,
+			Root:         data,
+		},
+		Wallet:            defWallet,
+		Miner:             miner,
+		EpochPrice:        types.BigInt(price),
+		MinBlocksDuration: uint64(dur),
+		DealStartEpoch:    -1,
+		FastRetrieval:     true,
+	}
+
+	if s := get("wallet"); len(s) > 0 {
+		if p.Wallet, err = address.NewFromString(s); err != nil {
+			return nil, fmt.Errorf("invalid wallet: %w", err)
+		}
+	}
+	if s := get("piece_cid"); len(s) > 0 {
+		pieceCid, err := cid.Parse(s)
+		if err != nil {
+			return nil, fmt.Errorf("invalid piece_cid: %w", err)
+		}
+		pieceSize, err := strconv.ParseUint(get("piece_size"), 10, 64)
+		if err != nil || pieceSize == 0 {
+			return nil, fmt.Errorf("must specify piece_size when manually setting piece_cid")
+		}
+		p.Data.PieceCid = &pieceCid
+		p.Data.PieceSize = abi.UnpaddedPieceSize(pieceSize)
+		p.Data.TransferType = storagemarket.TTManual
+	}
+	if s := get("start_epoch"); len(s) > 0 {
+		start, err := strconv.ParseInt(s, 10, 64)
+		if err != nil {
+			return nil, fmt.Errorf("invalid start_epoch: %w", err)
+		}
+		p.DealStartEpoch = abi.ChainEpoch(start)
+	}
+	if s := get("verified_deal"); len(s) > 0 {
+		if p.VerifiedDeal, err = strconv.ParseBool(s); err != nil {
+			return nil, fmt.Errorf("invalid verified_deal: %w", err)
+		}
+	}
+	if s := get("fast_retrieval"); len(s) > 0 {
+		if p.FastRetrieval, err = strconv.ParseBool(s); err != nil {
+			return nil, fmt.Errorf("invalid fast_retrieval: %w", err)
+		}
+	}
+	if s := get("provider_collateral"); len(s) > 0 {
+		if p.ProviderCollateral, err = big.FromString(s); err != nil {
+			return nil, fmt.Errorf("invalid provider_collateral: %w", err)
+		}
+	}
+	return p, nil
+}
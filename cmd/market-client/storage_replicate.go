@@ -0,0 +1,216 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"strconv"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus/venus-shared/types"
+	"github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+	"github.com/urfave/cli/v2"
+
+	cli2 "github.com/filecoin-project/venus-market/v2/cli"
+	"github.com/filecoin-project/venus-market/v2/cli/tablewriter"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+var storageDealsReplicateCmd = &cli.Command{
+	Name:  "replicate",
+	Usage: "Store data with many miners selected by their asks",
+	Description: `Query the asks of the candidate miners, filter out the miners asking more than max-price,
+not accepting the size of the piece, or failing too many past deals, then propose deals to the cheapest
+ones until the data is stored with the number of replicas. A miner failing the proposal is replaced by
+the next candidate. The price of each deal is the ask price of the miner.`,
+	ArgsUsage: "[dataCid duration]",
+	Flags: []cli.Flag{
+		&cli.IntFlag{
+			Name:  "replicas",
+			Usage: "number of distinct miners to store the data with",
+			Value: 3,
+		},
+		&cli.StringSliceFlag{
+			Name:  "miners",
+			Usage: "candidate miners, default to all miners with minimum power",
+		},
+		&cli.StringFlag{
+			Name:  "max-price",
+			Usage: "highest ask price accepted in FIL/GiB/Epoch, verified price is compared for verified deals",
+		},
+		&cli.Float64Flag{
+			Name:  "min-reliability",
+			Usage: "lowest ratio of past deals with a miner not failed, miners without finished deals are accepted",
+		},
+		&cli.StringFlag{
+			Name:  "manual-piece-cid",
+			Usage: "manually specify piece commitment for data (dataCid must be to a car file)",
+		},
+		&cli.Int64Flag{
+			Name:  "manual-piece-size",
+			Usage: "if manually specifying piece cid, used to specify size (dataCid must be to a car file)",
+		},
+		&cli.StringFlag{
+			Name:  "from",
+			Usage: "specify address to fund the deals with",
+		},
+		&cli.Int64Flag{
+			Name:  "start-epoch",
+			Usage: "specify the epoch that the deals should start at",
+			Value: -1,
+		},
+		&cli.BoolFlag{
+			Name:  "fast-retrieval",
+			Usage: "indicates that data should be available for fast retrieval",
+			Value: true,
+		},
+		&cli.BoolFlag{
+			Name:  "verified-deal",
+			Usage: "indicate that the deals count towards verified client total",
+		},
+		&cli.StringFlag{
+			Name:  "provider-collateral",
+			Usage: "specify the requested provider collateral the miners should put up",
+		},
+	},
+	Action: func(cctx *cli.Context) error {
+		if cctx.NArg() != 2 {
+			return errors.New("expected 2 args: dataCid, duration")
+		}
+
+		api, closer, err := cli2.NewMarketClientNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer closer()
+
+		extAPI, extCloser, err := cli2.NewMarketClientExtNode(cctx)
+		if err != nil {
+			return err
+		}
+		defer extCloser()
+		ctx := cli2.ReqContext(cctx)
+
+		data, err := cid.Parse(cctx.Args().Get(0))
+		if err != nil {
+			return err
+		}
+		dur, err := strconv.ParseInt(cctx.Args().Get(1), 10, 32)
+		if err != nil {
+			return err
+		}
+		if abi.ChainEpoch(dur) < MinDealDuration {
+			return fmt.Errorf("minimum deal duration is %d blocks", MinDealDuration)
+		}
+		if abi.ChainEpoch(dur) > MaxDealDuration {
+			return fmt.Errorf("maximum deal duration is %d blocks", MaxDealDuration)
+		}
+
+		params := types2.ReplicationParams{
+			Deal: client.StartDealParams{
+				Data: &storagemarket.DataRef{
+					TransferType: storagemarket.TTGraphsync,
+					Root:         data,
+				},
+				MinBlocksDuration: uint64(dur),
+				DealStartEpoch:    abi.ChainEpoch(cctx.Int64("start-epoch")),
+				FastRetrieval:     cctx.Bool("fast-retrieval"),
+				VerifiedDeal:      cctx.Bool("verified-deal"),
+			},
+			Replicas:       cctx.Int("replicas"),
+			MinReliability: cctx.Float64("min-reliability"),
+		}
+
+		if from := cctx.String("from"); from != "" {
+			if params.Deal.Wallet, err = address.NewFromString(from); err != nil {
+				return fmt.Errorf("failed to parse 'from' address: %w", err)
+			}
+		} else if params.Deal.Wallet, err = api.DefaultAddress(ctx); err != nil {
+			return err
+		}
+
+		for _, s := range cctx.StringSlice("miners") {
+			miner, err := address.NewFromString(s)
+			if err != nil {
+				return fmt.Errorf("invalid miner %s: %w", s, err)
+			}
+			params.Miners = append(params.Miners, miner)
+		}
+
+		if s := cctx.String("max-price"); s != "" {
+			price, err := types.ParseFIL(s)
+			if err != nil {
+				return fmt.Errorf("failed to parse max-price: %w", err)
+			}
+			params.MaxPrice = big.Int(price)
+		}
+
+		if pcs := cctx.String("provider-collateral"); pcs != "" {
+			if params.Deal.ProviderCollateral, err = big.FromString(pcs); err != nil {
+				return fmt.Errorf("failed to parse provider-collateral: %w", err)
+			}
+		}
+
+		if mpc := cctx.String("manual-piece-cid"); mpc != "" {
+			c, err := cid.Parse(mpc)
+			if err != nil {
+				return fmt.Errorf("failed to parse provided manual piece cid: %w", err)
+			}
+			psize := cctx.Int64("manual-piece-size")
+			if psize == 0 {
+				return fmt.Errorf("must specify piece size when manually setting cid")
+			}
+			params.Deal.Data.PieceCid = &c
+			params.Deal.Data.PieceSize = abi.UnpaddedPieceSize(psize)
+			params.Deal.Data.TransferType = storagemarket.TTManual
+		}
+
+		res, err := extAPI.ClientReplicateDeal(ctx, params)
+		if err != nil {
+			return err
+		}
+
+		for _, cand := range res.Rejected {
+			fmt.Printf("skip %s: %s\n", cand.Miner, cand.Rejected)
+		}
+
+		w := tablewriter.New(tablewriter.Col("Miner"),
+			tablewriter.Col("Price/GiB"),
+			tablewriter.Col("Price/Epoch"),
+			tablewriter.Col("Reliability"),
+			tablewriter.Col("ProposalCid"),
+			tablewriter.NewLineCol("Error"))
+		prices := make(map[address.Address]types2.ReplicaCandidate, len(res.Candidates))
+		for _, cand := range res.Candidates {
+			prices[cand.Miner] = cand
+		}
+		for _, deal := range res.Deals {
+			cand := prices[deal.Miner]
+			row := map[string]interface{}{
+				"Miner":       deal.Miner,
+				"Price/GiB":   types.FIL(cand.Price),
+				"Price/Epoch": types.FIL(cand.EpochPrice),
+				"Reliability": fmt.Sprintf("%.2f (%d deals)", cand.Reliability, cand.FinishedDeals),
+			}
+			if deal.ProposalCid != nil {
+				row["ProposalCid"] = deal.ProposalCid.String()
+			} else {
+				row["Error"] = deal.Error
+			}
+			w.Write(row)
+		}
+		if err := w.Flush(os.Stdout); err != nil {
+			return err
+		}
+
+		if res.Replicas < params.Replicas {
+			return fmt.Errorf("only %d of %d replicas proposed, %d miners accepted", res.Replicas, params.Replicas, len(res.Candidates))
+		}
+		fmt.Printf("%d replicas proposed\n", res.Replicas)
+		return nil
+	},
+}
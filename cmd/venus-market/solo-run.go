@@ -5,6 +5,7 @@ import (
 	"fmt"
 
 	"github.com/filecoin-project/venus-auth/cmd/jwtclient"
+	"github.com/filecoin-project/venus-market/v2/api"
 	"github.com/filecoin-project/venus-market/v2/api/clients"
 	"github.com/filecoin-project/venus-market/v2/api/impl"
 	cli2 "github.com/filecoin-project/venus-market/v2/cli"
@@ -14,6 +15,7 @@ import (
 	"github.com/filecoin-project/venus-market/v2/minermgr"
 	"github.com/filecoin-project/venus-market/v2/models"
 	"github.com/filecoin-project/venus-market/v2/network"
+	"github.com/filecoin-project/venus-market/v2/notify"
 	"github.com/filecoin-project/venus-market/v2/paychmgr"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	"github.com/filecoin-project/venus-market/v2/retrievalprovider"
@@ -58,6 +60,11 @@ func soloDaemon(cctx *cli.Context) error {
 	}
 	ctx := cctx.Context
 
+	localJwtClient, err := rpc.NewLocalJwtClient(cfg, &cfg.API)
+	if err != nil {
+		return fmt.Errorf("create local jwt client failed:%w", err)
+	}
+
 	resAPI := &impl.MarketNodeImpl{}
 	shutdownChan := make(chan struct{})
 	closeFunc, err := builder.New(ctx,
@@ -74,6 +81,7 @@ func soloDaemon(cctx *cli.Context) error {
 			return metrics2.CtxScope(context.Background(), "venus-market")
 		}),
 		builder.Override(new(types2.ShutdownChan), shutdownChan),
+		builder.Override(new(*rpc.JwtClient), localJwtClient),
 
 		// override marketconfig
 		builder.Override(new(config.MarketConfig), cfg),
@@ -92,6 +100,7 @@ func soloDaemon(cctx *cli.Context) error {
 		fundmgr.FundMgrOpts,
 		dagstore.DagstoreOpts,
 		paychmgr.PaychOpts,
+		notify.NotifyOpts,
 		// Markets
 		storageprovider.StorageProviderOpts(cfg),
 		retrievalprovider.RetrievalProviderOpts(cfg),
@@ -112,10 +121,19 @@ func soloDaemon(cctx *cli.Context) error {
 	finishCh := utils.MonitorShutdown(shutdownChan)
 
 	mux := mux.NewRouter()
-	mux.Handle("resource", rpc.NewPieceStorageServer(resAPI.PieceStorageMgr))
+	if err = mux.Handle("/resource", rpc.NewPieceStorageServer(resAPI.PieceStorageMgr, resAPI.PieceRepairWorker, resAPI.Repo.StorageDealRepo(), localJwtClient, &cfg.PieceStorage)).GetError(); err != nil {
+		return fmt.Errorf("handle 'resource' failed: %w", err)
+	}
+	if cfg.HttpRetrieval {
+		mux.PathPrefix("/ipfs/").Handler(resAPI.HttpRetrieval)
+		mux.PathPrefix("/piece/").Handler(resAPI.HttpRetrieval)
+	}
 
 	var fullAPI marketapi.IMarketStruct
 	permission.PermissionProxy(marketapi.IMarket(resAPI), &fullAPI)
 
-	return rpc.ServeRPC(ctx, cfg, &cfg.API, mux, 1000, cli2.API_NAMESPACE_VENUS_MARKET, nil, &fullAPI, finishCh)
+	var extAPI api.IMarketExtStruct
+	permission.PermissionProxy(api.IMarketExt(resAPI), &extAPI)
+
+	return rpc.ServeRPC(ctx, cfg, &cfg.API, mux, 1000, cli2.API_NAMESPACE_VENUS_MARKET, localJwtClient, nil, []interface{}{&fullAPI, &extAPI}, finishCh)
 }
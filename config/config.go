@@ -173,15 +173,61 @@ type MongoTopIndex struct {
 	Url string
 }
 
+const (
+	// PlacementRandom picks a random writable storage, honoring storage weight
+	PlacementRandom = "random"
+	// PlacementWeighted picks a storage randomly, weighted by free space multiplied by storage weight
+	PlacementWeighted = "weighted"
+	// PlacementRoundRobin cycles through writable storages in name order
+	PlacementRoundRobin = "round-robin"
+	// PlacementFillFirst keeps writing to the fullest storage until it runs out of space
+	PlacementFillFirst = "fill-first"
+)
+
 type PieceStorage struct {
+	// Placement policy used to choose a storage for new pieces,
+	// possible values: "random", "weighted", "round-robin", "fill-first".
+	// Default value: "random"
+	Placement string
+
+	// Replication policy of pieces across storages
+	Replication PieceReplication
+
+	// ScrubInterval is the interval between integrity checks of stored pieces, 0 disables the scheduled scrub
+	ScrubInterval Duration
+
+	// GCInterval is the interval between garbage collections of pieces whose deals have all ended, 0 disables the scheduled gc
+	GCInterval Duration
+	// GCGracePeriod is how long a piece is kept after its last deal expired
+	GCGracePeriod Duration
+
+	// RequireAuth makes the /resource endpoint only serve requests with a bearer token of read permission
+	// or a signed url issued for the piece, keep it false to allow anonymous download
+	RequireAuth bool
+
 	Fs []*FsPieceStorage
 	S3 []*S3PieceStorage
 }
 
+type PieceReplication struct {
+	// Copies is the number of copies each piece should have across storages, 0 and 1 both mean no replication
+	Copies int
+	// MinS3Copies is the minimum number of copies that should be placed on s3 storages
+	MinS3Copies int
+	// RepairInterval is the interval between scans for under-replicated pieces, 0 disables the repair worker
+	RepairInterval Duration
+}
+
 type FsPieceStorage struct {
 	Name     string
 	ReadOnly bool
 	Path     string
+
+	// Priority of the storage when placing new pieces, storages with higher priority
+	// are used first as long as they have enough space
+	Priority int
+	// Weight of the storage relative to storages with the same priority, 0 is treated as 1
+	Weight int
 }
 
 type S3PieceStorage struct {
@@ -192,6 +238,14 @@ type S3PieceStorage struct {
 	AccessKey string
 	SecretKey string
 	Token     string
+
+	// Priority of the storage when placing new pieces, storages with higher priority
+	// are used first as long as they have enough space
+	Priority int
+	// Weight of the storage relative to storages with the same priority, 0 is treated as 1
+	Weight int
+	// Quota limits the number of bytes stored in the bucket, 0 means unlimited
+	Quota int64
 }
 
 type User struct {
@@ -199,6 +253,35 @@ type User struct {
 	Account string
 }
 
+type MinerPaymentAddress struct {
+	Miner   Address
+	Addr    Address
+	Account string
+}
+
+type Webhook struct {
+	// Url receives events as json by POST, webhook is disabled if empty
+	Url string
+	// Secret is the key to sign the body with HMAC-SHA256, the signature is sent in header X-Venus-Market-Signature as sha256=<hex>
+	Secret string
+
+	// StorageDealStates are names of storage deal states to notify when deals enter them, e.g. "StorageDealActive",
+	// empty for all states
+	StorageDealStates []string
+	// When enabled, slashing of active deals is notified
+	DealSlashed bool
+	// When enabled, completion of retrieval deals is notified
+	RetrievalCompleted bool
+
+	// Timeout of a single delivery
+	Timeout Duration
+	// MinBackoff is the delay before the first retry of a failed delivery, doubled on each retry up to MaxBackoff
+	MinBackoff Duration
+	MaxBackoff Duration
+	// MaxAttempts is the number of deliveries before an event is dropped, 0 retries until delivered
+	MaxAttempts int
+}
+
 // StorageMiner is a miner config
 type MarketConfig struct {
 	Home `toml:"-"`
@@ -220,6 +303,10 @@ type MarketConfig struct {
 	StorageMiners           []User
 	RetrievalPaymentAddress User
 
+	// Payment addresses of retrievals for data stored by specified miners, retrievals of other miners
+	// are paid to RetrievalPaymentAddress
+	MinerRetrievalPaymentAddresses []MinerPaymentAddress
+
 	// When enabled, the miner can accept online deals
 	ConsiderOnlineStorageDeals bool
 	// When enabled, the miner can accept offline deals
@@ -273,8 +360,28 @@ type MarketConfig struct {
 
 	RetrievalPricing *RetrievalPricing
 
+	// When enabled, data of deals ready for retrieval can be retrieved for free over plain http,
+	// dags as CAR at /ipfs/<cid> and raw pieces at /piece/<pieceCid> of the api endpoint
+	HttpRetrieval bool
+
+	// Webhook posts events of deals to an external system
+	Webhook Webhook
+
 	MaxPublishDealsFee     types.FIL
 	MaxMarketBalanceAddFee types.FIL
+	// Publishing deals is held back while the base fee is above MaxPublishBaseFee, unless a pending deal
+	// would miss its start epoch given ExpectedSealDuration, 0 means no ceiling
+	MaxPublishBaseFee types.FIL
+}
+
+// RetrievalPaymentAddressOf return payment address of retrievals for data stored by the miner
+func (m *MarketConfig) RetrievalPaymentAddressOf(miner address.Address) address.Address {
+	for _, payment := range m.MinerRetrievalPaymentAddresses {
+		if address.Address(payment.Miner) == miner {
+			return address.Address(payment.Addr)
+		}
+	}
+	return address.Address(m.RetrievalPaymentAddress.Addr)
 }
 
 func (m *MarketConfig) RemovePieceStorage(name string) error {
@@ -315,6 +422,26 @@ type MarketClientConfig struct {
 	SimultaneousTransfersForRetrieval uint64
 	SimultaneousTransfersForStorage   uint64
 	DefaultMarketAddress              Address
+
+	// Renewal proposes replacement deals for active deals approaching their end
+	Renewal Renewal
+}
+
+// Renewal contains configs for renewing active deals of the client before they expire
+type Renewal struct {
+	// When enabled, active deals ending within Window are renewed with the same data
+	Enable bool
+	// Window is how long before the end of a deal it is renewed, it should be longer than the time miners take to
+	// start a deal
+	Window Duration
+	// Interval is how often deals are checked
+	Interval Duration
+	// DealDuration is the duration in epochs of renewed deals, the duration of the original deal is used if 0
+	DealDuration uint64
+	// FallbackMiners are tried when the miner of the original deal doesn't accept the renewal
+	FallbackMiners []Address
+	// MaxPrice is the highest ask price per GiB per epoch accepted for renewals, no limit if 0
+	MaxPrice types.FIL
 }
 
 var _ encoding.TextMarshaler = (*Duration)(nil)
@@ -60,7 +60,14 @@ var DefaultMarketConfig = &MarketConfig{
 	},
 	Journal: Journal{Path: "journal"},
 	PieceStorage: PieceStorage{
-		Fs: []*FsPieceStorage{},
+		Placement: PlacementRandom,
+		Replication: PieceReplication{
+			Copies:         1,
+			MinS3Copies:    0,
+			RepairInterval: Duration(time.Hour),
+		},
+		GCGracePeriod: Duration(time.Hour * 24 * 7),
+		Fs:            []*FsPieceStorage{},
 	},
 	ConsiderOnlineStorageDeals:     true,
 	ConsiderOfflineStorageDeals:    true,
@@ -91,8 +98,19 @@ var DefaultMarketConfig = &MarketConfig{
 		},
 	},
 
+	Webhook: Webhook{
+		StorageDealStates:  []string{},
+		DealSlashed:        true,
+		RetrievalCompleted: true,
+		Timeout:            Duration(10 * time.Second),
+		MinBackoff:         Duration(5 * time.Second),
+		MaxBackoff:         Duration(time.Hour),
+		MaxAttempts:        20,
+	},
+
 	MaxPublishDealsFee:     types.FIL(types.NewInt(0)),
 	MaxMarketBalanceAddFee: types.FIL(types.NewInt(0)),
+	MaxPublishBaseFee:      types.FIL(types.NewInt(0)),
 }
 
 var DefaultMarketClientConfig = &MarketClientConfig{
@@ -126,4 +144,12 @@ var DefaultMarketClientConfig = &MarketClientConfig{
 	DefaultMarketAddress:              Address(address.Undef),
 	SimultaneousTransfersForStorage:   DefaultSimultaneousTransfers,
 	SimultaneousTransfersForRetrieval: DefaultSimultaneousTransfers,
+
+	Renewal: Renewal{
+		Enable:         false,
+		Window:         Duration(14 * 24 * time.Hour),
+		Interval:       Duration(time.Hour),
+		FallbackMiners: []Address{},
+		MaxPrice:       types.FIL(types.NewInt(0)),
+	},
 }
@@ -4,6 +4,7 @@ import (
 	"context"
 	"time"
 
+	"github.com/filecoin-project/go-fil-markets/retrievalmarket"
 	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 	"github.com/ipfs/go-cid"
@@ -79,3 +80,6 @@ type GetExpectedSealDurationFunc func() (time.Duration, error)
 
 type StorageDealFilter func(ctx context.Context, deal storagemarket.MinerDeal) (bool, string, error)
 type RetrievalDealFilter func(ctx context.Context, deal types.ProviderDealState) (bool, string, error)
+
+// RetrievalPricingFunc compute the ask of a retrieval with the current ask and information of the deal and piece
+type RetrievalPricingFunc func(ctx context.Context, pricingInput retrievalmarket.PricingInput) (retrievalmarket.Ask, error)
@@ -2,19 +2,46 @@ package dagstore
 
 import (
 	"context"
+	"errors"
 	"fmt"
 	"io"
+	"sync"
+	"time"
 
+	lru "github.com/hashicorp/golang-lru"
 	"github.com/ipfs/go-cid"
+	logging "github.com/ipfs/go-log/v2"
 
 	"github.com/filecoin-project/dagstore/mount"
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	"github.com/filecoin-project/go-padreader"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/specs-storage/storage"
+	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
+	vTypes "github.com/filecoin-project/venus/venus-shared/types"
+	markettypes "github.com/filecoin-project/venus/venus-shared/types/market"
 
+	"github.com/filecoin-project/venus-market/v2/api/clients"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	"github.com/filecoin-project/venus-market/v2/utils"
 )
 
+var log = logging.Logger("dagstore")
+
+var (
+	// unsealTimeout is the max time to wait for the piece to show up in piece storage after requesting miner to unseal
+	unsealTimeout = time.Hour
+	// unsealCheckInterval is the interval to check whether the unsealed piece has been written to piece storage
+	unsealCheckInterval = time.Second * 10
+	// unsealedCacheTTL is how long the unseal state of a piece queried from miners is kept
+	unsealedCacheTTL = time.Minute * 5
+)
+
+// unsealedCacheSize is the number of pieces to keep unseal state queried from miners
+const unsealedCacheSize = 1024
+
 type MarketAPI interface {
 	FetchUnsealedPiece(ctx context.Context, pieceCid cid.Cid) (mount.Reader, error)
 	GetUnpaddedCARSize(ctx context.Context, pieceCid cid.Cid) (uint64, error)
@@ -26,15 +53,41 @@ type marketAPI struct {
 	pieceStorageMgr *piecestorage.PieceStorageManager
 	pieceRepo       repo.StorageDealRepo
 	useTransient    bool
+
+	// fullNode and requestEvent are used to unseal pieces missing in piece storage, requestEvent only exists in solo mode
+	fullNode     v1api.FullNode
+	requestEvent clients.MarketRequestEvent
+
+	unsealLk  sync.Mutex
+	unsealing map[cid.Cid]*unsealTask
+
+	// unsealedCache maps piece cid to unsealedState, avoid querying chain and miners on every retrieval query
+	unsealedCache *lru.Cache
+}
+
+type unsealedState struct {
+	unsealed bool
+	expire   time.Time
+}
+
+// unsealTask is shared by concurrent requests for the same piece, done is closed once the unseal finished
+type unsealTask struct {
+	done chan struct{}
+	err  error
 }
 
 var _ MarketAPI = (*marketAPI)(nil)
 
-func NewMarketAPI(repo repo.Repo, pieceStorageMgr *piecestorage.PieceStorageManager, useTransient bool) MarketAPI {
+func NewMarketAPI(repo repo.Repo, pieceStorageMgr *piecestorage.PieceStorageManager, useTransient bool, fullNode v1api.FullNode, requestEvent clients.MarketRequestEvent) MarketAPI {
+	unsealedCache, _ := lru.New(unsealedCacheSize)
 	return &marketAPI{
 		pieceRepo:       repo.StorageDealRepo(),
 		pieceStorageMgr: pieceStorageMgr,
 		useTransient:    useTransient,
+		fullNode:        fullNode,
+		requestEvent:    requestEvent,
+		unsealing:       make(map[cid.Cid]*unsealTask),
+		unsealedCache:   unsealedCache,
 	}
 }
 
@@ -43,12 +96,51 @@ func (m *marketAPI) Start(_ context.Context) error {
 }
 
 func (m *marketAPI) IsUnsealed(ctx context.Context, pieceCid cid.Cid) (bool, error) {
-	_, err := m.pieceStorageMgr.FindStorageForRead(ctx, pieceCid.String())
+	if _, err := m.pieceStorageMgr.FindStorageForRead(ctx, pieceCid.String()); err == nil {
+		return true, nil
+	}
+	if m.requestEvent == nil {
+		return false, nil
+	}
+
+	if val, ok := m.unsealedCache.Get(pieceCid); ok {
+		if state := val.(unsealedState); time.Now().Before(state.expire) {
+			return state.unsealed, nil
+		}
+	}
+	unsealed, err := m.isUnsealedInSector(ctx, pieceCid)
+	if err != nil {
+		return false, err
+	}
+	m.unsealedCache.Add(pieceCid, unsealedState{unsealed: unsealed, expire: time.Now().Add(unsealedCacheTTL)})
+	return unsealed, nil
+}
+
+// isUnsealedInSector check whether any miner has an unsealed copy of piece in its sector
+func (m *marketAPI) isUnsealedInSector(ctx context.Context, pieceCid cid.Cid) (bool, error) {
+	deals, err := m.pieceRepo.GetDealsByPieceCidAndStatus(ctx, pieceCid, storagemarket.StorageDealActive)
 	if err != nil {
-		return false, fmt.Errorf("unable to find storage for piece %s %w", pieceCid, err)
+		if errors.Is(err, repo.ErrNotFound) {
+			return false, nil
+		}
+		return false, fmt.Errorf("get active deals of piece %s: %w", pieceCid, err)
+	}
+	for _, deal := range deals {
+		sector, err := m.sectorRef(ctx, deal)
+		if err != nil {
+			log.Warnf("get sector of deal %d for piece %s: %v", deal.DealID, pieceCid, err)
+			continue
+		}
+		isUnsealed, err := m.requestEvent.IsUnsealed(ctx, deal.Proposal.Provider, pieceCid, sector, vTypes.PaddedByteIndex(deal.Offset), deal.Proposal.PieceSize)
+		if err != nil {
+			log.Warnf("check unseal state of piece %s in sector %d of miner %s: %v", pieceCid, deal.SectorNumber, deal.Proposal.Provider, err)
+			continue
+		}
+		if isUnsealed {
+			return true, nil
+		}
 	}
-	return true, nil
-	//todo check isunseal from miner
+	return false, nil
 }
 
 func (m *marketAPI) FetchUnsealedPiece(ctx context.Context, pieceCid cid.Cid) (mount.Reader, error) {
@@ -59,7 +151,13 @@ func (m *marketAPI) FetchUnsealedPiece(ctx context.Context, pieceCid cid.Cid) (m
 
 	pieceStorage, err := m.pieceStorageMgr.FindStorageForRead(ctx, pieceCid.String())
 	if err != nil {
-		return nil, err
+		if err = m.unsealPiece(ctx, pieceCid); err != nil {
+			return nil, fmt.Errorf("unseal piece %s: %w", pieceCid, err)
+		}
+		pieceStorage, err = m.pieceStorageMgr.FindStorageForRead(ctx, pieceCid.String())
+		if err != nil {
+			return nil, err
+		}
 	}
 	if m.useTransient {
 		//only need reader stream
@@ -70,9 +168,18 @@ func (m *marketAPI) FetchUnsealedPiece(ctx context.Context, pieceCid cid.Cid) (m
 
 		padR, err := padreader.NewInflator(r, payloadSize, pieceSize.Unpadded())
 		if err != nil {
+			_ = r.Close()
 			return nil, err
 		}
-		return &mountWrapper{r, padR}, nil
+		// random access is served by a ranged reader opened on demand, s3 storage read range by range instead of whole piece
+		openRandom := func() (mount.Reader, error) {
+			mr, err := pieceStorage.GetMountReader(context.Background(), pieceCid.String())
+			if err != nil {
+				return nil, err
+			}
+			return utils.NewAlgnZeroMountReader(mr, int(payloadSize), int(pieceSize.Unpadded())), nil
+		}
+		return newMountWrapper(r, padR, int64(pieceSize.Unpadded()), openRandom), nil
 	}
 	//must support seek/readeat
 	r, err := pieceStorage.GetMountReader(ctx, pieceCid.String())
@@ -98,24 +205,220 @@ func (m *marketAPI) GetUnpaddedCARSize(ctx context.Context, pieceCid cid.Cid) (u
 	return uint64(len), nil
 }
 
+// unsealPiece request the miner of piece to unseal it into a writable piece storage and wait until it's done,
+// concurrent requests for the same piece share one unseal
+func (m *marketAPI) unsealPiece(ctx context.Context, pieceCid cid.Cid) error {
+	m.unsealLk.Lock()
+	task, ok := m.unsealing[pieceCid]
+	if !ok {
+		task = &unsealTask{done: make(chan struct{})}
+		m.unsealing[pieceCid] = task
+		go func() {
+			// not bound to ctx of the first request, which may be cancelled while others are still waiting
+			task.err = m.doUnseal(context.Background(), pieceCid)
+			m.unsealLk.Lock()
+			delete(m.unsealing, pieceCid)
+			m.unsealLk.Unlock()
+			close(task.done)
+		}()
+	}
+	m.unsealLk.Unlock()
+
+	select {
+	case <-task.done:
+		return task.err
+	case <-ctx.Done():
+		return ctx.Err()
+	}
+}
+
+func (m *marketAPI) doUnseal(ctx context.Context, pieceCid cid.Cid) error {
+	if m.requestEvent == nil {
+		return fmt.Errorf("piece not found in piece storage and unseal is unavailable")
+	}
+
+	deals, err := m.pieceRepo.GetDealsByPieceCidAndStatus(ctx, pieceCid, storagemarket.StorageDealActive)
+	if err != nil {
+		return fmt.Errorf("get active deals: %w", err)
+	}
+
+	lastErr := fmt.Errorf("no active deal found")
+	for _, deal := range deals {
+		if lastErr = m.unsealFromDeal(ctx, pieceCid, deal); lastErr == nil {
+			return nil
+		}
+		log.Warnf("unseal piece %s from sector %d of miner %s: %v", pieceCid, deal.SectorNumber, deal.Proposal.Provider, lastErr)
+	}
+	return lastErr
+}
+
+func (m *marketAPI) unsealFromDeal(ctx context.Context, pieceCid cid.Cid, deal *markettypes.MinerDeal) error {
+	sector, err := m.sectorRef(ctx, deal)
+	if err != nil {
+		return err
+	}
+
+	pieceStorage, err := m.pieceStorageMgr.FindStorageForWrite(int64(deal.Proposal.PieceSize))
+	if err != nil {
+		return fmt.Errorf("find storage to write unsealed piece: %w", err)
+	}
+	dest, err := pieceStorage.GetPieceTransfer(ctx, pieceCid.String())
+	if err != nil {
+		return fmt.Errorf("get transfer destination from storage %s: %w", pieceStorage.GetName(), err)
+	}
+
+	log.Infof("request miner %s to unseal piece %s in sector %d to storage %s", deal.Proposal.Provider, pieceCid, deal.SectorNumber, pieceStorage.GetName())
+	if err = m.requestEvent.SectorsUnsealPiece(ctx, deal.Proposal.Provider, pieceCid, sector, vTypes.PaddedByteIndex(deal.Offset), deal.Proposal.PieceSize, dest); err != nil {
+		return fmt.Errorf("request unseal: %w", err)
+	}
+
+	// miner writes to piece storage directly, wait until the whole piece is written, which is padded or unpadded
+	timeoutCtx, cancel := context.WithTimeout(ctx, unsealTimeout)
+	defer cancel()
+	ticker := time.NewTicker(unsealCheckInterval)
+	defer ticker.Stop()
+	for {
+		size, err := m.unsealedSize(timeoutCtx, pieceStorage, pieceCid)
+		if err != nil {
+			log.Warnf("check unsealed piece %s in storage %s: %v", pieceCid, pieceStorage.GetName(), err)
+		}
+		if size == int64(deal.Proposal.PieceSize) || size == int64(deal.Proposal.PieceSize.Unpadded()) {
+			log.Infof("piece %s unsealed to storage %s", pieceCid, pieceStorage.GetName())
+			return nil
+		}
+
+		select {
+		case <-ticker.C:
+		case <-timeoutCtx.Done():
+			return fmt.Errorf("wait for unsealed piece in storage %s: %w", pieceStorage.GetName(), timeoutCtx.Err())
+		}
+	}
+}
+
+// unsealedSize return the size of piece written to storage so far, 0 if not written yet
+func (m *marketAPI) unsealedSize(ctx context.Context, pieceStorage piecestorage.IPieceStorage, pieceCid cid.Cid) (int64, error) {
+	has, err := pieceStorage.Has(ctx, pieceCid.String())
+	if err != nil || !has {
+		return 0, err
+	}
+	return pieceStorage.Len(ctx, pieceCid.String())
+}
+
+func (m *marketAPI) sectorRef(ctx context.Context, deal *markettypes.MinerDeal) (storage.SectorRef, error) {
+	mid, err := address.IDFromAddress(deal.Proposal.Provider)
+	if err != nil {
+		return storage.SectorRef{}, err
+	}
+
+	sectorInfo, err := m.fullNode.StateSectorGetInfo(ctx, deal.Proposal.Provider, deal.SectorNumber, vTypes.EmptyTSK)
+	if err != nil {
+		return storage.SectorRef{}, fmt.Errorf("get info of sector %d: %w", deal.SectorNumber, err)
+	}
+	if sectorInfo == nil {
+		return storage.SectorRef{}, fmt.Errorf("sector %d not found on chain", deal.SectorNumber)
+	}
+
+	return storage.SectorRef{
+		ID: abi.SectorID{
+			Miner:  abi.ActorID(mid),
+			Number: deal.SectorNumber,
+		},
+		ProofType: sectorInfo.SealProof,
+	}, nil
+}
+
+// mountWrapper read piece stream sequentially, and fallback to a ranged reader once random access is required
 type mountWrapper struct {
 	closeR io.ReadCloser
 	readR  io.Reader
+
+	size int64
+	// streamPos is the offset of next byte in sequential stream, pos is the offset of next Read
+	streamPos int64
+	pos       int64
+
+	// ReadAt may be called concurrently, randomLk guards the lazily opened ranged reader
+	randomLk   sync.Mutex
+	openRandom func() (mount.Reader, error)
+	random     mount.Reader
 }
 
 var _ mount.Reader = (*mountWrapper)(nil)
 
+func newMountWrapper(closeR io.ReadCloser, readR io.Reader, size int64, openRandom func() (mount.Reader, error)) *mountWrapper {
+	return &mountWrapper{
+		closeR:     closeR,
+		readR:      readR,
+		size:       size,
+		openRandom: openRandom,
+	}
+}
+
 func (r *mountWrapper) ReadAt(p []byte, off int64) (n int, err error) {
-	return 0, fmt.Errorf("ReadAt called but not implemented")
+	if off >= r.size {
+		return 0, io.EOF
+	}
+	random, err := r.randomReader()
+	if err != nil {
+		return 0, err
+	}
+	return random.ReadAt(p, off)
+}
+
+func (r *mountWrapper) randomReader() (mount.Reader, error) {
+	r.randomLk.Lock()
+	defer r.randomLk.Unlock()
+	if r.random == nil {
+		random, err := r.openRandom()
+		if err != nil {
+			return nil, fmt.Errorf("open ranged reader: %w", err)
+		}
+		r.random = random
+	}
+	return r.random, nil
 }
 
 func (r *mountWrapper) Seek(offset int64, whence int) (int64, error) {
-	return 0, fmt.Errorf("Seek called but not implemented")
+	var pos int64
+	switch whence {
+	case io.SeekStart:
+		pos = offset
+	case io.SeekCurrent:
+		pos = r.pos + offset
+	case io.SeekEnd:
+		pos = r.size + offset
+	default:
+		return 0, fmt.Errorf("invalid whence %d", whence)
+	}
+	if pos < 0 {
+		return 0, fmt.Errorf("negative position %d", pos)
+	}
+	r.pos = pos
+	return pos, nil
 }
+
 func (r *mountWrapper) Read(p []byte) (n int, err error) {
-	return r.readR.Read(p)
+	if r.pos != r.streamPos {
+		// stream can't go backward, read at the position instead
+		n, err = r.ReadAt(p, r.pos)
+		r.pos += int64(n)
+		return n, err
+	}
+	n, err = r.readR.Read(p)
+	r.streamPos += int64(n)
+	r.pos = r.streamPos
+	return n, err
 }
 
 func (r *mountWrapper) Close() error {
-	return r.closeR.Close()
+	var err error
+	r.randomLk.Lock()
+	if r.random != nil {
+		err = r.random.Close()
+	}
+	r.randomLk.Unlock()
+	if cerr := r.closeR.Close(); cerr != nil {
+		err = cerr
+	}
+	return err
 }
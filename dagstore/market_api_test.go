@@ -3,19 +3,29 @@ package dagstore
 import (
 	"bytes"
 	"context"
+	"fmt"
+	"io"
 	"strings"
+	"sync"
 	"testing"
+	"time"
 
 	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	"github.com/filecoin-project/go-state-types/abi"
 	"github.com/filecoin-project/go-state-types/builtin/v8/market"
+	"github.com/filecoin-project/go-state-types/builtin/v8/miner"
 	acrypto "github.com/filecoin-project/go-state-types/crypto"
+	"github.com/filecoin-project/specs-storage/storage"
 	"github.com/filecoin-project/venus-market/v2/config"
 	"github.com/filecoin-project/venus-market/v2/models"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	"github.com/filecoin-project/venus-market/v2/utils/test_helper"
+	vTypes "github.com/filecoin-project/venus/venus-shared/types"
 	markettypes "github.com/filecoin-project/venus/venus-shared/types/market"
 	"github.com/ipfs/go-cid"
 	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
 )
 
 func TestMarket(t *testing.T) {
@@ -53,7 +63,7 @@ func TestMarket(t *testing.T) {
 	_, err = memPieceStorage.SaveTo(ctx, testResourceId.String(), payloadWriter)
 	assert.Nil(t, err)
 
-	marketAPI := NewMarketAPI(r, pmgr, false)
+	marketAPI := NewMarketAPI(r, pmgr, false, nil, nil)
 
 	size, err := marketAPI.GetUnpaddedCARSize(ctx, testResourceId)
 	assert.Nil(t, err)
@@ -62,3 +72,201 @@ func TestMarket(t *testing.T) {
 	_, err = marketAPI.FetchUnsealedPiece(ctx, testResourceId)
 	assert.Nil(t, err)
 }
+
+func TestMarketTransientReader(t *testing.T) {
+	ctx := context.Background()
+	payload := "0123456789"
+	flen := abi.PaddedPieceSize(128)
+	pieceCid, _ := cid.Decode("baga6ea4seaqd6cvb2padh74lthhiay4jtlwqhj2qetbj5cipna6jlkmcrdljulq")
+	proposalCid, _ := cid.Decode("bafy2bzacecqwr2ggwu62ao246wzilhba5dvbocjwxxwyb2zn3wl7rgk2wsx3k")
+
+	memPieceStorage := piecestorage.NewMemPieceStore("", nil)
+	pmgr, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+	require.NoError(t, err)
+	pmgr.AddMemPieceStorage(memPieceStorage)
+
+	r := models.NewInMemoryRepo()
+	require.NoError(t, r.StorageDealRepo().SaveDeal(ctx, &markettypes.MinerDeal{
+		ClientDealProposal: market.ClientDealProposal{
+			Proposal: market.DealProposal{
+				Provider:  address.TestAddress,
+				Client:    address.TestAddress,
+				PieceCID:  pieceCid,
+				PieceSize: flen,
+			},
+			ClientSignature: acrypto.Signature{
+				Type: acrypto.SigTypeBLS,
+				Data: make([]byte, 10),
+			},
+		},
+		ProposalCid: proposalCid,
+		PayloadSize: uint64(len(payload)),
+	}))
+	_, err = memPieceStorage.SaveTo(ctx, pieceCid.String(), bytes.NewBufferString(payload))
+	require.NoError(t, err)
+
+	marketAPI := NewMarketAPI(r, pmgr, true, nil, nil)
+	reader, err := marketAPI.FetchUnsealedPiece(ctx, pieceCid)
+	require.NoError(t, err)
+	defer reader.Close() //nolint:errcheck
+
+	// sequential read from stream
+	buf := make([]byte, 4)
+	_, err = io.ReadFull(reader, buf)
+	require.NoError(t, err)
+	assert.Equal(t, "0123", string(buf))
+
+	// random read
+	n, err := reader.ReadAt(buf, 6)
+	require.NoError(t, err)
+	assert.Equal(t, 4, n)
+	assert.Equal(t, "6789", string(buf))
+
+	// read past payload is padded with zero
+	n, err = reader.ReadAt(buf, 8)
+	require.NoError(t, err)
+	assert.Equal(t, 4, n)
+	assert.Equal(t, []byte{'8', '9', 0, 0}, buf)
+
+	// seek backward then read
+	pos, err := reader.Seek(1, io.SeekStart)
+	require.NoError(t, err)
+	assert.Equal(t, int64(1), pos)
+	_, err = io.ReadFull(reader, buf)
+	require.NoError(t, err)
+	assert.Equal(t, "1234", string(buf))
+
+	pos, err = reader.Seek(0, io.SeekEnd)
+	require.NoError(t, err)
+	assert.Equal(t, int64(flen.Unpadded()), pos)
+	_, err = reader.Read(buf)
+	assert.Equal(t, io.EOF, err)
+}
+
+type mockSectorFullNode struct {
+	test_helper.MockFullnode
+}
+
+func (m mockSectorFullNode) StateSectorGetInfo(ctx context.Context, maddr address.Address, n abi.SectorNumber, tsk vTypes.TipSetKey) (*miner.SectorOnChainInfo, error) {
+	return &miner.SectorOnChainInfo{SectorNumber: n, SealProof: abi.RegisteredSealProof_StackedDrg2KiBV1_1}, nil
+}
+
+// mockRequestEvent act as a miner which write the unsealed piece to piece storage
+type mockRequestEvent struct {
+	lk       sync.Mutex
+	checked  int
+	unsealed int
+	store    *piecestorage.MemPieceStore
+	content  string
+}
+
+func (m *mockRequestEvent) IsUnsealed(ctx context.Context, miner address.Address, pieceCid cid.Cid, sector storage.SectorRef, offset vTypes.PaddedByteIndex, size abi.PaddedPieceSize) (bool, error) {
+	m.lk.Lock()
+	m.checked++
+	m.lk.Unlock()
+	return true, nil
+}
+
+func (m *mockRequestEvent) SectorsUnsealPiece(ctx context.Context, miner address.Address, pieceCid cid.Cid, sector storage.SectorRef, offset vTypes.PaddedByteIndex, size abi.PaddedPieceSize, dest string) error {
+	if dest != fmt.Sprintf("mock transfer resourceId %s", pieceCid) {
+		return fmt.Errorf("unexpected dest %s", dest)
+	}
+	m.lk.Lock()
+	m.unsealed++
+	m.lk.Unlock()
+
+	// write the piece in two steps, a partially written piece must not be taken as unsealed
+	go func() {
+		time.Sleep(time.Millisecond * 50)
+		_, _ = m.store.SaveTo(context.Background(), pieceCid.String(), bytes.NewBufferString(m.content[:len(m.content)/2]))
+		time.Sleep(time.Millisecond * 50)
+		_, _ = m.store.SaveTo(context.Background(), pieceCid.String(), bytes.NewBufferString(m.content))
+	}()
+	return nil
+}
+
+func TestMarketUnseal(t *testing.T) {
+	ctx := context.Background()
+	unsealCheckInterval = time.Millisecond * 10
+
+	payloadSize := 10
+	flen := abi.PaddedPieceSize(128)
+	pieceCid, _ := cid.Decode("baga6ea4seaqd6cvb2padh74lthhiay4jtlwqhj2qetbj5cipna6jlkmcrdljulq")
+	proposalCid, _ := cid.Decode("bafy2bzacecqwr2ggwu62ao246wzilhba5dvbocjwxxwyb2zn3wl7rgk2wsx3k")
+	provider, err := address.NewIDAddress(1000)
+	require.NoError(t, err)
+
+	memPieceStorage := piecestorage.NewMemPieceStore("", nil)
+	pmgr, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+	require.NoError(t, err)
+	pmgr.AddMemPieceStorage(memPieceStorage)
+
+	r := models.NewInMemoryRepo()
+	require.NoError(t, r.StorageDealRepo().SaveDeal(ctx, &markettypes.MinerDeal{
+		ClientDealProposal: market.ClientDealProposal{
+			Proposal: market.DealProposal{
+				Provider:  provider,
+				Client:    address.TestAddress,
+				PieceCID:  pieceCid,
+				PieceSize: flen,
+			},
+			ClientSignature: acrypto.Signature{
+				Type: acrypto.SigTypeBLS,
+				Data: make([]byte, 10),
+			},
+		},
+		ProposalCid:  proposalCid,
+		PayloadSize:  uint64(payloadSize),
+		SectorNumber: 1,
+		State:        storagemarket.StorageDealActive,
+	}))
+
+	t.Run("unseal unavailable", func(t *testing.T) {
+		marketAPI := NewMarketAPI(r, pmgr, false, nil, nil)
+		isUnsealed, err := marketAPI.IsUnsealed(ctx, pieceCid)
+		require.NoError(t, err)
+		assert.False(t, isUnsealed)
+
+		_, err = marketAPI.FetchUnsealedPiece(ctx, pieceCid)
+		assert.Error(t, err)
+	})
+
+	t.Run("unseal from miner", func(t *testing.T) {
+		event := &mockRequestEvent{store: memPieceStorage, content: strings.Repeat("1", int(flen.Unpadded()))}
+		marketAPI := NewMarketAPI(r, pmgr, false, mockSectorFullNode{}, event)
+
+		isUnsealed, err := marketAPI.IsUnsealed(ctx, pieceCid)
+		require.NoError(t, err)
+		assert.True(t, isUnsealed)
+
+		// unseal state is cached
+		isUnsealed, err = marketAPI.IsUnsealed(ctx, pieceCid)
+		require.NoError(t, err)
+		assert.True(t, isUnsealed)
+		assert.Equal(t, 1, event.checked)
+
+		// concurrent fetches of the same piece trigger only one unseal
+		var wg sync.WaitGroup
+		errs := make([]error, 5)
+		for i := range errs {
+			wg.Add(1)
+			go func(i int) {
+				defer wg.Done()
+				reader, err := marketAPI.FetchUnsealedPiece(ctx, pieceCid)
+				if err == nil {
+					err = reader.Close()
+				}
+				errs[i] = err
+			}(i)
+		}
+		wg.Wait()
+		for _, err := range errs {
+			assert.NoError(t, err)
+		}
+		assert.Equal(t, 1, event.unsealed)
+
+		size, err := memPieceStorage.Len(ctx, pieceCid.String())
+		require.NoError(t, err)
+		assert.Equal(t, int64(flen.Unpadded()), size)
+	})
+}
@@ -14,6 +14,9 @@ import (
 
 	"github.com/ipfs-force-community/venus-common-utils/builder"
 
+	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
+
+	"github.com/filecoin-project/venus-market/v2/api/clients"
 	"github.com/filecoin-project/venus-market/v2/config"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
@@ -29,9 +32,20 @@ const (
 	DefaultDAGStoreDir         = "dagstore"
 )
 
+type MarketAPIParams struct {
+	fx.In
+	Lc           fx.Lifecycle
+	Cfg          *config.DAGStoreConfig
+	Repo         repo.Repo
+	PieceStorage *piecestorage.PieceStorageManager
+	FullNode     v1api.FullNode
+	RequestEvent clients.MarketRequestEvent `optional:"true"`
+}
+
 // NewMarketAPI creates a new MarketAPI adaptor for the dagstore mounts.
-func CreateAndStartMarketAPI(lc fx.Lifecycle, r *config.DAGStoreConfig, repo repo.Repo, pieceStorage *piecestorage.PieceStorageManager) (MarketAPI, error) {
-	mountApi := NewMarketAPI(repo, pieceStorage, r.UseTransient)
+func CreateAndStartMarketAPI(params MarketAPIParams) (MarketAPI, error) {
+	lc := params.Lc
+	mountApi := NewMarketAPI(params.Repo, params.PieceStorage, params.Cfg.UseTransient, params.FullNode, params.RequestEvent)
 	lc.Append(fx.Hook{
 		OnStart: func(ctx context.Context) error {
 			return mountApi.Start(ctx)
@@ -16,9 +16,11 @@ import (
 	measure "github.com/ipfs/go-ds-measure"
 	logging "github.com/ipfs/go-log/v2"
 	ldbopts "github.com/syndtr/goleveldb/leveldb/opt"
+	"go.opencensus.io/tag"
 
 	"github.com/filecoin-project/go-statemachine/fsm"
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	carindex "github.com/ipld/go-car/v2/index"
 
 	"github.com/filecoin-project/dagstore"
@@ -210,12 +212,18 @@ func (w *Wrapper) gcLoop() {
 	}
 }
 
-func (w *Wrapper) LoadShard(ctx context.Context, pieceCid cid.Cid) (stores.ClosableBlockstore, error) {
+func (w *Wrapper) LoadShard(ctx context.Context, pieceCid cid.Cid) (_ stores.ClosableBlockstore, err error) {
 	log.Debugf("acquiring shard for piece CID %s", pieceCid)
 
+	start := time.Now()
+	defer func() {
+		metrics.Record(ctx, []tag.Mutator{tag.Upsert(metrics.Result, metrics.ResultOf(err))},
+			metrics.ShardAcquireDuration.M(metrics.SinceInMilliseconds(start)))
+	}()
+
 	key := shard.KeyFromCID(pieceCid)
 	resch := make(chan dagstore.ShardResult, 1)
-	err := w.dagst.AcquireShard(ctx, key, resch, dagstore.AcquireOpts{})
+	err = w.dagst.AcquireShard(ctx, key, resch, dagstore.AcquireOpts{})
 	log.Debugf("sent message to acquire shard for piece CID %s", pieceCid)
 
 	if err != nil {
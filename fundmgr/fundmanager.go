@@ -3,9 +3,11 @@ package fundmgr
 import (
 	"context"
 	"fmt"
+	stdbig "math/big"
 	"sync"
 
 	"github.com/filecoin-project/venus-market/v2/api/clients"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	"github.com/filecoin-project/venus/venus-shared/actors"
 
 	"github.com/filecoin-project/venus-market/v2/models/repo"
@@ -21,6 +23,7 @@ import (
 	types2 "github.com/filecoin-project/venus/venus-shared/types"
 	"github.com/ipfs/go-cid"
 	logging "github.com/ipfs/go-log/v2"
+	"go.opencensus.io/tag"
 	"go.uber.org/fx"
 )
 
@@ -126,12 +129,30 @@ func (fm *FundManager) getFundedAddress(addr address.Address) *fundedAddress {
 // Returns the cid of the message that was submitted on chain, or cid.Undef if
 // the required funds were already available.
 func (fm *FundManager) Reserve(ctx context.Context, wallet, addr address.Address, amt abi.TokenAmount) (cid.Cid, error) {
-	return fm.getFundedAddress(addr).reserve(ctx, wallet, amt)
+	msgCid, err := fm.getFundedAddress(addr).reserve(ctx, wallet, amt)
+	metrics.Record(ctx, []tag.Mutator{tag.Upsert(metrics.Result, metrics.ResultOf(err))}, metrics.FundReserveRequest.M(1))
+	return msgCid, err
 }
 
 // Subtract from `reserved`.
 func (fm *FundManager) Release(addr address.Address, amt abi.TokenAmount) error {
-	return fm.getFundedAddress(addr).release(amt)
+	err := fm.getFundedAddress(addr).release(amt)
+	metrics.Record(fm.ctx, []tag.Mutator{tag.Upsert(metrics.Result, metrics.ResultOf(err))}, metrics.FundReleaseRequest.M(1))
+	return err
+}
+
+// ReleaseAsync queues a release of amt and returns without waiting for it, as processing the release waits
+// for any pending message of the address. Reservations requested after it are covered by the released amount.
+// The result of the release is sent on the returned channel.
+func (fm *FundManager) ReleaseAsync(addr address.Address, amt abi.TokenAmount) <-chan error {
+	req := fm.getFundedAddress(addr).releaseAsync(amt)
+	errCh := make(chan error, 1)
+	go func() {
+		r := <-req.Result
+		metrics.Record(fm.ctx, []tag.Mutator{tag.Upsert(metrics.Result, metrics.ResultOf(r.err))}, metrics.FundReleaseRequest.M(1))
+		errCh <- r.err
+	}()
+	return errCh
 }
 
 // Withdraw unreserved funds. Only succeeds if there are enough unreserved
@@ -216,20 +237,16 @@ func (a *fundedAddress) release(amt abi.TokenAmount) error {
 	return err
 }
 
+func (a *fundedAddress) releaseAsync(amt abi.TokenAmount) *fundRequest {
+	return a.request(context.Background(), address.Undef, amt, &a.releases)
+}
+
 func (a *fundedAddress) withdraw(ctx context.Context, wallet address.Address, amt abi.TokenAmount) (cid.Cid, error) {
 	return a.requestAndWait(ctx, wallet, amt, &a.withdrawals)
 }
 
 func (a *fundedAddress) requestAndWait(ctx context.Context, wallet address.Address, amt abi.TokenAmount, reqs *[]*fundRequest) (cid.Cid, error) {
-	// Create a request and add it to the request queue
-	req := newFundRequest(ctx, wallet, amt)
-
-	a.lk.Lock()
-	*reqs = append(*reqs, req)
-	a.lk.Unlock()
-
-	// Process the queue
-	go a.process(ctx)
+	req := a.request(ctx, wallet, amt, reqs)
 
 	// Wait for the results
 	select {
@@ -240,6 +257,19 @@ func (a *fundedAddress) requestAndWait(ctx context.Context, wallet address.Addre
 	}
 }
 
+// request creates a request, adds it to the request queue and starts processing the queue
+func (a *fundedAddress) request(ctx context.Context, wallet address.Address, amt abi.TokenAmount, reqs *[]*fundRequest) *fundRequest {
+	req := newFundRequest(ctx, wallet, amt)
+
+	a.lk.Lock()
+	*reqs = append(*reqs, req)
+	a.lk.Unlock()
+
+	// Process the queue
+	go a.process(ctx)
+	return req
+}
+
 // Used by the tests
 //lint:ignore U1000 ingore this for now
 func (a *fundedAddress) onProcessStart(fn func() bool) {
@@ -321,6 +351,9 @@ func (a *fundedAddress) applyStateChange(ctx context.Context, msgCid *cid.Cid, a
 	a.state.MsgCid = msgCid
 	if !amtReserved.Nil() {
 		a.state.AmtReserved = amtReserved
+
+		reserved, _ := new(stdbig.Float).SetInt(amtReserved.Int).Float64()
+		metrics.Record(ctx, []tag.Mutator{tag.Upsert(metrics.Address, a.state.Addr.String())}, metrics.FundReserved.M(reserved))
 	}
 	a.saveState(ctx)
 }
@@ -108,6 +108,45 @@ func TestFundManagerBasic(t *testing.T) {
 	require.Error(t, err)
 }
 
+// TestFundManagerReleaseAsync verifies that a release queued while a message is pending covers the
+// reservations requested after it
+func TestFundManagerReleaseAsync(t *testing.T) {
+	s := setup(t)
+	defer s.fm.Stop()
+
+	// Reserve 10, the message is pending
+	amt := abi.NewTokenAmount(10)
+	sentinel, err := s.fm.Reserve(s.ctx, s.walletAddr, s.acctAddr, amt)
+	require.NoError(t, err)
+	msgCount := s.mockApi.messageCount()
+
+	// Release 10 returns before the message lands
+	errCh := s.fm.ReleaseAsync(s.acctAddr, amt)
+	select {
+	case <-errCh:
+		require.Fail(t, "release should wait for the pending message")
+	default:
+	}
+
+	// Reserve 10 after the release is covered by it
+	type reserveResult struct {
+		msgCid cid.Cid
+		err    error
+	}
+	reserved := make(chan reserveResult, 1)
+	go func() {
+		msgCid, err := s.fm.Reserve(s.ctx, s.walletAddr, s.acctAddr, amt)
+		reserved <- reserveResult{msgCid: msgCid, err: err}
+	}()
+
+	s.mockApi.completeMsg(sentinel)
+	require.NoError(t, <-errCh)
+	res := <-reserved
+	require.NoError(t, res.err)
+	require.Equal(t, cid.Undef, res.msgCid)
+	require.Equal(t, msgCount, s.mockApi.messageCount())
+}
+
 // TestFundManagerParallel verifies that operations can be run in parallel
 func TestFundManagerParallel(t *testing.T) {
 	s := setup(t)
@@ -39,6 +39,8 @@ const (
 	LFileName = LabelKey("filename")  // Local file path of the source file.
 	LCARPath  = LabelKey("car_path")  // Path of the CARv2 file containing the imported data.
 	LCAROwner = LabelKey("car_owner") // Owner of the CAR; "importmgr" is us; "user" or empty is them.
+
+	LManifestPath = LabelKey("manifest") // Path of the manifest of an aggregated CAR.
 )
 
 func NewManager(ctx context.Context, ds badger.ImportClientDS, rootDir string) *Manager {
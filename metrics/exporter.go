@@ -0,0 +1,47 @@
+package metrics
+
+import (
+	"fmt"
+	"net/http"
+	"sync"
+
+	"contrib.go.opencensus.io/exporter/prometheus"
+	promclient "github.com/prometheus/client_golang/prometheus"
+	"go.opencensus.io/stats/view"
+)
+
+var (
+	exporterOnce sync.Once
+	exporter     http.Handler
+	exporterErr  error
+)
+
+// Exporter register the default views and return a handler serving them in prometheus format,
+// views and the exporter are created once, later calls return the same handler
+func Exporter() (http.Handler, error) {
+	exporterOnce.Do(func() {
+		exporter, exporterErr = newExporter()
+	})
+	return exporter, exporterErr
+}
+
+func newExporter() (http.Handler, error) {
+	if err := view.Register(DefaultViews...); err != nil {
+		return nil, fmt.Errorf("register metric views: %w", err)
+	}
+
+	// use the default registry so that go runtime and process metrics are exported too
+	registry, ok := promclient.DefaultRegisterer.(*promclient.Registry)
+	if !ok {
+		return nil, fmt.Errorf("failed to export default prometheus registry, got %T", promclient.DefaultRegisterer)
+	}
+
+	exporter, err := prometheus.NewExporter(prometheus.Options{
+		Registry:  registry,
+		Namespace: "venus_market",
+	})
+	if err != nil {
+		return nil, fmt.Errorf("create prometheus exporter: %w", err)
+	}
+	return exporter, nil
+}
@@ -0,0 +1,36 @@
+package metrics
+
+import (
+	"context"
+	"io/ioutil"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+	"go.opencensus.io/stats/view"
+	"go.opencensus.io/tag"
+)
+
+func TestExporter(t *testing.T) {
+	exporter, err := Exporter()
+	require.NoError(t, err)
+	defer view.Unregister(DefaultViews...)
+
+	// the exporter is only created once
+	again, err := Exporter()
+	require.NoError(t, err)
+	assert.Equal(t, exporter, again)
+
+	ctx := context.Background()
+	Record(ctx, []tag.Mutator{tag.Upsert(Result, ResultOf(nil))}, FundReserveRequest.M(1))
+	Record(ctx, nil, RetrievalBytesSent.M(1024))
+
+	rec := httptest.NewRecorder()
+	exporter.ServeHTTP(rec, httptest.NewRequest("GET", "/debug/metrics", nil))
+	body, err := ioutil.ReadAll(rec.Body)
+	require.NoError(t, err)
+
+	assert.Contains(t, string(body), `venus_market_fund_reserve_request{result="success"} 1`)
+	assert.Contains(t, string(body), `venus_market_retrieval_bytes_sent 1024`)
+}
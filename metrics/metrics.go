@@ -0,0 +1,164 @@
+package metrics
+
+import (
+	"context"
+	"time"
+
+	"go.opencensus.io/stats"
+	"go.opencensus.io/stats/view"
+	"go.opencensus.io/tag"
+)
+
+// Distribution
+var (
+	defaultMillisecondsDistribution = view.Distribution(0.01, 0.05, 0.1, 0.3, 0.6, 0.8, 1, 2, 3, 4, 5, 6, 8, 10, 13, 16, 20, 25, 30, 40, 50, 65, 80, 100, 130, 160, 200, 250, 300, 400, 500, 650, 800, 1000, 2000, 3000, 4000, 5000, 7500, 10000, 20000, 50000, 100000, 300000, 600000)
+	batchSizeDistribution           = view.Distribution(1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024)
+	gasDistribution                 = view.Distribution(1e6, 5e6, 1e7, 5e7, 1e8, 5e8, 1e9, 5e9, 1e10)
+)
+
+// Global Tags
+var (
+	Miner, _       = tag.NewKey("miner")
+	DealState, _   = tag.NewKey("deal_state")
+	Result, _      = tag.NewKey("result")
+	StorageName, _ = tag.NewKey("storage_name")
+	StorageType, _ = tag.NewKey("storage_type")
+	Address, _     = tag.NewKey("address")
+)
+
+// values of tag Result
+const (
+	ResultSuccess = "success"
+	ResultFailure = "failure"
+)
+
+// Measures
+var (
+	// storage deal
+	StorageDealStateTransition = stats.Int64("storage/deal_state_transition", "Counter of storage deals entering a state", stats.UnitDimensionless)
+	DealPublishBatchSize       = stats.Int64("storage/publish_batch_size", "Number of deals in a publish message", stats.UnitDimensionless)
+	DealPublishGasUsed         = stats.Int64("storage/publish_gas_used", "Gas used by publish messages", stats.UnitDimensionless)
+
+	// retrieval deal
+	RetrievalDealStarted   = stats.Int64("retrieval/deal_started", "Counter of retrieval deals accepted", stats.UnitDimensionless)
+	RetrievalDealCompleted = stats.Int64("retrieval/deal_completed", "Counter of retrieval deals completed", stats.UnitDimensionless)
+	RetrievalDealFailed    = stats.Int64("retrieval/deal_failed", "Counter of retrieval deals failed", stats.UnitDimensionless)
+	RetrievalBytesSent     = stats.Int64("retrieval/bytes_sent", "Bytes sent to retrieval clients", stats.UnitBytes)
+
+	// dagstore
+	ShardAcquireDuration = stats.Float64("dagstore/shard_acquire_ms", "Duration of acquiring a shard", stats.UnitMilliseconds)
+
+	// piece storage
+	PieceStorageReadBytes  = stats.Int64("piecestorage/read_bytes", "Bytes read from piece storage", stats.UnitBytes)
+	PieceStorageWriteBytes = stats.Int64("piecestorage/write_bytes", "Bytes written to piece storage", stats.UnitBytes)
+
+	// fund
+	FundReserveRequest = stats.Int64("fund/reserve_request", "Counter of fund reservation requests", stats.UnitDimensionless)
+	FundReleaseRequest = stats.Int64("fund/release_request", "Counter of fund release requests", stats.UnitDimensionless)
+	FundReserved       = stats.Float64("fund/reserved", "Amount of funds reserved in market actor, in attoFIL", stats.UnitDimensionless)
+)
+
+var (
+	StorageDealStateTransitionView = &view.View{
+		Measure:     StorageDealStateTransition,
+		Aggregation: view.Count(),
+		TagKeys:     []tag.Key{Miner, DealState},
+	}
+	DealPublishBatchSizeView = &view.View{
+		Measure:     DealPublishBatchSize,
+		Aggregation: batchSizeDistribution,
+	}
+	DealPublishGasUsedView = &view.View{
+		Measure:     DealPublishGasUsed,
+		Aggregation: gasDistribution,
+	}
+
+	RetrievalDealStartedView = &view.View{
+		Measure:     RetrievalDealStarted,
+		Aggregation: view.Count(),
+	}
+	RetrievalDealCompletedView = &view.View{
+		Measure:     RetrievalDealCompleted,
+		Aggregation: view.Count(),
+	}
+	RetrievalDealFailedView = &view.View{
+		Measure:     RetrievalDealFailed,
+		Aggregation: view.Count(),
+	}
+	RetrievalBytesSentView = &view.View{
+		Measure:     RetrievalBytesSent,
+		Aggregation: view.Sum(),
+	}
+
+	ShardAcquireDurationView = &view.View{
+		Measure:     ShardAcquireDuration,
+		Aggregation: defaultMillisecondsDistribution,
+		TagKeys:     []tag.Key{Result},
+	}
+
+	PieceStorageReadBytesView = &view.View{
+		Measure:     PieceStorageReadBytes,
+		Aggregation: view.Sum(),
+		TagKeys:     []tag.Key{StorageName, StorageType},
+	}
+	PieceStorageWriteBytesView = &view.View{
+		Measure:     PieceStorageWriteBytes,
+		Aggregation: view.Sum(),
+		TagKeys:     []tag.Key{StorageName, StorageType},
+	}
+
+	FundReserveRequestView = &view.View{
+		Measure:     FundReserveRequest,
+		Aggregation: view.Count(),
+		TagKeys:     []tag.Key{Result},
+	}
+	FundReleaseRequestView = &view.View{
+		Measure:     FundReleaseRequest,
+		Aggregation: view.Count(),
+		TagKeys:     []tag.Key{Result},
+	}
+	FundReservedView = &view.View{
+		Measure:     FundReserved,
+		Aggregation: view.LastValue(),
+		TagKeys:     []tag.Key{Address},
+	}
+)
+
+// DefaultViews is an array of OpenCensus views for metric gathering purposes
+var DefaultViews = []*view.View{
+	StorageDealStateTransitionView,
+	DealPublishBatchSizeView,
+	DealPublishGasUsedView,
+
+	RetrievalDealStartedView,
+	RetrievalDealCompletedView,
+	RetrievalDealFailedView,
+	RetrievalBytesSentView,
+
+	ShardAcquireDurationView,
+
+	PieceStorageReadBytesView,
+	PieceStorageWriteBytesView,
+
+	FundReserveRequestView,
+	FundReleaseRequestView,
+	FundReservedView,
+}
+
+// SinceInMilliseconds returns the duration of time since the provide time as a float64.
+func SinceInMilliseconds(startTime time.Time) float64 {
+	return float64(time.Since(startTime).Nanoseconds()) / 1e6
+}
+
+// ResultOf return the value of tag Result for an error
+func ResultOf(err error) string {
+	if err != nil {
+		return ResultFailure
+	}
+	return ResultSuccess
+}
+
+// Record records measurements with tags, the failure of tagging is ignored as metrics should never break the caller
+func Record(ctx context.Context, mutators []tag.Mutator, ms ...stats.Measurement) {
+	_ = stats.RecordWithTags(ctx, mutators, ms...)
+}
@@ -61,6 +61,16 @@ func NeAddrMgrImpl(ctx metrics.MetricsCtx, fullNode v1api.FullNode, authClient *
 		return nil, err
 	}
 
+	for _, payment := range cfg.MinerRetrievalPaymentAddresses {
+		err = m.addUser(ctx, types.User{
+			Addr:    address.Address(payment.Addr),
+			Account: payment.Account,
+		})
+		if err != nil {
+			return nil, err
+		}
+	}
+
 	err = m.addUser(ctx, convertConfigAddress(cfg.AddressConfig.DealPublishControl)...)
 	if err != nil {
 		return nil, err
@@ -52,6 +52,46 @@ func (ps *badgerCidInfoRepo) ListCidInfoKeys(ctx context.Context) ([]cid.Cid, er
 	return out, nil
 }
 
+func (ps *badgerCidInfoRepo) RemovePieceBlockLocations(ctx context.Context, pieceCIDs []cid.Cid) error {
+	if len(pieceCIDs) == 0 {
+		return nil
+	}
+	removed := make(map[cid.Cid]struct{}, len(pieceCIDs))
+	for _, pieceCID := range pieceCIDs {
+		removed[pieceCID] = struct{}{}
+	}
+
+	// cid infos are keyed by block, so the store is walked once for all the pieces
+	var cis []piecestore.CIDInfo
+	if err := ps.cidInfos.List(ctx, &cis); err != nil {
+		return err
+	}
+
+	for _, ci := range cis {
+		locations := make([]piecestore.PieceBlockLocation, 0, len(ci.PieceBlockLocations))
+		for _, pbl := range ci.PieceBlockLocations {
+			if _, ok := removed[pbl.PieceCID]; !ok {
+				locations = append(locations, pbl)
+			}
+		}
+		if len(locations) == len(ci.PieceBlockLocations) {
+			continue
+		}
+
+		var err error
+		if len(locations) == 0 {
+			err = ps.cidInfos.Get(ci.CID).End(ctx)
+		} else {
+			ci.PieceBlockLocations = locations
+			err = ps.cidInfos.Save(ctx, ci.CID, &ci)
+		}
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
 // Retrieve the CIDInfo associated with `pieceCID` from the CID info store.
 func (ps *badgerCidInfoRepo) GetCIDInfo(ctx context.Context, payloadCID cid.Cid) (piecestore.CIDInfo, error) {
 	var out piecestore.CIDInfo
@@ -27,12 +27,14 @@ const (
 	storageDeals      = "/deals"
 	storageAsk        = "/storage-ask"
 	paych             = "/paych/"
+	webhookOutbox     = "/webhook/outbox"
 
 	// client
 	dealClient      = "/deals/client"
 	dealLocal       = "/deals/local"
 	retrievalClient = "/retrievals/client"
 	clientTransfer  = "/datatransfer/client/transfers"
+	dealRenewal     = "/deals/renewal"
 )
 
 // /metadata
@@ -74,6 +76,9 @@ type StorageAskDS datastore.Batching //key = latest
 // /metadata/paych/
 type PayChanDS datastore.Batching
 
+// /metadata/webhook/outbox
+type WebhookOutboxDS datastore.Batching
+
 //*********************************client
 // /metadata/deals/client
 type ClientDatastore datastore.Batching
@@ -90,6 +95,9 @@ type ImportClientDS datastore.Batching
 // /metadata/datatransfer/client/transfers
 type ClientTransferDS datastore.Batching
 
+// /metadata/deals/renewal
+type ClientRenewalDS datastore.Batching
+
 func NewMetadataDS(mctx metrics.MetricsCtx, lc fx.Lifecycle, homeDir *config.HomeDir) (MetadataDS, error) {
 	db, err := badger.NewDatastore(path.Join(string(*homeDir), metadata), &badger.DefaultOptions)
 	if err != nil {
@@ -144,6 +152,10 @@ func NewPayChanDS(ds MetadataDS) PayChanDS {
 	return namespace.Wrap(ds, datastore.NewKey(paych))
 }
 
+func NewWebhookOutboxDS(ds MetadataDS) WebhookOutboxDS {
+	return namespace.Wrap(ds, datastore.NewKey(webhookOutbox))
+}
+
 // NewClientDatastore creates a datastore for the client to store its deals
 func NewClientDatastore(ds MetadataDS) ClientDatastore {
 	return namespace.Wrap(ds, datastore.NewKey(dealClient))
@@ -162,6 +174,11 @@ func NewClientTransferDS(ds MetadataDS) ClientTransferDS {
 	return namespace.Wrap(ds, datastore.NewKey(clientTransfer))
 }
 
+// NewClientRenewalDS creates a datastore for the client to record renewals of its deals
+func NewClientRenewalDS(ds MetadataDS) ClientRenewalDS {
+	return namespace.Wrap(ds, datastore.NewKey(dealRenewal))
+}
+
 //nolint
 type BadgerRepo struct {
 	dsParams *BadgerDSParams
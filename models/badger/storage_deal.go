@@ -2,6 +2,7 @@ package badger
 
 import (
 	"bytes"
+	"container/heap"
 	"context"
 
 	"github.com/filecoin-project/go-address"
@@ -14,6 +15,8 @@ import (
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 	"github.com/ipfs/go-cid"
 	"github.com/ipfs/go-datastore"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 )
 
 type storageDealRepo struct {
@@ -309,3 +312,93 @@ func (sdr *storageDealRepo) GetPieceSize(ctx context.Context, pieceCID cid.Cid)
 	}
 	return deal.PayloadSize, deal.ClientDealProposal.Proposal.PieceSize, nil
 }
+
+func (sdr *storageDealRepo) QueryDeals(ctx context.Context, params *types2.DealQueryParams) (*types2.DealQueryResult, error) {
+	field, err := params.SortField()
+	if err != nil {
+		return nil, err
+	}
+
+	var hasCursor bool
+	var cursorValue int64
+	var cursorCid string
+	if len(params.Cursor) > 0 {
+		value, propCid, err := types2.DecodeDealCursor(params.Cursor)
+		if err != nil {
+			return nil, err
+		}
+		hasCursor, cursorValue, cursorCid = true, value, propCid.String()
+	}
+
+	// keep one more deal than a page to tell whether there is a next page, the last one in order is on the top of heap
+	size := params.PageSize() + 1
+	h := &dealHeap{field: field, desc: params.Desc}
+	if err = travelDeals(ctx, sdr.ds, func(deal *types.MinerDeal) (bool, error) {
+		if !params.Match(deal) {
+			return false, nil
+		}
+		if hasCursor {
+			value, propCid := types2.DealSortValue(field, deal), deal.ProposalCid.String()
+			after := lessDeal(cursorValue, cursorCid, value, propCid)
+			if params.Desc {
+				after = lessDeal(value, propCid, cursorValue, cursorCid)
+			}
+			if !after {
+				return false, nil
+			}
+		}
+		if h.Len() < size {
+			heap.Push(h, deal)
+		} else if h.before(deal, h.deals[0]) {
+			h.deals[0] = deal
+			heap.Fix(h, 0)
+		}
+		return false, nil
+	}); err != nil {
+		return nil, err
+	}
+
+	deals := make([]*types.MinerDeal, h.Len())
+	for i := len(deals) - 1; i >= 0; i-- {
+		deals[i] = heap.Pop(h).(*types.MinerDeal)
+	}
+	return types2.NewDealQueryResult(params, field, deals), nil
+}
+
+// lessDeal compare deals by sort value and then proposal cid in ascending order
+func lessDeal(value1 int64, cid1 string, value2 int64, cid2 string) bool {
+	if value1 != value2 {
+		return value1 < value2
+	}
+	return cid1 < cid2
+}
+
+// dealHeap is a heap of deals whose top is the last one in the query order
+type dealHeap struct {
+	field types2.DealSortField
+	desc  bool
+	deals []*types.MinerDeal
+}
+
+// before report whether deal a comes before deal b in the query order
+func (h *dealHeap) before(a, b *types.MinerDeal) bool {
+	va, vb := types2.DealSortValue(h.field, a), types2.DealSortValue(h.field, b)
+	if h.desc {
+		return lessDeal(vb, b.ProposalCid.String(), va, a.ProposalCid.String())
+	}
+	return lessDeal(va, a.ProposalCid.String(), vb, b.ProposalCid.String())
+}
+
+func (h *dealHeap) Len() int { return len(h.deals) }
+
+func (h *dealHeap) Less(i, j int) bool { return h.before(h.deals[j], h.deals[i]) }
+
+func (h *dealHeap) Swap(i, j int) { h.deals[i], h.deals[j] = h.deals[j], h.deals[i] }
+
+func (h *dealHeap) Push(x interface{}) { h.deals = append(h.deals, x.(*types.MinerDeal)) }
+
+func (h *dealHeap) Pop() interface{} {
+	last := h.deals[len(h.deals)-1]
+	h.deals = h.deals[:len(h.deals)-1]
+	return last
+}
@@ -53,4 +53,23 @@ func doTestCidinfo(t *testing.T, repo repo.ICidInfoRepo) {
 
 	require.NoError(t, err)
 	require.LessOrEqual(t, 2, len(cids))
+
+	// payLoadCid2 is also in another piece, only its location in that piece is left after removing pieceCid
+	pieceCid2 := randCid(t)
+	err = repo.AddPieceBlockLocations(ctx, pieceCid2, map[cid.Cid]piecestore.BlockLocation{
+		payLoadCid2: {BlockSize: 30, RelOffset: 100},
+	})
+	require.NoError(t, err)
+	require.NoError(t, repo.RemovePieceBlockLocations(ctx, []cid.Cid{pieceCid}))
+
+	cids, err = repo.ListCidInfoKeys(ctx)
+	require.NoError(t, err)
+	assert.NotContains(t, cids, payLoadCid1)
+	assert.Contains(t, cids, payLoadCid2)
+
+	cidInfo, err = repo.GetCIDInfo(ctx, payLoadCid2)
+	require.NoError(t, err)
+	require.Len(t, cidInfo.PieceBlockLocations, 1)
+	assert.Equal(t, pieceCid2, cidInfo.PieceBlockLocations[0].PieceCID)
+	assert.Equal(t, uint64(100), cidInfo.PieceBlockLocations[0].RelOffset)
 }
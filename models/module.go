@@ -23,6 +23,7 @@ var DBOptions = func(server bool, mysqlCfg *config.Mysql) builder.Option {
 			builder.Override(new(badger2.StagingDS), badger2.NewStagingDS),
 			builder.Override(new(badger2.StagingBlockstore), badger2.NewStagingBlockStore),
 			builder.Override(new(badger2.DagTransferDS), badger2.NewDagTransferDS),
+			builder.Override(new(badger2.WebhookOutboxDS), badger2.NewWebhookOutboxDS),
 			builder.ApplyIfElse(func(s *builder.Settings) bool {
 				return mysqlCfg != nil && len(mysqlCfg.ConnectionString) > 0
 			}, builder.Options(
@@ -58,6 +59,7 @@ var DBOptions = func(server bool, mysqlCfg *config.Mysql) builder.Option {
 				builder.Override(new(badger2.RetrievalClientDS), badger2.NewRetrievalClientDS),
 				builder.Override(new(badger2.ImportClientDS), badger2.NewImportClientDS),
 				builder.Override(new(badger2.ClientTransferDS), badger2.NewClientTransferDS),
+				builder.Override(new(badger2.ClientRenewalDS), badger2.NewClientRenewalDS),
 
 				builder.Override(new(repo.Repo), badger2.NewBadgerRepo),
 			),
@@ -101,4 +101,15 @@ func (m *mysqlCidInfoRepo) ListCidInfoKeys(ctx context.Context) ([]cid.Cid, erro
 
 }
 
+func (m *mysqlCidInfoRepo) RemovePieceBlockLocations(ctx context.Context, pieceCIDs []cid.Cid) error {
+	if len(pieceCIDs) == 0 {
+		return nil
+	}
+	pieces := make([]string, 0, len(pieceCIDs))
+	for _, pieceCID := range pieceCIDs {
+		pieces = append(pieces, DBCid(pieceCID).String())
+	}
+	return m.WithContext(ctx).Table(cidInfoTableName).Where("piece_cid in ?", pieces).Delete(&cidInfo{}).Error
+}
+
 var _ repo.ICidInfoRepo = (*mysqlCidInfoRepo)(nil)
@@ -9,6 +9,7 @@ import (
 	"unicode/utf8"
 
 	"github.com/filecoin-project/venus-market/v2/models/repo"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 
 	"github.com/filecoin-project/go-fil-markets/piecestore"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
@@ -49,7 +50,7 @@ type storageDeal struct {
 	AvailableForRetrieval bool       `gorm:"column:available_for_retrieval;"`
 
 	DealID       uint64 `gorm:"column:deal_id;type:bigint unsigned;index"`
-	CreationTime int64  `gorm:"column:creation_time;type:bigint;"`
+	CreationTime int64  `gorm:"column:creation_time;type:bigint;index"`
 
 	TransferChannelId ChannelID `gorm:"embedded;embeddedPrefix:tci_"`
 	SectorNumber      uint64    `gorm:"column:sector_number;type:bigint unsigned;"`
@@ -542,3 +543,74 @@ func fromDbDeals(dbDeals []*storageDeal) ([]*types.MinerDeal, error) {
 	}
 	return results, nil
 }
+
+var dealSortColumns = map[types2.DealSortField]string{
+	types2.DealSortByCreationTime: "creation_time",
+	types2.DealSortByDealID:       "deal_id",
+	types2.DealSortByPieceSize:    "cdp_piece_size",
+}
+
+func (sdr *storageDealRepo) QueryDeals(ctx context.Context, params *types2.DealQueryParams) (*types2.DealQueryResult, error) {
+	field, err := params.SortField()
+	if err != nil {
+		return nil, err
+	}
+	column := dealSortColumns[field]
+
+	query := sdr.WithContext(ctx).Table(storageDealTableName)
+	if params.Miner != address.Undef {
+		query = query.Where("cdp_provider = ?", DBAddress(params.Miner).String())
+	}
+	if params.Client != address.Undef {
+		query = query.Where("cdp_client = ?", DBAddress(params.Client).String())
+	}
+	if len(params.States) > 0 {
+		query = query.Where("state in ?", params.States)
+	}
+	if len(params.PieceStatuses) > 0 {
+		query = query.Where("piece_status in ?", params.PieceStatuses)
+	}
+	if params.Verified != nil {
+		query = query.Where("cdp_verified_deal = ?", *params.Verified)
+	}
+	if !params.CreatedAfter.IsZero() {
+		query = query.Where("creation_time >= ?", params.CreatedAfter.UnixNano())
+	}
+	if !params.CreatedBefore.IsZero() {
+		query = query.Where("creation_time < ?", params.CreatedBefore.UnixNano())
+	}
+	if params.PieceCID != nil {
+		query = query.Where("cdp_piece_cid = ?", DBCid(*params.PieceCID).String())
+	}
+	if params.PayloadCID != nil {
+		query = query.Where("ref_root = ?", DBCid(*params.PayloadCID).String())
+	}
+	if params.DealID != nil {
+		query = query.Where("deal_id = ?", *params.DealID)
+	}
+
+	order, cmp := "ASC", ">"
+	if params.Desc {
+		order, cmp = "DESC", "<"
+	}
+	if len(params.Cursor) > 0 {
+		value, propCid, err := types2.DecodeDealCursor(params.Cursor)
+		if err != nil {
+			return nil, err
+		}
+		query = query.Where(fmt.Sprintf("(%s %s ? OR (%s = ? AND proposal_cid %s ?))", column, cmp, column, cmp),
+			value, value, DBCid(propCid).String())
+	}
+
+	var dbDeals []*storageDeal
+	if err := query.Order(fmt.Sprintf("%s %s, proposal_cid %s", column, order, order)).
+		Limit(params.PageSize() + 1).Find(&dbDeals).Error; err != nil {
+		return nil, err
+	}
+
+	deals, err := fromDbDeals(dbDeals)
+	if err != nil {
+		return nil, err
+	}
+	return types2.NewDealQueryResult(params, field, deals), nil
+}
@@ -18,6 +18,8 @@ import (
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 	"github.com/ipfs/go-cid"
 	"github.com/libp2p/go-libp2p-core/peer"
+
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 )
 
 type FundRepo interface {
@@ -43,6 +45,8 @@ type StorageDealRepo interface {
 	GetDealByAddrAndStatus(ctx context.Context, addr address.Address, status ...storagemarket.StorageDealStatus) ([]*types.MinerDeal, error)
 	ListDealByAddr(ctx context.Context, mAddr address.Address) ([]*types.MinerDeal, error)
 	ListDeal(ctx context.Context) ([]*types.MinerDeal, error)
+	//QueryDeals list a page of deals which match the filters of params, in the order of params
+	QueryDeals(ctx context.Context, params *types2.DealQueryParams) (*types2.DealQueryResult, error)
 
 	GetPieceInfo(ctx context.Context, pieceCID cid.Cid) (*piecestore.PieceInfo, error)
 	GetPieceSize(ctx context.Context, pieceCID cid.Cid) (uint64, abi.PaddedPieceSize, error)
@@ -91,6 +95,8 @@ type ICidInfoRepo interface {
 	AddPieceBlockLocations(ctx context.Context, pieceCID cid.Cid, blockLocations map[cid.Cid]piecestore.BlockLocation) error
 	GetCIDInfo(ctx context.Context, payloadCID cid.Cid) (piecestore.CIDInfo, error)
 	ListCidInfoKeys(ctx context.Context) ([]cid.Cid, error)
+	//RemovePieceBlockLocations remove locations of blocks in the pieces in one pass, cid infos left without any location are deleted
+	RemovePieceBlockLocations(ctx context.Context, pieceCIDs []cid.Cid) error
 }
 
 type Repo interface {
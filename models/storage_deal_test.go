@@ -20,6 +20,7 @@ import (
 	"github.com/filecoin-project/go-state-types/crypto"
 	"github.com/filecoin-project/venus-market/v2/models/badger"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
 	"github.com/stretchr/testify/assert"
 	typegen "github.com/whyrusleeping/cbor-gen"
 )
@@ -40,6 +41,18 @@ func TestStorageDeal(t *testing.T) {
 		db := BadgerDB(t)
 		testStorageDeal(t, badger.NewStorageDealRepo(db))
 	})
+
+	t.Run("mysql query", func(t *testing.T) {
+		repo := MysqlDB(t)
+		defer func() {
+			_ = repo.Close()
+		}()
+		testQueryDeals(t, repo.StorageDealRepo())
+	})
+
+	t.Run("badger query", func(t *testing.T) {
+		testQueryDeals(t, badger.NewStorageDealRepo(BadgerDB(t)))
+	})
 }
 
 func getTestMinerDeal(t *testing.T) *types.MinerDeal {
@@ -190,6 +203,87 @@ func testStorageDeal(t *testing.T, dealRepo repo.StorageDealRepo) {
 
 }
 
+func testQueryDeals(t *testing.T, dealRepo repo.StorageDealRepo) {
+	ctx := context.TODO()
+	miner := randAddress(t)
+	start := time.Unix(0, time.Now().UnixNano()).UTC()
+
+	deals := make([]*types.MinerDeal, 5)
+	for i := range deals {
+		deal := getTestMinerDeal(t)
+		deal.Proposal.Provider = miner
+		deal.Proposal.VerifiedDeal = i%2 == 0
+		deal.DealID = abi.DealID(100 - i)
+		deal.CreationTime = typegen.CborTime(start.Add(time.Duration(i) * time.Minute))
+		if i == 4 {
+			deal.State = storagemarket.StorageDealSealing
+			deal.PieceStatus = types.Assigned
+		}
+		deals[i] = deal
+		require.NoError(t, dealRepo.SaveDeal(ctx, deal))
+	}
+	other := getTestMinerDeal(t)
+	require.NoError(t, dealRepo.SaveDeal(ctx, other))
+
+	list := func(params types2.DealQueryParams) *types2.DealQueryResult {
+		res, err := dealRepo.QueryDeals(ctx, &params)
+		require.NoError(t, err)
+		return res
+	}
+	proposals := func(deals []*types.MinerDeal) []string {
+		res := make([]string, len(deals))
+		for i, deal := range deals {
+			res[i] = deal.ProposalCid.String()
+		}
+		return res
+	}
+
+	// page through deals of miner by creation time
+	var paged []*types.MinerDeal
+	params := types2.DealQueryParams{Miner: miner, Limit: 2}
+	for i := 0; i < 3; i++ {
+		res := list(params)
+		paged = append(paged, res.Deals...)
+		params.Cursor = res.NextCursor
+		if i < 2 {
+			assert.NotEmpty(t, res.NextCursor)
+		}
+	}
+	assert.Empty(t, params.Cursor)
+	assert.Equal(t, proposals(deals), proposals(paged))
+	compareDeal(t, paged[0], deals[0])
+
+	res := list(types2.DealQueryParams{Miner: miner, SortBy: types2.DealSortByDealID, Desc: true, Limit: 2})
+	assert.Equal(t, proposals(deals[:2]), proposals(res.Deals))
+	res = list(types2.DealQueryParams{Miner: miner, SortBy: types2.DealSortByDealID, Limit: 2})
+	assert.Equal(t, proposals([]*types.MinerDeal{deals[4], deals[3]}), proposals(res.Deals))
+
+	verified := true
+	res = list(types2.DealQueryParams{Miner: miner, Verified: &verified})
+	assert.Equal(t, proposals([]*types.MinerDeal{deals[0], deals[2], deals[4]}), proposals(res.Deals))
+	assert.Empty(t, res.NextCursor)
+
+	res = list(types2.DealQueryParams{Miner: miner, States: []storagemarket.StorageDealStatus{storagemarket.StorageDealSealing}})
+	assert.Equal(t, proposals(deals[4:]), proposals(res.Deals))
+	res = list(types2.DealQueryParams{Miner: miner, PieceStatuses: []types.PieceStatus{types.Assigned, types.Packing}})
+	assert.Equal(t, proposals(deals[4:]), proposals(res.Deals))
+
+	res = list(types2.DealQueryParams{Miner: miner, CreatedAfter: start.Add(time.Minute), CreatedBefore: start.Add(3 * time.Minute)})
+	assert.Equal(t, proposals(deals[1:3]), proposals(res.Deals))
+
+	dealID := deals[3].DealID
+	res = list(types2.DealQueryParams{Miner: miner, DealID: &dealID})
+	assert.Equal(t, proposals(deals[3:4]), proposals(res.Deals))
+
+	res = list(types2.DealQueryParams{Client: other.Proposal.Client, PieceCID: &other.Proposal.PieceCID, PayloadCID: &other.Ref.Root})
+	assert.Equal(t, proposals([]*types.MinerDeal{other}), proposals(res.Deals))
+
+	_, err := dealRepo.QueryDeals(ctx, &types2.DealQueryParams{SortBy: "label"})
+	assert.Error(t, err)
+	_, err = dealRepo.QueryDeals(ctx, &types2.DealQueryParams{Cursor: "invalid"})
+	assert.Error(t, err)
+}
+
 func compareDeal(t *testing.T, actual, excepted *types.MinerDeal) {
 	assert.Equal(t, excepted.ClientDealProposal, actual.ClientDealProposal)
 	assert.Equal(t, excepted.ProposalCid, actual.ProposalCid)
@@ -0,0 +1,7 @@
+package notify
+
+import (
+	"github.com/ipfs-force-community/venus-common-utils/builder"
+)
+
+var NotifyOpts = builder.Override(new(*WebhookNotifier), NewWebhookNotifier)
@@ -0,0 +1,409 @@
+package notify
+
+import (
+	"bytes"
+	"context"
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"io"
+	"io/ioutil"
+	"net/http"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/retrievalmarket"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/google/uuid"
+	"github.com/ipfs-force-community/venus-common-utils/metrics"
+	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-datastore"
+	"github.com/ipfs/go-datastore/query"
+	logging "github.com/ipfs/go-log/v2"
+	"github.com/libp2p/go-libp2p-core/peer"
+	"go.uber.org/fx"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/badger"
+)
+
+var log = logging.Logger("notify")
+
+// idleInterval is the interval to check the outbox when no event is pending
+var idleInterval = time.Minute
+
+const (
+	defaultMinBackoff   = time.Second
+	maxDiscardedRespLen = 1 << 10
+	// outboxPageSize is the number of outbox entries read at a time
+	outboxPageSize = 100
+)
+
+// types of event
+const (
+	EventStorageDealState       = "storage_deal_state"
+	EventStorageDealSlashed     = "storage_deal_slashed"
+	EventRetrievalDealCompleted = "retrieval_deal_completed"
+)
+
+// headers of webhook request
+const (
+	SignatureHeader = "X-Venus-Market-Signature"
+	EventHeader     = "X-Venus-Market-Event"
+	DeliveryHeader  = "X-Venus-Market-Delivery"
+)
+
+// Event is the body posted to the webhook, ID is unique for each event and kept across retries,
+// receivers could use it to drop duplicated deliveries
+type Event struct {
+	ID   string          `json:"id"`
+	Type string          `json:"type"`
+	Time time.Time       `json:"time"`
+	Data json.RawMessage `json:"data"`
+}
+
+// StorageDealData is the data of storage deal events
+type StorageDealData struct {
+	ProposalCid cid.Cid
+	DealID      abi.DealID
+	Provider    address.Address
+	Client      peer.ID
+	PieceCid    cid.Cid
+	PieceSize   abi.PaddedPieceSize
+	State       string
+	Message     string
+	SlashEpoch  abi.ChainEpoch `json:",omitempty"`
+}
+
+// RetrievalDealData is the data of retrieval deal events
+type RetrievalDealData struct {
+	DealID        retrievalmarket.DealID
+	Receiver      peer.ID
+	PayloadCid    cid.Cid
+	PieceCid      *cid.Cid
+	TotalSent     uint64
+	FundsReceived abi.TokenAmount
+}
+
+type outboxEntry struct {
+	Event       Event
+	Attempts    int
+	NextAttempt time.Time
+}
+
+// WebhookNotifier posts signed events to the configured url, events are saved in an outbox before delivery
+// and removed once delivered, so that they survive restarts. A nil notifier notifies nothing.
+type WebhookNotifier struct {
+	cfg    config.Webhook
+	states map[storagemarket.StorageDealStatus]struct{}
+	ds     datastore.Batching
+	client *http.Client
+	wake   chan struct{}
+}
+
+func NewWebhookNotifier(mctx metrics.MetricsCtx, lc fx.Lifecycle, cfg *config.MarketConfig, ds badger.WebhookOutboxDS) (*WebhookNotifier, error) {
+	notifier, err := newWebhookNotifier(cfg.Webhook, ds)
+	if err != nil {
+		return nil, err
+	}
+	if !notifier.enabled() {
+		return notifier, nil
+	}
+
+	ctx := metrics.LifecycleCtx(mctx, lc)
+	lc.Append(fx.Hook{
+		OnStart: func(_ context.Context) error {
+			go notifier.run(ctx)
+			return nil
+		},
+	})
+	return notifier, nil
+}
+
+func newWebhookNotifier(cfg config.Webhook, ds datastore.Batching) (*WebhookNotifier, error) {
+	states := make(map[storagemarket.StorageDealStatus]struct{}, len(cfg.StorageDealStates))
+	for _, name := range cfg.StorageDealStates {
+		status, ok := dealStatusOf(name)
+		if !ok {
+			return nil, fmt.Errorf("unknown storage deal state %s in webhook config", name)
+		}
+		states[status] = struct{}{}
+	}
+
+	return &WebhookNotifier{
+		cfg:    cfg,
+		states: states,
+		ds:     ds,
+		client: &http.Client{Timeout: time.Duration(cfg.Timeout)},
+		wake:   make(chan struct{}, 1),
+	}, nil
+}
+
+func dealStatusOf(name string) (storagemarket.StorageDealStatus, bool) {
+	for status, statusName := range storagemarket.DealStates {
+		if statusName == name {
+			return status, true
+		}
+	}
+	return 0, false
+}
+
+func (n *WebhookNotifier) enabled() bool {
+	return n != nil && n.cfg.Url != ""
+}
+
+// StorageDealStateChanged notify the storage deal entered its current state, if the state is selected
+func (n *WebhookNotifier) StorageDealStateChanged(ctx context.Context, deal *types.MinerDeal) {
+	if !n.enabled() {
+		return
+	}
+	if _, ok := n.states[deal.State]; len(n.states) > 0 && !ok {
+		return
+	}
+	n.enqueue(ctx, EventStorageDealState, storageDealData(deal))
+}
+
+// StorageDealSlashed notify the active storage deal has been slashed at slashEpoch
+func (n *WebhookNotifier) StorageDealSlashed(ctx context.Context, deal *types.MinerDeal, slashEpoch abi.ChainEpoch) {
+	if !n.enabled() || !n.cfg.DealSlashed {
+		return
+	}
+	data := storageDealData(deal)
+	data.SlashEpoch = slashEpoch
+	n.enqueue(ctx, EventStorageDealSlashed, data)
+}
+
+// RetrievalDealCompleted notify the retrieval deal has been completed
+func (n *WebhookNotifier) RetrievalDealCompleted(ctx context.Context, deal *types.ProviderDealState) {
+	if !n.enabled() || !n.cfg.RetrievalCompleted {
+		return
+	}
+	n.enqueue(ctx, EventRetrievalDealCompleted, RetrievalDealData{
+		DealID:        deal.ID,
+		Receiver:      deal.Receiver,
+		PayloadCid:    deal.PayloadCID,
+		PieceCid:      deal.PieceCID,
+		TotalSent:     deal.TotalSent,
+		FundsReceived: deal.FundsReceived,
+	})
+}
+
+func storageDealData(deal *types.MinerDeal) StorageDealData {
+	return StorageDealData{
+		ProposalCid: deal.ProposalCid,
+		DealID:      deal.DealID,
+		Provider:    deal.Proposal.Provider,
+		Client:      deal.Client,
+		PieceCid:    deal.Proposal.PieceCID,
+		PieceSize:   deal.Proposal.PieceSize,
+		State:       storagemarket.DealStates[deal.State],
+		Message:     deal.Message,
+	}
+}
+
+// enqueue save the event in outbox and wake up the delivery loop, failure is logged only
+// as notification should never break the deal flow
+func (n *WebhookNotifier) enqueue(ctx context.Context, eventType string, data interface{}) {
+	raw, err := json.Marshal(data)
+	if err != nil {
+		log.Errorf("marshal data of %s event: %v", eventType, err)
+		return
+	}
+
+	now := time.Now()
+	entry := outboxEntry{
+		Event: Event{
+			ID:   uuid.New().String(),
+			Type: eventType,
+			Time: now,
+			Data: raw,
+		},
+		NextAttempt: now,
+	}
+	if err := n.putEntry(ctx, outboxKey(&entry.Event), &entry); err != nil {
+		log.Errorf("save %s event %s to outbox: %v", eventType, entry.Event.ID, err)
+		return
+	}
+
+	select {
+	case n.wake <- struct{}{}:
+	default:
+	}
+}
+
+// outboxKey order events by creation time
+func outboxKey(evt *Event) datastore.Key {
+	return datastore.NewKey(fmt.Sprintf("%020d-%s", evt.Time.UnixNano(), evt.ID))
+}
+
+func (n *WebhookNotifier) putEntry(ctx context.Context, key datastore.Key, entry *outboxEntry) error {
+	data, err := json.Marshal(entry)
+	if err != nil {
+		return err
+	}
+	return n.ds.Put(ctx, key, data)
+}
+
+func (n *WebhookNotifier) run(ctx context.Context) {
+	timer := time.NewTimer(0)
+	defer timer.Stop()
+
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case <-n.wake:
+		case <-timer.C:
+		}
+
+		wait := n.deliverDue(ctx)
+
+		if !timer.Stop() {
+			select {
+			case <-timer.C:
+			default:
+			}
+		}
+		timer.Reset(wait)
+	}
+}
+
+// deliverDue deliver events in outbox which are due, and return the duration until the next event is due.
+// the pass stops at the first failed delivery, the rest are retried with it after backoff
+func (n *WebhookNotifier) deliverDue(ctx context.Context) time.Duration {
+	wait := idleInterval
+	var lastKey string
+	for {
+		entries, err := n.outboxPage(ctx, lastKey)
+		if err != nil {
+			log.Errorf("read webhook outbox: %v", err)
+			return idleInterval
+		}
+
+		for _, e := range entries {
+			if ctx.Err() != nil {
+				return wait
+			}
+
+			key := datastore.NewKey(e.Key)
+			var entry outboxEntry
+			if err := json.Unmarshal(e.Value, &entry); err != nil {
+				log.Errorf("drop invalid webhook outbox entry %s: %v", e.Key, err)
+				_ = n.ds.Delete(ctx, key)
+				continue
+			}
+
+			if delay := time.Until(entry.NextAttempt); delay > 0 {
+				if delay < wait {
+					wait = delay
+				}
+				continue
+			}
+
+			err := n.deliver(ctx, &entry.Event)
+			if err == nil {
+				if err := n.ds.Delete(ctx, key); err != nil {
+					log.Errorf("remove delivered event %s from webhook outbox: %v", entry.Event.ID, err)
+				}
+				continue
+			}
+
+			entry.Attempts++
+			if n.cfg.MaxAttempts > 0 && entry.Attempts >= n.cfg.MaxAttempts {
+				log.Errorf("drop %s event %s after %d attempts: %v", entry.Event.Type, entry.Event.ID, entry.Attempts, err)
+				_ = n.ds.Delete(ctx, key)
+				return n.backoff(entry.Attempts)
+			}
+
+			backoff := n.backoff(entry.Attempts)
+			log.Warnf("deliver %s event %s failed, attempts %d, retry in %s: %v", entry.Event.Type, entry.Event.ID, entry.Attempts, backoff, err)
+			entry.NextAttempt = time.Now().Add(backoff)
+			if err := n.putEntry(ctx, key, &entry); err != nil {
+				log.Errorf("update webhook outbox entry %s: %v", entry.Event.ID, err)
+			}
+			// the endpoint is failing, stop the pass instead of trying the rest
+			return backoff
+		}
+
+		if len(entries) < outboxPageSize {
+			return wait
+		}
+		lastKey = entries[len(entries)-1].Key
+	}
+}
+
+// outboxPage read a page of outbox entries after lastKey in order of key
+func (n *WebhookNotifier) outboxPage(ctx context.Context, lastKey string) ([]query.Entry, error) {
+	q := query.Query{
+		Orders: []query.Order{query.OrderByKey{}},
+		Limit:  outboxPageSize,
+	}
+	if len(lastKey) > 0 {
+		q.Filters = []query.Filter{query.FilterKeyCompare{Op: query.GreaterThan, Key: lastKey}}
+	}
+	res, err := n.ds.Query(ctx, q)
+	if err != nil {
+		return nil, err
+	}
+	return res.Rest()
+}
+
+// backoff return the delay before the next attempt, doubled after each failed attempt
+func (n *WebhookNotifier) backoff(attempts int) time.Duration {
+	backoff := time.Duration(n.cfg.MinBackoff)
+	if backoff <= 0 {
+		backoff = defaultMinBackoff
+	}
+	maxBackoff := time.Duration(n.cfg.MaxBackoff)
+	for i := 1; i < attempts; i++ {
+		if maxBackoff > 0 && backoff >= maxBackoff {
+			break
+		}
+		backoff *= 2
+	}
+	if maxBackoff > 0 && backoff > maxBackoff {
+		backoff = maxBackoff
+	}
+	return backoff
+}
+
+func (n *WebhookNotifier) deliver(ctx context.Context, evt *Event) error {
+	body, err := json.Marshal(evt)
+	if err != nil {
+		return err
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, n.cfg.Url, bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set(EventHeader, evt.Type)
+	req.Header.Set(DeliveryHeader, evt.ID)
+	if n.cfg.Secret != "" {
+		req.Header.Set(SignatureHeader, Sign(n.cfg.Secret, body))
+	}
+
+	resp, err := n.client.Do(req)
+	if err != nil {
+		return err
+	}
+	defer resp.Body.Close() //nolint:errcheck
+	_, _ = io.Copy(ioutil.Discard, io.LimitReader(resp.Body, maxDiscardedRespLen))
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return fmt.Errorf("webhook responded with status %s", resp.Status)
+	}
+	return nil
+}
+
+// Sign return the signature of body sent in header X-Venus-Market-Signature, as "sha256=" followed by hex of HMAC-SHA256 with the secret
+func Sign(secret string, body []byte) string {
+	mac := hmac.New(sha256.New, []byte(secret))
+	_, _ = mac.Write(body)
+	return "sha256=" + hex.EncodeToString(mac.Sum(nil))
+}
@@ -0,0 +1,168 @@
+package notify
+
+import (
+	"context"
+	"encoding/json"
+	"io/ioutil"
+	"net/http"
+	"net/http/httptest"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-datastore"
+	"github.com/ipfs/go-datastore/query"
+	dssync "github.com/ipfs/go-datastore/sync"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+)
+
+type webhookReceiver struct {
+	lk     sync.Mutex
+	fail   bool
+	events []Event
+}
+
+func (r *webhookReceiver) ServeHTTP(w http.ResponseWriter, req *http.Request) {
+	r.lk.Lock()
+	defer r.lk.Unlock()
+	if r.fail {
+		w.WriteHeader(http.StatusInternalServerError)
+		return
+	}
+
+	body, _ := ioutil.ReadAll(req.Body)
+	if req.Header.Get(SignatureHeader) != Sign("secret", body) {
+		w.WriteHeader(http.StatusUnauthorized)
+		return
+	}
+	var evt Event
+	if err := json.Unmarshal(body, &evt); err != nil {
+		w.WriteHeader(http.StatusBadRequest)
+		return
+	}
+	r.events = append(r.events, evt)
+}
+
+func (r *webhookReceiver) setFail(fail bool) {
+	r.lk.Lock()
+	defer r.lk.Unlock()
+	r.fail = fail
+}
+
+func (r *webhookReceiver) received() []Event {
+	r.lk.Lock()
+	defer r.lk.Unlock()
+	return append([]Event(nil), r.events...)
+}
+
+func outboxLen(t *testing.T, ds datastore.Batching) int {
+	res, err := ds.Query(context.Background(), query.Query{KeysOnly: true})
+	require.NoError(t, err)
+	entries, err := res.Rest()
+	require.NoError(t, err)
+	return len(entries)
+}
+
+func TestWebhookNotifier(t *testing.T) {
+	ctx := context.Background()
+	receiver := &webhookReceiver{}
+	srv := httptest.NewServer(receiver)
+	defer srv.Close()
+
+	cfg := config.Webhook{
+		Url:                srv.URL,
+		Secret:             "secret",
+		StorageDealStates:  []string{"StorageDealActive"},
+		RetrievalCompleted: true,
+		Timeout:            config.Duration(time.Second),
+		MinBackoff:         config.Duration(time.Hour),
+	}
+	ds := dssync.MutexWrap(datastore.NewMapDatastore())
+	notifier, err := newWebhookNotifier(cfg, ds)
+	require.NoError(t, err)
+
+	deal := &types.MinerDeal{State: storagemarket.StorageDealSealing}
+	notifier.StorageDealStateChanged(ctx, deal)
+	assert.Equal(t, 0, outboxLen(t, ds), "state not selected")
+	notifier.StorageDealSlashed(ctx, deal, 100)
+	assert.Equal(t, 0, outboxLen(t, ds), "slash not enabled")
+
+	deal.State = storagemarket.StorageDealActive
+	notifier.StorageDealStateChanged(ctx, deal)
+	notifier.RetrievalDealCompleted(ctx, &types.ProviderDealState{TotalSent: 1024})
+	assert.Equal(t, 2, outboxLen(t, ds))
+
+	t.Run("retry later on failure", func(t *testing.T) {
+		receiver.setFail(true)
+		wait := notifier.deliverDue(ctx)
+		assert.Greater(t, int64(wait), int64(time.Minute-time.Second))
+		assert.Equal(t, 2, outboxLen(t, ds))
+		assert.Len(t, receiver.received(), 0)
+	})
+
+	t.Run("deliver after restart", func(t *testing.T) {
+		receiver.setFail(false)
+		cfg.MinBackoff = 0
+		restarted, err := newWebhookNotifier(cfg, ds)
+		require.NoError(t, err)
+
+		// entries are not due yet
+		restarted.deliverDue(ctx)
+		assert.Len(t, receiver.received(), 0)
+
+		// make them due, only the first one was tried as the pass stopped at the failure
+		res, err := ds.Query(ctx, query.Query{Orders: []query.Order{query.OrderByKey{}}})
+		require.NoError(t, err)
+		entries, err := res.Rest()
+		require.NoError(t, err)
+		for i, e := range entries {
+			var entry outboxEntry
+			require.NoError(t, json.Unmarshal(e.Value, &entry))
+			assert.Equal(t, 1-i, entry.Attempts)
+			entry.NextAttempt = time.Now()
+			require.NoError(t, restarted.putEntry(ctx, datastore.NewKey(e.Key), &entry))
+		}
+
+		restarted.deliverDue(ctx)
+		assert.Equal(t, 0, outboxLen(t, ds))
+		events := receiver.received()
+		require.Len(t, events, 2)
+		assert.Equal(t, EventStorageDealState, events[0].Type)
+		assert.Equal(t, EventRetrievalDealCompleted, events[1].Type)
+
+		var data StorageDealData
+		require.NoError(t, json.Unmarshal(events[0].Data, &data))
+		assert.Equal(t, "StorageDealActive", data.State)
+	})
+
+	t.Run("deliver more than a page", func(t *testing.T) {
+		for i := 0; i < outboxPageSize*2+1; i++ {
+			notifier.RetrievalDealCompleted(ctx, &types.ProviderDealState{TotalSent: uint64(i)})
+		}
+		notifier.deliverDue(ctx)
+		assert.Equal(t, 0, outboxLen(t, ds))
+		assert.Len(t, receiver.received(), outboxPageSize*2+3)
+	})
+}
+
+func TestWebhookBackoff(t *testing.T) {
+	notifier, err := newWebhookNotifier(config.Webhook{
+		MinBackoff: config.Duration(time.Second),
+		MaxBackoff: config.Duration(10 * time.Second),
+	}, nil)
+	require.NoError(t, err)
+
+	assert.Equal(t, time.Second, notifier.backoff(1))
+	assert.Equal(t, 2*time.Second, notifier.backoff(2))
+	assert.Equal(t, 8*time.Second, notifier.backoff(4))
+	assert.Equal(t, 10*time.Second, notifier.backoff(5))
+	assert.Equal(t, 10*time.Second, notifier.backoff(100))
+
+	_, err = newWebhookNotifier(config.Webhook{StorageDealStates: []string{"NotAState"}}, nil)
+	assert.Error(t, err)
+}
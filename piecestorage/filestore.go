@@ -59,9 +59,14 @@ func (f *fsPieceStorage) SaveTo(ctx context.Context, resourceId string, r io.Rea
 	defer func() { _ = tempFile.Close() }()
 	wlen, err := io.Copy(tempFile, r)
 	if err != nil {
+		// remove partial file, the reader may fail in the middle of transfer
+		_ = os.Remove(tempFile.Name())
 		return -1, fmt.Errorf("unable to write file to %s  %w", dstPath, err)
 	}
 	err = utils.Move(tempFile.Name(), dstPath)
+	if err == nil {
+		recordWrite(ctx, f, wlen)
+	}
 	return wlen, err
 }
 
@@ -71,7 +76,7 @@ func (f *fsPieceStorage) GetReaderCloser(ctx context.Context, resourceId string)
 	if err != nil {
 		return nil, fmt.Errorf("unable to open file %s %w", dstPath, err)
 	}
-	return fs, nil
+	return &meteredReadCloser{ReadCloser: fs, st: f}, nil
 }
 
 func (f *fsPieceStorage) GetMountReader(ctx context.Context, resourceId string) (mount.Reader, error) {
@@ -80,13 +85,20 @@ func (f *fsPieceStorage) GetMountReader(ctx context.Context, resourceId string)
 	if err != nil {
 		return nil, fmt.Errorf("unable to open file %s %w", dstPath, err)
 	}
-	return fs, nil
+	return &meteredMountReader{Reader: fs, st: f}, nil
 }
 
 func (f *fsPieceStorage) GetRedirectUrl(_ context.Context, _ string) (string, error) {
 	return "", ErrUnsupportRedirect
 }
 
+func (f *fsPieceStorage) GetPieceTransfer(_ context.Context, resourceId string) (string, error) {
+	if f.fsCfg.ReadOnly {
+		return "", fmt.Errorf("do not write to a 'readonly' piece store")
+	}
+	return path.Join(f.baseUrl, resourceId), nil
+}
+
 func (f *fsPieceStorage) Has(ctx context.Context, resourceId string) (bool, error) {
 	_, err := os.Stat(path.Join(f.baseUrl, resourceId))
 	if err != nil {
@@ -98,6 +110,13 @@ func (f *fsPieceStorage) Has(ctx context.Context, resourceId string) (bool, erro
 	return true, nil
 }
 
+func (f *fsPieceStorage) Delete(ctx context.Context, resourceId string) error {
+	if f.fsCfg.ReadOnly {
+		return fmt.Errorf("do not delete from a 'readonly' piece store")
+	}
+	return os.Remove(path.Join(f.baseUrl, resourceId))
+}
+
 func (f *fsPieceStorage) Validate(resourceId string) error {
 	st, err := os.Stat(f.baseUrl)
 	if err != nil {
@@ -122,6 +141,17 @@ func (f *fsPieceStorage) CanAllocate(size int64) bool {
 	return st.Available > size
 }
 
+func (f *fsPieceStorage) GetStorageStatus() (StorageStatus, error) {
+	st, err := fsutil.Statfs(f.baseUrl)
+	if err != nil {
+		return StorageStatus{}, fmt.Errorf("unable to get status of %s %w", f.baseUrl, err)
+	}
+	return StorageStatus{
+		Capacity:  st.Capacity,
+		Available: st.Available,
+	}, nil
+}
+
 func (f *fsPieceStorage) Type() Protocol {
 	return FS
 }
@@ -143,6 +173,5 @@ func NewFsPieceStorage(fsCfg *config.FsPieceStorage) (IPieceStorage, error) {
 }
 
 func NewS3PieceStorage(s3Cfg *config.S3PieceStorage) (IPieceStorage, error) {
-	s3 := &s3PieceStorage{s3Cfg: s3Cfg}
-	return s3, nil
+	return newS3PieceStorage(s3Cfg)
 }
@@ -19,6 +19,9 @@ type MemPieceStore struct {
 	dataLk            *sync.RWMutex
 	status            *StorageStatus //status for testing
 	RedirectResources map[string]bool
+
+	Priority int //placement priority for testing
+	Weight   int //placement weight for testing
 }
 
 func NewMemPieceStore(name string, status *StorageStatus) *MemPieceStore {
@@ -83,6 +86,10 @@ func (m *MemPieceStore) GetMountReader(ctx context.Context, resourceId string) (
 
 }
 
+func (m *MemPieceStore) GetPieceTransfer(_ context.Context, resourceId string) (string, error) {
+	return fmt.Sprintf("mock transfer resourceId %s", resourceId), nil
+}
+
 func (m *MemPieceStore) Has(ctx context.Context, resourceId string) (bool, error) {
 	m.dataLk.RLock()
 	defer m.dataLk.RUnlock()
@@ -90,6 +97,16 @@ func (m *MemPieceStore) Has(ctx context.Context, resourceId string) (bool, error
 	return ok, nil
 }
 
+func (m *MemPieceStore) Delete(ctx context.Context, resourceId string) error {
+	m.dataLk.Lock()
+	defer m.dataLk.Unlock()
+	if _, ok := m.data[resourceId]; !ok {
+		return fmt.Errorf("unable to find resource %s", resourceId)
+	}
+	delete(m.data, resourceId)
+	return nil
+}
+
 func (m *MemPieceStore) CanAllocate(size int64) bool {
 	if m.status != nil {
 		return m.status.Available > size
@@ -97,6 +114,13 @@ func (m *MemPieceStore) CanAllocate(size int64) bool {
 	return true
 }
 
+func (m *MemPieceStore) GetStorageStatus() (StorageStatus, error) {
+	if m.status != nil {
+		return *m.status, nil
+	}
+	return StorageStatus{}, nil
+}
+
 func (m *MemPieceStore) GetRedirectUrl(_ context.Context, resourceId string) (string, error) {
 	if isRedirect, ok := m.RedirectResources[resourceId]; ok {
 		if isRedirect {
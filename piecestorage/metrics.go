@@ -0,0 +1,62 @@
+package piecestorage
+
+import (
+	"context"
+	"io"
+
+	"github.com/filecoin-project/dagstore/mount"
+	"go.opencensus.io/tag"
+
+	"github.com/filecoin-project/venus-market/v2/metrics"
+)
+
+func storageTags(st IPieceStorage) []tag.Mutator {
+	return []tag.Mutator{
+		tag.Upsert(metrics.StorageName, st.GetName()),
+		tag.Upsert(metrics.StorageType, string(st.Type())),
+	}
+}
+
+// recordWrite count bytes written into piece storage
+func recordWrite(ctx context.Context, st IPieceStorage, n int64) {
+	if n > 0 {
+		metrics.Record(ctx, storageTags(st), metrics.PieceStorageWriteBytes.M(n))
+	}
+}
+
+// recordRead count bytes read from piece storage
+func recordRead(st IPieceStorage, n int) {
+	if n > 0 {
+		metrics.Record(context.Background(), storageTags(st), metrics.PieceStorageReadBytes.M(int64(n)))
+	}
+}
+
+// meteredReadCloser count bytes read through the reader of piece
+type meteredReadCloser struct {
+	io.ReadCloser
+	st IPieceStorage
+}
+
+func (r *meteredReadCloser) Read(p []byte) (int, error) {
+	n, err := r.ReadCloser.Read(p)
+	recordRead(r.st, n)
+	return n, err
+}
+
+// meteredMountReader count bytes read through the mount reader of piece, by both Read and ReadAt
+type meteredMountReader struct {
+	mount.Reader
+	st IPieceStorage
+}
+
+func (r *meteredMountReader) Read(p []byte) (int, error) {
+	n, err := r.Reader.Read(p)
+	recordRead(r.st, n)
+	return n, err
+}
+
+func (r *meteredMountReader) ReadAt(p []byte, off int64) (int, error) {
+	n, err := r.Reader.ReadAt(p, off)
+	recordRead(r.st, n)
+	return n, err
+}
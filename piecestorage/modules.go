@@ -12,5 +12,6 @@ var PieceStorageOpts = func(cfg *config.MarketConfig) builder.Option {
 		builder.Override(new(*PieceStorageManager), func(cfg *config.PieceStorage) (*PieceStorageManager, error) {
 			return NewPieceStorageManager(cfg)
 		}),
+		builder.Override(new(*RepairWorker), NewRepairWorker),
 	)
 }
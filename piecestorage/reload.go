@@ -0,0 +1,148 @@
+package piecestorage
+
+import (
+	"fmt"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+// Reload diff piece storage config against the live storages, add, remove or reconfigure storages in place.
+// all new storages are built before any of them is applied, so that a reload either applies fully or not at all.
+// readers opened before reloading keep working, s3 storages pick up new credentials for following requests
+func (p *PieceStorageManager) Reload(cfg *config.PieceStorage) (types2.ConfigReloadResult, error) {
+	var result types2.ConfigReloadResult
+
+	selector, err := newStorageSelector(cfg.Placement)
+	if err != nil {
+		return result, err
+	}
+
+	names := make(map[string]struct{})
+	for _, fsCfg := range cfg.Fs {
+		if err := checkStorageName(names, fsCfg.Name); err != nil {
+			return result, err
+		}
+	}
+	for _, s3Cfg := range cfg.S3 {
+		if err := checkStorageName(names, s3Cfg.Name); err != nil {
+			return result, err
+		}
+	}
+
+	p.reloadLk.Lock()
+	defer p.reloadLk.Unlock()
+
+	p.RLock()
+	current := make(map[string]IPieceStorage, len(p.storages))
+	for name, st := range p.storages {
+		current[name] = st
+	}
+	p.RUnlock()
+
+	// build and validate every new storage without holding the lock, so that a bad config changes nothing
+	// and slow client creation doesn't block readers
+	added := make(map[string]IPieceStorage)
+	replaced := make(map[string]IPieceStorage)
+	var s3Updates []*s3ConfigUpdate
+	for _, fsCfg := range cfg.Fs {
+		old, ok := current[fsCfg.Name].(*fsPieceStorage)
+		if ok && *old.fsCfg == *fsCfg {
+			continue
+		}
+		st, err := NewFsPieceStorage(fsCfg)
+		if err != nil {
+			return result, fmt.Errorf("unable to create fs piece storage %w", err)
+		}
+		if _, exist := current[fsCfg.Name]; exist {
+			replaced[fsCfg.Name] = st
+		} else {
+			added[fsCfg.Name] = st
+		}
+	}
+
+	for _, s3Cfg := range cfg.S3 {
+		old, ok := current[s3Cfg.Name].(*s3PieceStorage)
+		if ok {
+			oldCfg := old.config()
+			if *oldCfg == *s3Cfg {
+				continue
+			}
+			if oldCfg.EndPoint == s3Cfg.EndPoint {
+				update, err := old.prepareConfig(s3Cfg)
+				if err != nil {
+					return result, err
+				}
+				s3Updates = append(s3Updates, update)
+				continue
+			}
+		}
+		st, err := newS3PieceStorage(s3Cfg)
+		if err != nil {
+			return result, fmt.Errorf("unable to create object piece storage %w", err)
+		}
+		if _, exist := current[s3Cfg.Name]; exist {
+			replaced[s3Cfg.Name] = st
+		} else {
+			added[s3Cfg.Name] = st
+		}
+	}
+
+	// storages not from config such as memory storage are left alone
+	removed := make(map[string]IPieceStorage)
+	for name, st := range current {
+		if st.Type() != FS && st.Type() != S3 {
+			continue
+		}
+		if _, ok := names[name]; !ok {
+			removed[name] = st
+		}
+	}
+
+	// swap in all changes at once, the diff is made against the snapshot, storages added or removed by others
+	// after taking the snapshot are left as they are
+	p.Lock()
+	defer p.Unlock()
+
+	for name := range added {
+		if _, exist := p.storages[name]; exist {
+			return result, fmt.Errorf("piece storage %s is added during reloading", name)
+		}
+	}
+	for _, update := range s3Updates {
+		update.apply()
+		result.UpdatedPieceStorages = append(result.UpdatedPieceStorages, update.s3Cfg.Name)
+	}
+	for name, st := range replaced {
+		p.storages[name] = st
+		result.UpdatedPieceStorages = append(result.UpdatedPieceStorages, name)
+	}
+	for name, st := range added {
+		p.storages[name] = st
+		result.AddedPieceStorages = append(result.AddedPieceStorages, name)
+	}
+	for name, st := range removed {
+		if p.storages[name] != st {
+			continue
+		}
+		delete(p.storages, name)
+		result.RemovedPieceStorages = append(result.RemovedPieceStorages, name)
+	}
+
+	p.selector = selector
+	p.replication = newReplicationPolicy(cfg.Replication)
+	log.Infof("reload piece storage config, added %v, removed %v, updated %v",
+		result.AddedPieceStorages, result.RemovedPieceStorages, result.UpdatedPieceStorages)
+	return result, nil
+}
+
+func checkStorageName(names map[string]struct{}, name string) error {
+	if name == "" {
+		return fmt.Errorf("piece storage name is empty, must set storage name in piece storage config `name=yourname`")
+	}
+	if _, ok := names[name]; ok {
+		return fmt.Errorf("duplicate storage name: %s", name)
+	}
+	names[name] = struct{}{}
+	return nil
+}
@@ -0,0 +1,165 @@
+package piecestorage
+
+import (
+	"context"
+	"sync"
+	"time"
+
+	"github.com/ipfs-force-community/venus-common-utils/metrics"
+	"go.uber.org/fx"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+)
+
+// repairQueueSize bound the pieces waiting for replication, pieces beyond it are left to the periodic repair
+const repairQueueSize = 1024
+
+// RepairWorker replicate newly saved pieces queued to it, and periodically scan all storages and copy
+// under-replicated pieces to other storages. Queued pieces and the scan are handled one at a time, so that a piece
+// is never copied by both at once.
+type RepairWorker struct {
+	mgr      *PieceStorageManager
+	interval time.Duration
+
+	queueLk sync.Mutex
+	queued  map[string]struct{}
+	queue   chan string
+
+	statusLk sync.RWMutex
+	status   types2.PieceRepairStatus
+}
+
+func NewRepairWorker(mctx metrics.MetricsCtx, lc fx.Lifecycle, mgr *PieceStorageManager, cfg *config.PieceStorage) *RepairWorker {
+	worker := newRepairWorker(mgr, time.Duration(cfg.Replication.RepairInterval))
+	worker.status.Enabled = worker.interval > 0 && newReplicationPolicy(cfg.Replication).enabled()
+
+	ctx := metrics.LifecycleCtx(mctx, lc)
+	lc.Append(fx.Hook{
+		OnStart: func(_ context.Context) error {
+			go worker.loop(ctx)
+			return nil
+		},
+	})
+	return worker
+}
+
+func newRepairWorker(mgr *PieceStorageManager, interval time.Duration) *RepairWorker {
+	return &RepairWorker{
+		mgr:      mgr,
+		interval: interval,
+		queued:   make(map[string]struct{}),
+		queue:    make(chan string, repairQueueSize),
+	}
+}
+
+// Queue add a piece to be replicated by the worker in background. The piece is skipped if it is already waiting in
+// queue, or the queue is full, in which case the periodic repair will copy it.
+func (w *RepairWorker) Queue(resourceId string) {
+	if w == nil {
+		return
+	}
+	w.queueLk.Lock()
+	defer w.queueLk.Unlock()
+
+	if _, ok := w.queued[resourceId]; ok {
+		return
+	}
+	select {
+	case w.queue <- resourceId:
+		w.queued[resourceId] = struct{}{}
+	default:
+		log.Warnf("replication queue is full, leave piece %s to repair", resourceId)
+	}
+}
+
+func (w *RepairWorker) Status() types2.PieceRepairStatus {
+	w.statusLk.RLock()
+	defer w.statusLk.RUnlock()
+	return w.status
+}
+
+func (w *RepairWorker) loop(ctx context.Context) {
+	var tick <-chan time.Time
+	if w.Status().Enabled {
+		ticker := time.NewTicker(w.interval)
+		defer ticker.Stop()
+		tick = ticker.C
+		w.repair(ctx)
+	}
+
+	for {
+		select {
+		case resourceId := <-w.queue:
+			w.replicate(ctx, resourceId)
+		case <-tick:
+			w.repair(ctx)
+		case <-ctx.Done():
+			log.Infof("piece repair worker exit")
+			return
+		}
+	}
+}
+
+func (w *RepairWorker) replicate(ctx context.Context, resourceId string) {
+	w.queueLk.Lock()
+	delete(w.queued, resourceId)
+	w.queueLk.Unlock()
+
+	if _, err := w.mgr.ReplicatePiece(ctx, resourceId); err != nil {
+		log.Warnf("replicate piece %s: %v", resourceId, err)
+	}
+}
+
+func (w *RepairWorker) repair(ctx context.Context) {
+	w.statusLk.Lock()
+	w.status.Running = true
+	w.status.LastStart = time.Now()
+	w.statusLk.Unlock()
+
+	var status types2.PieceRepairStatus
+	// resource id -> storages which have the piece
+	holders := make(map[string][]IPieceStorage)
+	for _, st := range w.mgr.listStorages() {
+		resourceIds, err := st.ListResourceIds(ctx)
+		if err != nil {
+			log.Warnf("list pieces in storage %s: %v", st.GetName(), err)
+			status.LastError = err.Error()
+			continue
+		}
+		for _, resourceId := range resourceIds {
+			holders[resourceId] = append(holders[resourceId], st)
+		}
+	}
+
+	for resourceId, sts := range holders {
+		if ctx.Err() != nil {
+			break
+		}
+		status.Scanned++
+		if !w.mgr.IsUnderReplicated(sts) {
+			continue
+		}
+
+		status.UnderReplicated++
+		copied, err := w.mgr.ReplicatePiece(ctx, resourceId)
+		if err != nil {
+			log.Errorf("repair piece %s: %v", resourceId, err)
+			status.Failed++
+			status.LastError = err.Error()
+			continue
+		}
+		if copied > 0 {
+			status.Repaired++
+		}
+	}
+	log.Infof("piece repair finished, scanned %d, under-replicated %d, repaired %d, failed %d",
+		status.Scanned, status.UnderReplicated, status.Repaired, status.Failed)
+
+	w.statusLk.Lock()
+	status.Enabled = w.status.Enabled
+	status.LastStart = w.status.LastStart
+	status.LastEnd = time.Now()
+	w.status = status
+	w.statusLk.Unlock()
+}
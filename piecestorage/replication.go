@@ -0,0 +1,173 @@
+package piecestorage
+
+import (
+	"context"
+	"fmt"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+)
+
+// replicationPolicy describe how many copies of a piece should exist across storages
+type replicationPolicy struct {
+	copies      int
+	minS3Copies int
+}
+
+func newReplicationPolicy(cfg config.PieceReplication) replicationPolicy {
+	policy := replicationPolicy{copies: cfg.Copies, minS3Copies: cfg.MinS3Copies}
+	if policy.copies < policy.minS3Copies {
+		policy.copies = policy.minS3Copies
+	}
+	if policy.copies < 1 {
+		policy.copies = 1
+	}
+	return policy
+}
+
+// missing return the number of copies and s3 copies still needed by a piece which already placed on storages
+func (policy replicationPolicy) missing(holders []IPieceStorage) (int, int) {
+	var s3Copies int
+	for _, st := range holders {
+		if st.Type() == S3 {
+			s3Copies++
+		}
+	}
+
+	needCopies := policy.copies - len(holders)
+	needS3Copies := policy.minS3Copies - s3Copies
+	if needS3Copies < 0 {
+		needS3Copies = 0
+	}
+	if needCopies < needS3Copies {
+		needCopies = needS3Copies
+	}
+	return needCopies, needS3Copies
+}
+
+func (policy replicationPolicy) enabled() bool {
+	return policy.copies > 1 || policy.minS3Copies > 0
+}
+
+func (p *PieceStorageManager) listStorages() []IPieceStorage {
+	p.RLock()
+	defer p.RUnlock()
+
+	storages := make([]IPieceStorage, 0, len(p.storages))
+	for _, st := range p.storages {
+		storages = append(storages, st)
+	}
+	return storages
+}
+
+// IsUnderReplicated check whether a piece placed on given storages need more copies
+func (p *PieceStorageManager) IsUnderReplicated(holders []IPieceStorage) bool {
+	p.RLock()
+	policy := p.replication
+	p.RUnlock()
+
+	needCopies, _ := policy.missing(holders)
+	return needCopies > 0
+}
+
+// ReplicatePiece copy piece to other writable storages until the replication policy is satisfied,
+// return the number of copies created
+func (p *PieceStorageManager) ReplicatePiece(ctx context.Context, resourceId string) (int, error) {
+	p.RLock()
+	policy, selector := p.replication, p.selector
+	p.RUnlock()
+	if !policy.enabled() {
+		return 0, nil
+	}
+
+	var holders, candidates []IPieceStorage
+	for _, st := range p.listStorages() {
+		has, err := st.Has(ctx, resourceId)
+		if err != nil {
+			log.Warnf("check piece %s in storage %s: %v", resourceId, st.GetName(), err)
+			continue
+		}
+		if has {
+			holders = append(holders, st)
+		} else if !st.ReadOnly() {
+			candidates = append(candidates, st)
+		}
+	}
+	if len(holders) == 0 {
+		return 0, fmt.Errorf("unable to find piece in storage %s", resourceId)
+	}
+
+	needCopies, needS3Copies := policy.missing(holders)
+	if needCopies == 0 {
+		return 0, nil
+	}
+
+	src := holders[0]
+	size, err := src.Len(ctx, resourceId)
+	if err != nil {
+		return 0, fmt.Errorf("get length of piece %s in storage %s: %w", resourceId, src.GetName(), err)
+	}
+
+	var allocatable []IPieceStorage
+	for _, st := range candidates {
+		if st.CanAllocate(size) {
+			allocatable = append(allocatable, st)
+		}
+	}
+
+	var copied int
+	for copied < needCopies {
+		pool := allocatable
+		if copied < needS3Copies {
+			pool = filterByType(allocatable, S3)
+		}
+		if len(pool) == 0 {
+			return copied, fmt.Errorf("no storage available for copy %d of piece %s", len(holders)+copied+1, resourceId)
+		}
+
+		dst, err := selector.Select(highestPriority(pool))
+		if err != nil {
+			return copied, err
+		}
+		if err = copyPiece(ctx, src, dst, resourceId); err != nil {
+			return copied, err
+		}
+		log.Infof("replicate piece %s from %s to %s", resourceId, src.GetName(), dst.GetName())
+
+		copied++
+		allocatable = removeStorage(allocatable, dst)
+	}
+	return copied, nil
+}
+
+func copyPiece(ctx context.Context, src, dst IPieceStorage, resourceId string) error {
+	r, err := src.GetReaderCloser(ctx, resourceId)
+	if err != nil {
+		return fmt.Errorf("open piece %s in storage %s: %w", resourceId, src.GetName(), err)
+	}
+	defer r.Close() //nolint:errcheck
+
+	if _, err = dst.SaveTo(ctx, resourceId, r); err != nil {
+		return fmt.Errorf("copy piece %s to storage %s: %w", resourceId, dst.GetName(), err)
+	}
+	return nil
+}
+
+func filterByType(storages []IPieceStorage, protocol Protocol) []IPieceStorage {
+	var filtered []IPieceStorage
+	for _, st := range storages {
+		if st.Type() == protocol {
+			filtered = append(filtered, st)
+		}
+	}
+	return filtered
+}
+
+func removeStorage(storages []IPieceStorage, target IPieceStorage) []IPieceStorage {
+	var left []IPieceStorage
+	for _, st := range storages {
+		if st.GetName() != target.GetName() {
+			left = append(left, st)
+		}
+	}
+	return left
+}
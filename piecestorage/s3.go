@@ -6,6 +6,7 @@ import (
 	"io"
 	"net/url"
 	"strings"
+	"sync"
 	"time"
 
 	logging "github.com/ipfs/go-log/v2"
@@ -44,38 +45,114 @@ func parseS3Endpoint(endPoint string) (string, string, string, error) {
 	return endPointUrl.String(), hostSeq[0], bucket, nil
 }
 
+const (
+	// s3UsageRefreshInterval is how long the bucket usage calculated by listing objects is trusted
+	s3UsageRefreshInterval = time.Minute * 10
+	// s3UsageRetryInterval is how long to wait before listing objects again after a failed listing
+	s3UsageRetryInterval = time.Minute
+	// s3UsageListTimeout bound the time of listing all objects in bucket
+	s3UsageListTimeout = time.Minute * 5
+)
+
 type s3PieceStorage struct {
-	bucket   string
+	bucket string
+
+	// client and config can be replaced by reloading config, readers get latest client for each request
+	lk       sync.RWMutex
 	s3Client *s3.S3
 	uploader *s3manager.Uploader
 	s3Cfg    *config.S3PieceStorage
+
+	// usage is tracked by saving and deleting objects, and corrected by listing the bucket in background
+	usageLk    sync.Mutex
+	used       int64
+	usageKnown bool
+	refreshing bool
+	refreshAt  time.Time
 }
 
 func newS3PieceStorage(s3Cfg *config.S3PieceStorage) (IPieceStorage, error) {
-	endpoint, region, bucket, err := parseS3Endpoint(s3Cfg.EndPoint)
+	s3Client, uploader, bucket, err := newS3Client(s3Cfg)
 	if err != nil {
 		return nil, err
 	}
-	sess := session.Must(session.NewSession(&aws.Config{
+	st := &s3PieceStorage{s3Cfg: s3Cfg, bucket: bucket, s3Client: s3Client, uploader: uploader}
+	if s3Cfg.Quota > 0 {
+		// list the bucket ahead, so that the usage is known before the first write
+		st.usage()
+	}
+	return st, nil
+}
+
+func newS3Client(s3Cfg *config.S3PieceStorage) (*s3.S3, *s3manager.Uploader, string, error) {
+	endpoint, region, bucket, err := parseS3Endpoint(s3Cfg.EndPoint)
+	if err != nil {
+		return nil, nil, "", err
+	}
+	sess, err := session.NewSession(&aws.Config{
 		Credentials:      credentials.NewStaticCredentials(s3Cfg.AccessKey, s3Cfg.SecretKey, s3Cfg.Token),
 		Endpoint:         aws.String(endpoint),
 		S3ForcePathStyle: aws.Bool(false),
 		Region:           aws.String(region),
 		//LogLevel:         aws.LogLevel(aws.LogDebug),
-	}))
+	})
+	if err != nil {
+		return nil, nil, "", fmt.Errorf("create s3 session %w", err)
+	}
 	uploader := s3manager.NewUploader(sess, func(uploader *s3manager.Uploader) {
 		uploader.Concurrency = 8
 	})
-	return &s3PieceStorage{s3Cfg: s3Cfg, bucket: bucket, s3Client: s3.New(sess), uploader: uploader}, nil
+	return s3.New(sess), uploader, bucket, nil
+}
+
+// s3ConfigUpdate is a new config of an s3 storage with its client built and validated, but not applied yet
+type s3ConfigUpdate struct {
+	st       *s3PieceStorage
+	s3Cfg    *config.S3PieceStorage
+	s3Client *s3.S3
+	uploader *s3manager.Uploader
+}
+
+// prepareConfig build client for the new credentials and options of storage, the endpoint must not be changed
+func (s *s3PieceStorage) prepareConfig(s3Cfg *config.S3PieceStorage) (*s3ConfigUpdate, error) {
+	s3Client, uploader, bucket, err := newS3Client(s3Cfg)
+	if err != nil {
+		return nil, err
+	}
+	if bucket != s.bucket {
+		return nil, fmt.Errorf("bucket of s3 piece storage %s can not be changed from %s to %s", s3Cfg.Name, s.bucket, bucket)
+	}
+	return &s3ConfigUpdate{st: s, s3Cfg: s3Cfg, s3Client: s3Client, uploader: uploader}, nil
+}
+
+func (u *s3ConfigUpdate) apply() {
+	u.st.lk.Lock()
+	defer u.st.lk.Unlock()
+	u.st.s3Client, u.st.uploader, u.st.s3Cfg = u.s3Client, u.uploader, u.s3Cfg
+}
+
+func (s *s3PieceStorage) client() *s3.S3 {
+	s.lk.RLock()
+	defer s.lk.RUnlock()
+	return s.s3Client
+}
+
+func (s *s3PieceStorage) config() *config.S3PieceStorage {
+	s.lk.RLock()
+	defer s.lk.RUnlock()
+	return s.s3Cfg
 }
 
 func (s *s3PieceStorage) SaveTo(ctx context.Context, resourceId string, r io.Reader) (int64, error) {
-	if s.s3Cfg.ReadOnly {
+	s.lk.RLock()
+	readOnly, uploader := s.s3Cfg.ReadOnly, s.uploader
+	s.lk.RUnlock()
+	if readOnly {
 		return 0, fmt.Errorf("do not write to a 'readonly' piece store")
 	}
 
 	countReader := utils.NewCounterBufferReader(r)
-	resp, err := s.uploader.Upload(&s3manager.UploadInput{
+	resp, err := uploader.Upload(&s3manager.UploadInput{
 		Bucket: aws.String(s.bucket),
 		Key:    aws.String(resourceId),
 		Body:   countReader,
@@ -84,16 +161,18 @@ func (s *s3PieceStorage) SaveTo(ctx context.Context, resourceId string, r io.Rea
 		return 0, err
 	}
 	log.Infof("update file to s3 piece storage, upload id %s", resp.UploadID)
+	s.addUsage(int64(countReader.Count()))
+	recordWrite(ctx, s, int64(countReader.Count()))
 	return int64(countReader.Count()), nil
 }
 
 func (s *s3PieceStorage) Len(ctx context.Context, piececid string) (int64, error) {
-	params := &s3.GetObjectInput{
+	params := &s3.HeadObjectInput{
 		Bucket: aws.String(s.bucket),
 		Key:    aws.String(piececid),
 	}
 
-	result, err := s.s3Client.GetObject(params)
+	result, err := s.client().HeadObject(params)
 	if err != nil {
 		return 0, err
 	}
@@ -105,7 +184,7 @@ func (s *s3PieceStorage) ListResourceIds(ctx context.Context) ([]string, error)
 		Bucket: aws.String(s.bucket),
 	}
 
-	result, err := s.s3Client.ListObjectsV2(params)
+	result, err := s.client().ListObjectsV2(params)
 	if err != nil {
 		return nil, err
 	}
@@ -119,28 +198,28 @@ func (s *s3PieceStorage) ListResourceIds(ctx context.Context) ([]string, error)
 	return pieces, nil
 }
 
-func (s s3PieceStorage) GetReaderCloser(ctx context.Context, resourceId string) (io.ReadCloser, error) {
+func (s *s3PieceStorage) GetReaderCloser(ctx context.Context, resourceId string) (io.ReadCloser, error) {
 	params := &s3.GetObjectInput{
 		Bucket: aws.String(s.bucket),
 		Key:    aws.String(resourceId),
 	}
 
-	result, err := s.s3Client.GetObject(params)
+	result, err := s.client().GetObject(params)
 	if err != nil {
 		return nil, err
 	}
-	return result.Body, nil
+	return &meteredReadCloser{ReadCloser: result.Body, st: s}, nil
 }
 
-func (s s3PieceStorage) GetMountReader(ctx context.Context, resourceId string) (mount.Reader, error) {
+func (s *s3PieceStorage) GetMountReader(ctx context.Context, resourceId string) (mount.Reader, error) {
 	len, err := s.Len(ctx, resourceId)
 	if err != nil {
 		return nil, err
 	}
-	return newSeekWraper(s.s3Client, s.bucket, resourceId, len-1), nil
+	return &meteredMountReader{Reader: newSeekWraper(s, resourceId, len-1), st: s}, nil
 }
 
-func (s s3PieceStorage) GetRedirectUrl(ctx context.Context, resourceId string) (string, error) {
+func (s *s3PieceStorage) GetRedirectUrl(ctx context.Context, resourceId string) (string, error) {
 	if has, err := s.Has(ctx, resourceId); err != nil {
 		return "", fmt.Errorf("check object: %s exist error:%w", resourceId, err)
 	} else if !has {
@@ -152,12 +231,106 @@ func (s s3PieceStorage) GetRedirectUrl(ctx context.Context, resourceId string) (
 		Key:    aws.String(resourceId),
 	}
 
-	req, _ := s.s3Client.GetObjectRequest(params)
+	req, _ := s.client().GetObjectRequest(params)
+	return req.Presign(time.Hour * 24)
+}
+
+func (s *s3PieceStorage) GetPieceTransfer(_ context.Context, resourceId string) (string, error) {
+	if s.config().ReadOnly {
+		return "", fmt.Errorf("do not write to a 'readonly' piece store")
+	}
+
+	params := &s3.PutObjectInput{
+		Bucket: aws.String(s.bucket),
+		Key:    aws.String(resourceId),
+	}
+
+	req, _ := s.client().PutObjectRequest(params)
 	return req.Presign(time.Hour * 24)
 }
 
 func (s *s3PieceStorage) CanAllocate(size int64) bool {
-	return true
+	quota := s.config().Quota
+	if quota <= 0 {
+		return true
+	}
+
+	used, ok := s.usage()
+	if !ok {
+		log.Warnf("usage of bucket %s is not calculated yet", s.bucket)
+		return false
+	}
+	return quota-used > size
+}
+
+func (s *s3PieceStorage) GetStorageStatus() (StorageStatus, error) {
+	quota := s.config().Quota
+	if quota <= 0 {
+		return StorageStatus{}, nil
+	}
+
+	used, ok := s.usage()
+	if !ok {
+		return StorageStatus{}, fmt.Errorf("usage of bucket %s is not calculated yet", s.bucket)
+	}
+	available := quota - used
+	if available < 0 {
+		available = 0
+	}
+	return StorageStatus{
+		Capacity:  quota,
+		Available: available,
+	}, nil
+}
+
+// usage return bytes stored in bucket and whether it is known. It never lists the bucket itself, but starts a
+// listing in background when the usage is not known or not refreshed for a while.
+func (s *s3PieceStorage) usage() (int64, bool) {
+	s.usageLk.Lock()
+	defer s.usageLk.Unlock()
+
+	if !s.refreshing && !time.Now().Before(s.refreshAt) {
+		s.refreshing = true
+		go s.refreshUsage()
+	}
+	return s.used, s.usageKnown
+}
+
+// refreshUsage calculate the usage by listing all objects in bucket. Objects saved or deleted during the listing
+// may be counted twice or missed, which is corrected by the next refresh.
+func (s *s3PieceStorage) refreshUsage() {
+	ctx, cancel := context.WithTimeout(context.Background(), s3UsageListTimeout)
+	defer cancel()
+
+	var used int64
+	err := s.client().ListObjectsV2PagesWithContext(ctx, &s3.ListObjectsV2Input{
+		Bucket: aws.String(s.bucket),
+	}, func(page *s3.ListObjectsV2Output, lastPage bool) bool {
+		for _, obj := range page.Contents {
+			if obj.Size != nil {
+				used += *obj.Size
+			}
+		}
+		return true
+	})
+
+	s.usageLk.Lock()
+	defer s.usageLk.Unlock()
+	s.refreshing = false
+	if err != nil {
+		log.Warnf("list objects of bucket %s for usage: %v", s.bucket, err)
+		s.refreshAt = time.Now().Add(s3UsageRetryInterval)
+		return
+	}
+	s.used = used
+	s.usageKnown = true
+	s.refreshAt = time.Now().Add(s3UsageRefreshInterval)
+}
+
+func (s *s3PieceStorage) addUsage(size int64) {
+	s.usageLk.Lock()
+	defer s.usageLk.Unlock()
+	s.used += size
 }
 
 func (s *s3PieceStorage) Has(ctx context.Context, piececid string) (bool, error) {
@@ -166,7 +339,7 @@ func (s *s3PieceStorage) Has(ctx context.Context, piececid string) (bool, error)
 		Key:    aws.String(piececid),
 	}
 
-	_, err := s.s3Client.HeadObject(params)
+	_, err := s.client().HeadObject(params)
 	if err != nil {
 		if aerr, ok := err.(awserr.Error); ok {
 			switch aerr.Code() {
@@ -181,8 +354,28 @@ func (s *s3PieceStorage) Has(ctx context.Context, piececid string) (bool, error)
 	return true, nil
 }
 
+func (s *s3PieceStorage) Delete(ctx context.Context, resourceId string) error {
+	if s.config().ReadOnly {
+		return fmt.Errorf("do not delete from a 'readonly' piece store")
+	}
+
+	size, err := s.Len(ctx, resourceId)
+	if err != nil {
+		return err
+	}
+	_, err = s.client().DeleteObject(&s3.DeleteObjectInput{
+		Bucket: aws.String(s.bucket),
+		Key:    aws.String(resourceId),
+	})
+	if err != nil {
+		return err
+	}
+	s.addUsage(-size)
+	return nil
+}
+
 func (s *s3PieceStorage) Validate(piececid string) error {
-	_, err := s.s3Client.GetBucketAcl(&s3.GetBucketAclInput{
+	_, err := s.client().GetBucketAcl(&s3.GetBucketAclInput{
 		Bucket: aws.String(s.bucket),
 	})
 	return err
@@ -193,43 +386,32 @@ func (s *s3PieceStorage) Type() Protocol {
 }
 
 func (s *s3PieceStorage) ReadOnly() bool {
-	return s.s3Cfg.ReadOnly
+	return s.config().ReadOnly
 }
 
 func (s *s3PieceStorage) GetName() string {
-	return s.s3Cfg.Name
+	return s.config().Name
 }
 
 var _ mount.Reader = (*seekWraper)(nil)
 
 type seekWraper struct {
-	s3Client   *s3.S3
-	bucket     string
+	storage    *s3PieceStorage
 	resourceId string
 	len        int64
 	offset     int64
 }
 
-func newSeekWraper(s3Client *s3.S3, bucket, resourceId string, len int64) *seekWraper {
-	return &seekWraper{s3Client, bucket, resourceId, len, 0}
+func newSeekWraper(storage *s3PieceStorage, resourceId string, len int64) *seekWraper {
+	return &seekWraper{storage, resourceId, len, 0}
 }
 
 func (sw *seekWraper) Read(p []byte) (n int, err error) {
-	params := &s3.GetObjectInput{
-		Bucket: aws.String(sw.bucket),
-		Key:    aws.String(sw.resourceId),
-		Range:  aws.String(fmt.Sprintf("bytes=%d-%d", sw.offset, sw.offset+int64(len(p)))),
-	}
-
-	result, err := sw.s3Client.GetObject(params)
-	if err != nil {
-		return 0, err
-	}
-	n, err = result.Body.Read(p)
-	if err != nil {
-		return 0, err
-	}
+	n, err = sw.ReadAt(p, sw.offset)
 	sw.offset = sw.offset + int64(n)
+	if err == io.EOF && n > 0 {
+		err = nil
+	}
 	return
 }
 
@@ -241,23 +423,36 @@ func (sw *seekWraper) Seek(offset int64, whence int) (int64, error) {
 	return sw.offset, nil
 }
 
+// ReadAt read data in range [off, off+len(p)) with a ranged request, sw.len is the index of the last byte
 func (sw *seekWraper) ReadAt(p []byte, off int64) (n int, err error) {
-	maxLen := off + int64(len(p))
-	if maxLen > sw.len {
-		maxLen = sw.len
+	if len(p) == 0 {
+		return 0, nil
+	}
+	if off > sw.len {
+		return 0, io.EOF
+	}
+	end := off + int64(len(p)) - 1
+	if end > sw.len {
+		end = sw.len
 	}
 	params := &s3.GetObjectInput{
-		Bucket: aws.String(sw.bucket),
+		Bucket: aws.String(sw.storage.bucket),
 		Key:    aws.String(sw.resourceId),
-		Range:  aws.String(fmt.Sprintf("bytes=%d-%d", off, maxLen)),
+		Range:  aws.String(fmt.Sprintf("bytes=%d-%d", off, end)),
 	}
 
-	req, result := sw.s3Client.GetObjectRequest(params)
+	req, result := sw.storage.client().GetObjectRequest(params)
 	err = req.Send()
 	if err != nil {
 		return 0, err
 	}
-	return io.ReadFull(result.Body, p)
+	defer result.Body.Close() //nolint:errcheck
+
+	n, err = io.ReadFull(result.Body, p[:end-off+1])
+	if err == nil && n < len(p) {
+		err = io.EOF
+	}
+	return n, err
 }
 
 func (sw *seekWraper) Close() error {
@@ -0,0 +1,190 @@
+package piecestorage
+
+import (
+	"fmt"
+	"math/rand"
+	"sort"
+	"sync/atomic"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+)
+
+// storageSelector choose one storage from writable storages which all have enough space for the piece
+type storageSelector interface {
+	Select(storages []IPieceStorage) (IPieceStorage, error)
+}
+
+func newStorageSelector(policy string) (storageSelector, error) {
+	switch policy {
+	case "", config.PlacementRandom:
+		return &randomSelector{}, nil
+	case config.PlacementWeighted:
+		return &weightedSelector{}, nil
+	case config.PlacementRoundRobin:
+		return &roundRobinSelector{}, nil
+	case config.PlacementFillFirst:
+		return &fillFirstSelector{}, nil
+	default:
+		return nil, fmt.Errorf("unknown piece storage placement policy %s", policy)
+	}
+}
+
+// placementOf return priority and weight of storage, weight is always positive
+func placementOf(st IPieceStorage) (int, int) {
+	var priority, weight int
+	switch s := st.(type) {
+	case *fsPieceStorage:
+		priority, weight = s.fsCfg.Priority, s.fsCfg.Weight
+	case *s3PieceStorage:
+		cfg := s.config()
+		priority, weight = cfg.Priority, cfg.Weight
+	case *MemPieceStore:
+		priority, weight = s.Priority, s.Weight
+	}
+	if weight <= 0 {
+		weight = 1
+	}
+	return priority, weight
+}
+
+// highestPriority filter out storages which have lower priority than others
+func highestPriority(storages []IPieceStorage) []IPieceStorage {
+	var selected []IPieceStorage
+	var maxPriority int
+	for _, st := range storages {
+		priority, _ := placementOf(st)
+		if len(selected) == 0 || priority > maxPriority {
+			selected = []IPieceStorage{st}
+			maxPriority = priority
+		} else if priority == maxPriority {
+			selected = append(selected, st)
+		}
+	}
+	return selected
+}
+
+func sortByName(storages []IPieceStorage) {
+	sort.Slice(storages, func(i, j int) bool {
+		return storages[i].GetName() < storages[j].GetName()
+	})
+}
+
+// availableOf return available space of storage, storage without capacity limit return false
+func availableOf(st IPieceStorage) (int64, bool) {
+	status, err := st.GetStorageStatus()
+	if err != nil {
+		log.Warnf("unable to get status of storage %s: %v", st.GetName(), err)
+		return 0, true
+	}
+	if status.Capacity <= 0 {
+		return 0, false
+	}
+	return status.Available, true
+}
+
+type randomSelector struct{}
+
+func (s *randomSelector) Select(storages []IPieceStorage) (IPieceStorage, error) {
+	if len(storages) == 0 {
+		return nil, fmt.Errorf("given storages is zero")
+	}
+
+	weights := make([]float64, len(storages))
+	for idx, st := range storages {
+		_, weight := placementOf(st)
+		weights[idx] = float64(weight)
+	}
+	return weightedPick(storages, weights), nil
+}
+
+// weightedSelector choose storage randomly with probability proportional to free space * weight.
+// storages without capacity limit are treated as having as much free space as the emptiest limited storage
+type weightedSelector struct{}
+
+func (s *weightedSelector) Select(storages []IPieceStorage) (IPieceStorage, error) {
+	if len(storages) == 0 {
+		return nil, fmt.Errorf("given storages is zero")
+	}
+
+	var maxAvailable int64 = 1
+	availables := make([]int64, len(storages))
+	limited := make([]bool, len(storages))
+	for idx, st := range storages {
+		availables[idx], limited[idx] = availableOf(st)
+		if limited[idx] && availables[idx] > maxAvailable {
+			maxAvailable = availables[idx]
+		}
+	}
+
+	weights := make([]float64, len(storages))
+	for idx, st := range storages {
+		_, weight := placementOf(st)
+		available := availables[idx]
+		if !limited[idx] {
+			available = maxAvailable
+		}
+		weights[idx] = float64(available) * float64(weight)
+	}
+	return weightedPick(storages, weights), nil
+}
+
+type roundRobinSelector struct {
+	next uint64
+}
+
+func (s *roundRobinSelector) Select(storages []IPieceStorage) (IPieceStorage, error) {
+	if len(storages) == 0 {
+		return nil, fmt.Errorf("given storages is zero")
+	}
+
+	sorted := append([]IPieceStorage{}, storages...)
+	sortByName(sorted)
+	idx := atomic.AddUint64(&s.next, 1) - 1
+	return sorted[idx%uint64(len(sorted))], nil
+}
+
+// fillFirstSelector choose the storage with least free space, so that a storage is filled up before moving to next one.
+// storages without capacity limit are used after all limited storages are full
+type fillFirstSelector struct{}
+
+func (s *fillFirstSelector) Select(storages []IPieceStorage) (IPieceStorage, error) {
+	if len(storages) == 0 {
+		return nil, fmt.Errorf("given storages is zero")
+	}
+
+	sorted := append([]IPieceStorage{}, storages...)
+	sortByName(sorted)
+
+	var selected IPieceStorage
+	var selectedAvailable int64
+	var selectedLimited bool
+	for _, st := range sorted {
+		available, limited := availableOf(st)
+		switch {
+		case selected == nil,
+			limited && !selectedLimited,
+			limited && selectedLimited && available < selectedAvailable:
+			selected, selectedAvailable, selectedLimited = st, available, limited
+		}
+	}
+	return selected, nil
+}
+
+func weightedPick(storages []IPieceStorage, weights []float64) IPieceStorage {
+	var total float64
+	for _, w := range weights {
+		total += w
+	}
+	if total <= 0 {
+		return storages[rand.Intn(len(storages))]
+	}
+
+	r := rand.Float64() * total
+	for idx, w := range weights {
+		if r < w {
+			return storages[idx]
+		}
+		r -= w
+	}
+	return storages[len(storages)-1]
+}
@@ -12,12 +12,22 @@ import (
 
 type PieceStorageManager struct {
 	sync.RWMutex
-	storages map[string]IPieceStorage
+	storages    map[string]IPieceStorage
+	selector    storageSelector
+	replication replicationPolicy
+
+	// reloadLk serializes config reloads, so the storages seen while preparing a reload are not changed by another one
+	reloadLk sync.Mutex
 }
 
 func NewPieceStorageManager(cfg *config.PieceStorage) (*PieceStorageManager, error) {
 	var storages = make(map[string]IPieceStorage)
 
+	selector, err := newStorageSelector(cfg.Placement)
+	if err != nil {
+		return nil, err
+	}
+
 	// todo: extract name check logic to a function and check blank in name
 
 	for _, fsCfg := range cfg.Fs {
@@ -53,10 +63,24 @@ func NewPieceStorageManager(cfg *config.PieceStorage) (*PieceStorageManager, err
 		}
 		storages[s3Cfg.Name] = st
 	}
-	return &PieceStorageManager{storages: storages}, nil
+	return &PieceStorageManager{
+		storages:    storages,
+		selector:    selector,
+		replication: newReplicationPolicy(cfg.Replication),
+	}, nil
 }
 
 func (p *PieceStorageManager) FindStorageForRead(ctx context.Context, s string) (IPieceStorage, error) {
+	storages := p.FindStoragesForRead(ctx, s)
+	if len(storages) == 0 {
+		return nil, fmt.Errorf("unable to find piece in storage %s", s)
+	}
+
+	return randStorageSelector(storages)
+}
+
+// FindStoragesForRead return all storages which have the resource
+func (p *PieceStorageManager) FindStoragesForRead(ctx context.Context, s string) []IPieceStorage {
 	var storages []IPieceStorage
 	p.RLock()
 	defer p.RUnlock()
@@ -71,12 +95,7 @@ func (p *PieceStorageManager) FindStorageForRead(ctx context.Context, s string)
 			storages = append(storages, st)
 		}
 	}
-
-	if len(storages) == 0 {
-		return nil, fmt.Errorf("unable to find piece in storage %s", s)
-	}
-
-	return randStorageSelector(storages)
+	return storages
 }
 
 func (p *PieceStorageManager) FindStorageForWrite(size int64) (IPieceStorage, error) {
@@ -94,8 +113,7 @@ func (p *PieceStorageManager) FindStorageForWrite(size int64) (IPieceStorage, er
 	if len(storages) == 0 {
 		return nil, fmt.Errorf("unable to find enough space for size %d", size)
 	}
-	//todo better to use argorithems base on stroage capacity and usage
-	return randStorageSelector(storages)
+	return p.selector.Select(highestPriority(storages))
 }
 
 func (p *PieceStorageManager) GetPieceStorageByName(name string) (IPieceStorage, error) {
@@ -159,7 +177,7 @@ func (p *PieceStorageManager) ListStorageInfos() types.PieceStorageInfos {
 	for _, st := range p.storages {
 		switch st.Type() {
 		case S3:
-			cfg := st.(*s3PieceStorage).s3Cfg
+			cfg := st.(*s3PieceStorage).config()
 			s3 = append(s3, types.S3Storage{
 				Name:     cfg.Name,
 				EndPoint: cfg.EndPoint,
@@ -1,6 +1,8 @@
 package piecestorage
 
 import (
+	"bytes"
+	"context"
 	"fmt"
 	"os"
 	"testing"
@@ -106,3 +108,182 @@ func TestRandSelect(t *testing.T) {
 	_, err = psm.GetPieceStorageByName("10")
 	assert.NotNil(t, err)
 }
+
+func TestPrioritySelect(t *testing.T) {
+	psm, err := NewPieceStorageManager(&config.PieceStorage{})
+	assert.Nil(t, err)
+
+	low := NewMemPieceStore("low", nil)
+	high := NewMemPieceStore("high", nil)
+	high.Priority = 1
+	full := NewMemPieceStore("full", &StorageStatus{Capacity: 1024, Available: 0})
+	full.Priority = 2
+	psm.AddMemPieceStorage(low)
+	psm.AddMemPieceStorage(high)
+	psm.AddMemPieceStorage(full)
+
+	for i := 0; i < 100; i++ {
+		st, err := psm.FindStorageForWrite(1024)
+		assert.Nil(t, err)
+		assert.Equal(t, "high", st.GetName())
+	}
+}
+
+func TestRoundRobinSelect(t *testing.T) {
+	psm, err := NewPieceStorageManager(&config.PieceStorage{Placement: config.PlacementRoundRobin})
+	assert.Nil(t, err)
+	psm.AddMemPieceStorage(NewMemPieceStore("1", nil))
+	psm.AddMemPieceStorage(NewMemPieceStore("2", nil))
+	psm.AddMemPieceStorage(NewMemPieceStore("3", nil))
+
+	selectName := []string{}
+	for i := 0; i < 6; i++ {
+		st, err := psm.FindStorageForWrite(1024)
+		assert.Nil(t, err)
+		selectName = append(selectName, st.GetName())
+	}
+	assert.Equal(t, []string{"1", "2", "3", "1", "2", "3"}, selectName)
+}
+
+func TestFillFirstSelect(t *testing.T) {
+	psm, err := NewPieceStorageManager(&config.PieceStorage{Placement: config.PlacementFillFirst})
+	assert.Nil(t, err)
+	psm.AddMemPieceStorage(NewMemPieceStore("unlimited", nil))
+	psm.AddMemPieceStorage(NewMemPieceStore("empty", &StorageStatus{Capacity: 1 << 30, Available: 1 << 30}))
+	psm.AddMemPieceStorage(NewMemPieceStore("half", &StorageStatus{Capacity: 1 << 30, Available: 1 << 29}))
+
+	st, err := psm.FindStorageForWrite(1024)
+	assert.Nil(t, err)
+	assert.Equal(t, "half", st.GetName())
+
+	// too large for limited storages
+	st, err = psm.FindStorageForWrite(1 << 31)
+	assert.Nil(t, err)
+	assert.Equal(t, "unlimited", st.GetName())
+}
+
+func TestWeightedSelect(t *testing.T) {
+	psm, err := NewPieceStorageManager(&config.PieceStorage{Placement: config.PlacementWeighted})
+	assert.Nil(t, err)
+	psm.AddMemPieceStorage(NewMemPieceStore("large", &StorageStatus{Capacity: 1 << 40, Available: 1 << 40}))
+	psm.AddMemPieceStorage(NewMemPieceStore("small", &StorageStatus{Capacity: 1 << 40, Available: 1 << 20}))
+
+	counts := map[string]int{}
+	for i := 0; i < 1000; i++ {
+		st, err := psm.FindStorageForWrite(1024)
+		assert.Nil(t, err)
+		counts[st.GetName()]++
+	}
+	assert.Greater(t, counts["large"], counts["small"])
+
+	_, err = NewPieceStorageManager(&config.PieceStorage{Placement: "unknown"})
+	assert.NotNil(t, err)
+}
+
+func TestReplicatePiece(t *testing.T) {
+	ctx := context.Background()
+	psm, err := NewPieceStorageManager(&config.PieceStorage{
+		Replication: config.PieceReplication{Copies: 2},
+	})
+	assert.Nil(t, err)
+
+	src := NewMemPieceStore("1", nil)
+	psm.AddMemPieceStorage(src)
+	psm.AddMemPieceStorage(NewMemPieceStore("2", nil))
+	psm.AddMemPieceStorage(NewMemPieceStore("3", &StorageStatus{Capacity: 1024, Available: 0}))
+
+	_, err = src.SaveTo(ctx, "piece", bytes.NewReader([]byte("piece data")))
+	assert.Nil(t, err)
+	assert.True(t, psm.IsUnderReplicated([]IPieceStorage{src}))
+
+	copied, err := psm.ReplicatePiece(ctx, "piece")
+	assert.Nil(t, err)
+	assert.Equal(t, 1, copied)
+
+	dst, err := psm.GetPieceStorageByName("2")
+	assert.Nil(t, err)
+	has, err := dst.Has(ctx, "piece")
+	assert.Nil(t, err)
+	assert.True(t, has)
+
+	// already satisfied
+	copied, err = psm.ReplicatePiece(ctx, "piece")
+	assert.Nil(t, err)
+	assert.Equal(t, 0, copied)
+
+	_, err = psm.ReplicatePiece(ctx, "not-exist")
+	assert.NotNil(t, err)
+}
+
+func TestRepairWorkerQueue(t *testing.T) {
+	ctx := context.Background()
+	psm, err := NewPieceStorageManager(&config.PieceStorage{
+		Replication: config.PieceReplication{Copies: 2},
+	})
+	assert.Nil(t, err)
+	src := NewMemPieceStore("1", nil)
+	dst := NewMemPieceStore("2", nil)
+	psm.AddMemPieceStorage(src)
+	psm.AddMemPieceStorage(dst)
+	_, err = src.SaveTo(ctx, "piece", bytes.NewReader([]byte("piece data")))
+	assert.Nil(t, err)
+
+	worker := newRepairWorker(psm, 0)
+	worker.queue = make(chan string, 1)
+	worker.Queue("piece")
+	// deduplicated while waiting
+	worker.Queue("piece")
+	// dropped when the queue is full
+	worker.Queue("other")
+	assert.Len(t, worker.queue, 1)
+
+	worker.replicate(ctx, <-worker.queue)
+	has, err := dst.Has(ctx, "piece")
+	assert.Nil(t, err)
+	assert.True(t, has)
+
+	// queued again after being handled
+	worker.Queue("piece")
+	assert.Len(t, worker.queue, 1)
+
+	var nilWorker *RepairWorker
+	nilWorker.Queue("piece")
+}
+
+func TestReloadPieceStorage(t *testing.T) {
+	psm, err := NewPieceStorageManager(&config.PieceStorage{
+		Fs: []*config.FsPieceStorage{
+			{Name: "a", Path: t.TempDir()},
+			{Name: "b", Path: t.TempDir()},
+		},
+	})
+	assert.Nil(t, err)
+	psm.AddMemPieceStorage(NewMemPieceStore("mem", nil))
+
+	cPath := t.TempDir()
+	result, err := psm.Reload(&config.PieceStorage{
+		Placement: config.PlacementRoundRobin,
+		Fs: []*config.FsPieceStorage{
+			{Name: "a", Path: cPath, ReadOnly: true},
+			{Name: "c", Path: t.TempDir()},
+		},
+	})
+	assert.Nil(t, err)
+	assert.Equal(t, []string{"c"}, result.AddedPieceStorages)
+	assert.Equal(t, []string{"b"}, result.RemovedPieceStorages)
+	assert.Equal(t, []string{"a"}, result.UpdatedPieceStorages)
+
+	st, err := psm.GetPieceStorageByName("a")
+	assert.Nil(t, err)
+	assert.True(t, st.ReadOnly())
+	_, err = psm.GetPieceStorageByName("mem")
+	assert.Nil(t, err)
+
+	// invalid config changes nothing
+	_, err = psm.Reload(&config.PieceStorage{
+		Fs: []*config.FsPieceStorage{{Name: "a", Path: cPath}, {Name: "a", Path: cPath}},
+	})
+	assert.NotNil(t, err)
+	_, err = psm.GetPieceStorageByName("c")
+	assert.Nil(t, err)
+}
@@ -22,7 +22,7 @@ const (
 )
 
 type StorageStatus struct {
-	Capacity  int64
+	Capacity  int64 // Capacity of storage, zero means unlimited
 	Available int64 // Available to use for sector storage
 }
 
@@ -40,7 +40,13 @@ type IPieceStorage interface {
 	GetMountReader(context.Context, string) (mount.Reader, error)
 	//GetRedirectUrl get url if storage support redirect
 	GetRedirectUrl(context.Context, string) (string, error)
+	//GetPieceTransfer get destination for others to write the piece into this storage, file path or presigned url
+	GetPieceTransfer(context.Context, string) (string, error)
 	Has(context.Context, string) (bool, error)
+	//Delete remove the resource from storage
+	Delete(context.Context, string) error
 	Validate(string) error
 	CanAllocate(int64) bool
+	//GetStorageStatus get capacity and available space of storage
+	GetStorageStatus() (StorageStatus, error)
 }
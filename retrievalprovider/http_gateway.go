@@ -0,0 +1,282 @@
+package retrievalprovider
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/filecoin-project/go-fil-markets/retrievalmarket"
+	"github.com/filecoin-project/go-fil-markets/stores"
+	"github.com/filecoin-project/go-state-types/big"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-cid"
+	"github.com/ipld/go-car"
+	"github.com/ipld/go-ipld-prime/datamodel"
+	"github.com/ipld/go-ipld-prime/node/basicnode"
+	"github.com/ipld/go-ipld-prime/traversal/selector"
+	"github.com/ipld/go-ipld-prime/traversal/selector/builder"
+	selectorparse "github.com/ipld/go-ipld-prime/traversal/selector/parse"
+	textselector "github.com/ipld/go-ipld-selector-text-lite"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
+)
+
+const (
+	ipfsPathPrefix  = "/ipfs/"
+	piecePathPrefix = "/piece/"
+)
+
+// HttpRetrievalGateway serves free retrieval over plain http. Responses are trustless: blocks of the CAR can be
+// verified against the root cid, and piece data can be verified against the piece cid.
+//
+//  GET /ipfs/<cid>[/<path>][?selector=<json selector>]  CAR of the dag, scoped by ipld path or selector
+//  GET /piece/<pieceCid>                               raw piece data, support range requests
+type HttpRetrievalGateway struct {
+	pieceInfo       *PieceInfo
+	dagStore        stores.DAGStoreWrapper
+	pieceStorageMgr *piecestorage.PieceStorageManager
+	dealFilter      config.RetrievalDealFilter
+	blocklist       config.StorageDealPieceCidBlocklistConfigFunc
+}
+
+var _ http.Handler = (*HttpRetrievalGateway)(nil)
+
+func NewHttpRetrievalGateway(dagStore stores.DAGStoreWrapper,
+	repo repo.Repo,
+	pieceStorageMgr *piecestorage.PieceStorageManager,
+	dealFilter config.RetrievalDealFilter,
+	blocklist config.StorageDealPieceCidBlocklistConfigFunc,
+) *HttpRetrievalGateway {
+	return &HttpRetrievalGateway{
+		pieceInfo:       NewPieceInfo(dagStore, repo.StorageDealRepo(), pieceStorageMgr),
+		dagStore:        dagStore,
+		pieceStorageMgr: pieceStorageMgr,
+		dealFilter:      dealFilter,
+		blocklist:       blocklist,
+	}
+}
+
+func (gw *HttpRetrievalGateway) ServeHTTP(res http.ResponseWriter, req *http.Request) {
+	if req.Method != http.MethodGet && req.Method != http.MethodHead {
+		httpError(res, http.StatusText(http.StatusMethodNotAllowed), http.StatusMethodNotAllowed)
+		return
+	}
+
+	switch {
+	case strings.HasPrefix(req.URL.Path, ipfsPathPrefix):
+		gw.serveDag(res, req, strings.TrimPrefix(req.URL.Path, ipfsPathPrefix))
+	case strings.HasPrefix(req.URL.Path, piecePathPrefix):
+		gw.servePiece(res, req, strings.TrimPrefix(req.URL.Path, piecePathPrefix))
+	default:
+		httpError(res, http.StatusText(http.StatusNotFound), http.StatusNotFound)
+	}
+}
+
+func (gw *HttpRetrievalGateway) serveDag(res http.ResponseWriter, req *http.Request, path string) {
+	ctx := req.Context()
+
+	segments := strings.SplitN(strings.Trim(path, "/"), "/", 2)
+	root, err := cid.Decode(segments[0])
+	if err != nil {
+		httpError(res, fmt.Sprintf("invalid cid %s: %s", segments[0], err), http.StatusBadRequest)
+		return
+	}
+	var dagPath string
+	if len(segments) > 1 {
+		dagPath = segments[1]
+	}
+	sel, err := dagSelector(dagPath, req.URL.Query().Get("selector"))
+	if err != nil {
+		httpError(res, err.Error(), http.StatusBadRequest)
+		return
+	}
+
+	deals, err := gw.pieceInfo.GetPieceInfoFromCid(ctx, root, nil)
+	if err != nil {
+		if errors.Is(err, repo.ErrNotFound) {
+			httpError(res, fmt.Sprintf("no piece ready for retrieval contains %s", root), http.StatusNotFound)
+			return
+		}
+		httpError(res, fmt.Sprintf("find pieces of %s: %s", root, err), http.StatusInternalServerError)
+		return
+	}
+
+	// try pieces one by one, the same piece may be stored by deals of different miners,
+	// only rejection by filter or blocklist is reported as forbidden
+	var lastErr error
+	lastCode := http.StatusInternalServerError
+	tried := make(map[cid.Cid]struct{})
+	for _, deal := range deals {
+		pieceCid := deal.Proposal.PieceCID
+		if _, ok := tried[pieceCid]; ok {
+			continue
+		}
+		tried[pieceCid] = struct{}{}
+
+		accept, reason, err := gw.accept(ctx, root, pieceCid)
+		if err != nil {
+			lastErr, lastCode = fmt.Errorf("check retrieval of piece %s: %w", pieceCid, err), http.StatusInternalServerError
+			continue
+		}
+		if !accept {
+			lastErr, lastCode = fmt.Errorf("retrieval of piece %s rejected: %s", pieceCid, reason), http.StatusForbidden
+			continue
+		}
+
+		bs, err := gw.dagStore.LoadShard(ctx, pieceCid)
+		if err != nil {
+			lastErr, lastCode = fmt.Errorf("load shard of piece %s: %w", pieceCid, err), http.StatusInternalServerError
+			continue
+		}
+		has, err := bs.Has(ctx, root)
+		if err != nil || !has {
+			_ = bs.Close()
+			if err != nil {
+				lastErr, lastCode = fmt.Errorf("read piece %s: %w", pieceCid, err), http.StatusInternalServerError
+			} else {
+				lastErr, lastCode = fmt.Errorf("piece %s doesn't contain %s", pieceCid, root), http.StatusNotFound
+			}
+			continue
+		}
+
+		res.Header().Set("Content-Type", "application/vnd.ipld.car; version=1")
+		res.Header().Set("X-Content-Type-Options", "nosniff")
+		res.Header().Set("Cache-Control", "public, max-age=29030400, immutable")
+		res.Header().Set("Etag", fmt.Sprintf("\"%s.car\"", root))
+		if req.Method == http.MethodHead {
+			_ = bs.Close()
+			res.WriteHeader(http.StatusOK)
+			return
+		}
+
+		// headers can't be changed after the body has began, so errors during traversal can only be logged
+		sc := car.NewSelectiveCar(ctx, bs, []car.Dag{{Root: root, Selector: sel}}, car.TraverseLinksOnlyOnce())
+		if err = sc.Write(res); err != nil {
+			log.Errorf("write CAR of %s from piece %s: %v", root, pieceCid, err)
+		}
+		_ = bs.Close()
+		return
+	}
+
+	httpError(res, fmt.Sprintf("unable to retrieve %s: %v", root, lastErr), lastCode)
+}
+
+func (gw *HttpRetrievalGateway) servePiece(res http.ResponseWriter, req *http.Request, path string) {
+	ctx := req.Context()
+
+	pieceCid, err := cid.Decode(strings.Trim(path, "/"))
+	if err != nil {
+		httpError(res, fmt.Sprintf("invalid piece cid %s: %s", path, err), http.StatusBadRequest)
+		return
+	}
+
+	if _, err = gw.pieceInfo.GetPieceInfoFromCid(ctx, cid.Undef, &pieceCid); err != nil {
+		if errors.Is(err, repo.ErrNotFound) {
+			httpError(res, fmt.Sprintf("piece %s is not ready for retrieval", pieceCid), http.StatusNotFound)
+			return
+		}
+		httpError(res, fmt.Sprintf("find deals of piece %s: %s", pieceCid, err), http.StatusInternalServerError)
+		return
+	}
+
+	accept, reason, err := gw.accept(ctx, cid.Undef, pieceCid)
+	if err != nil {
+		httpError(res, fmt.Sprintf("check retrieval of piece %s: %s", pieceCid, err), http.StatusInternalServerError)
+		return
+	}
+	if !accept {
+		httpError(res, fmt.Sprintf("retrieval of piece %s rejected: %s", pieceCid, reason), http.StatusForbidden)
+		return
+	}
+
+	pieceStorage, err := gw.pieceStorageMgr.FindStorageForRead(ctx, pieceCid.String())
+	if err != nil {
+		httpError(res, fmt.Sprintf("piece %s not found in piece storage", pieceCid), http.StatusNotFound)
+		return
+	}
+	flen, err := pieceStorage.Len(ctx, pieceCid.String())
+	if err != nil {
+		httpError(res, fmt.Sprintf("get length of piece %s: %s", pieceCid, err), http.StatusInternalServerError)
+		return
+	}
+	r, err := pieceStorage.GetMountReader(ctx, pieceCid.String())
+	if err != nil {
+		httpError(res, fmt.Sprintf("open piece %s: %s", pieceCid, err), http.StatusInternalServerError)
+		return
+	}
+	defer r.Close() //nolint:errcheck
+
+	res.Header().Set("Content-Type", "application/piece")
+	res.Header().Set("Cache-Control", "public, max-age=29030400, immutable")
+	res.Header().Set("Etag", fmt.Sprintf("\"%s\"", pieceCid))
+	http.ServeContent(res, req, "", time.Time{}, io.NewSectionReader(r, 0, flen))
+}
+
+// accept apply the piece blocklist and retrieval deal filter which also used by paid retrieval,
+// free retrieval is presented to the filter as a deal with zero price
+func (gw *HttpRetrievalGateway) accept(ctx context.Context, payloadCid cid.Cid, pieceCid cid.Cid) (bool, string, error) {
+	blocklist, err := gw.blocklist()
+	if err != nil {
+		return false, "", err
+	}
+	for _, blocked := range blocklist {
+		if blocked.Equals(pieceCid) {
+			return false, fmt.Sprintf("piece %s is blocklisted", pieceCid), nil
+		}
+	}
+
+	state := types.ProviderDealState{
+		DealProposal: retrievalmarket.DealProposal{
+			PayloadCID: payloadCid,
+			Params: retrievalmarket.Params{
+				PieceCID:     &pieceCid,
+				PricePerByte: big.Zero(),
+				UnsealPrice:  big.Zero(),
+			},
+		},
+		Status: retrievalmarket.DealStatusNew,
+	}
+	return gw.dealFilter(ctx, state)
+}
+
+// dagSelector build selector from json selector or ipld path, blocks along the path are included to make the CAR verifiable
+func dagSelector(path string, jsonSelector string) (datamodel.Node, error) {
+	if len(jsonSelector) > 0 {
+		if len(path) > 0 {
+			return nil, fmt.Errorf("path and selector can't be used together")
+		}
+		sel, err := selectorparse.ParseJSONSelector(jsonSelector)
+		if err != nil {
+			return nil, fmt.Errorf("failed to parse json-selector '%s': %w", jsonSelector, err)
+		}
+		return sel, nil
+	}
+
+	if len(path) == 0 {
+		return selectorparse.CommonSelector_ExploreAllRecursively, nil
+	}
+
+	ssb := builder.NewSelectorSpecBuilder(basicnode.Prototype.Any)
+	selspec, err := textselector.SelectorSpecFromPath(
+		textselector.Expression(path), true,
+		ssb.ExploreRecursive(
+			selector.RecursionLimitNone(),
+			ssb.ExploreUnion(ssb.Matcher(), ssb.ExploreAll(ssb.ExploreRecursiveEdge())),
+		),
+	)
+	if err != nil {
+		return nil, fmt.Errorf("failed to parse text-selector '%s': %w", path, err)
+	}
+	return selspec.Node(), nil
+}
+
+func httpError(res http.ResponseWriter, err string, code int) {
+	log.Warnf("http retrieval fail Code: %d, Message: %s", code, err)
+	http.Error(res, err, code)
+}
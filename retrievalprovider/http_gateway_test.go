@@ -0,0 +1,143 @@
+package retrievalprovider
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"io/ioutil"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	dagstore2 "github.com/filecoin-project/dagstore"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-cid"
+	"github.com/ipld/go-car"
+	carv2 "github.com/ipld/go-car/v2"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/dagstore"
+	"github.com/filecoin-project/venus-market/v2/models"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
+)
+
+func TestHttpRetrievalGateway(t *testing.T) {
+	ctx := context.Background()
+	carPath := "../dagstore/fixtures/sample-rw-bs-v2.car"
+	carReader, err := carv2.OpenReader(carPath)
+	require.NoError(t, err)
+	roots, err := carReader.Roots()
+	require.NoError(t, err)
+	require.NoError(t, carReader.Close())
+	root := roots[0]
+
+	r := models.NewInMemoryRepo()
+	dagStore := dagstore.NewMockDagStoreWrapper()
+	pieceStorageMgr, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+	require.NoError(t, err)
+	memStore := piecestorage.NewMemPieceStore("memtest", nil)
+	pieceStorageMgr.AddMemPieceStorage(memStore)
+
+	pieceCid := randCid(t)
+	deal := getTestMinerDeal(t, root, pieceCid)
+	deal.PieceStatus = types.Proving
+	require.NoError(t, r.StorageDealRepo().SaveDeal(ctx, deal))
+	require.NoError(t, dagStore.RegisterShard(ctx, pieceCid, carPath, false, make(chan dagstore2.ShardResult, 1)))
+	dagStore.AddBlockToPieceIndex(root, pieceCid)
+	_, err = memStore.SaveTo(ctx, pieceCid.String(), bytes.NewBufferString("mock piece content"))
+	require.NoError(t, err)
+
+	var blocklist []cid.Cid
+	var rejectReason string
+	var filterErr error
+	gw := NewHttpRetrievalGateway(dagStore, r, pieceStorageMgr,
+		func(ctx context.Context, state types.ProviderDealState) (bool, string, error) {
+			return len(rejectReason) == 0, rejectReason, filterErr
+		},
+		func() ([]cid.Cid, error) {
+			return blocklist, nil
+		},
+	)
+	get := func(path string, header http.Header) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodGet, "http://127.0.0.1:3030"+path, nil)
+		for k, v := range header {
+			req.Header[k] = v
+		}
+		w := httptest.NewRecorder()
+		gw.ServeHTTP(w, req)
+		return w
+	}
+
+	t.Run("dag as car", func(t *testing.T) {
+		w := get(fmt.Sprintf("/ipfs/%s", root), nil)
+		require.Equal(t, http.StatusOK, w.Code)
+
+		cr, err := car.NewCarReader(w.Body)
+		require.NoError(t, err)
+		assert.Equal(t, []cid.Cid{root}, cr.Header.Roots)
+		blk, err := cr.Next()
+		require.NoError(t, err)
+		assert.Equal(t, root, blk.Cid())
+	})
+
+	t.Run("unknown dag", func(t *testing.T) {
+		w := get(fmt.Sprintf("/ipfs/%s", randCid(t)), nil)
+		assert.NotEqual(t, http.StatusOK, w.Code)
+	})
+
+	t.Run("piece", func(t *testing.T) {
+		w := get(fmt.Sprintf("/piece/%s", pieceCid), nil)
+		require.Equal(t, http.StatusOK, w.Code)
+		data, err := ioutil.ReadAll(w.Body)
+		require.NoError(t, err)
+		assert.Equal(t, "mock piece content", string(data))
+
+		w = get(fmt.Sprintf("/piece/%s", pieceCid), http.Header{"Range": []string{"bytes=5-9"}})
+		require.Equal(t, http.StatusPartialContent, w.Code)
+		assert.Equal(t, "piece", w.Body.String())
+	})
+
+	t.Run("piece without deal", func(t *testing.T) {
+		w := get(fmt.Sprintf("/piece/%s", randCid(t)), nil)
+		assert.Equal(t, http.StatusNotFound, w.Code)
+	})
+
+	t.Run("rejected by filter", func(t *testing.T) {
+		rejectReason = "not allowed"
+		defer func() { rejectReason = "" }()
+
+		w := get(fmt.Sprintf("/ipfs/%s", root), nil)
+		assert.Equal(t, http.StatusForbidden, w.Code)
+		w = get(fmt.Sprintf("/piece/%s", pieceCid), nil)
+		assert.Equal(t, http.StatusForbidden, w.Code)
+	})
+
+	t.Run("blocklisted", func(t *testing.T) {
+		blocklist = []cid.Cid{pieceCid}
+		defer func() { blocklist = nil }()
+
+		w := get(fmt.Sprintf("/ipfs/%s", root), nil)
+		assert.Equal(t, http.StatusForbidden, w.Code)
+		w = get(fmt.Sprintf("/piece/%s", pieceCid), nil)
+		assert.Equal(t, http.StatusForbidden, w.Code)
+	})
+
+	t.Run("filter failed", func(t *testing.T) {
+		filterErr = fmt.Errorf("filter unavailable")
+		defer func() { filterErr = nil }()
+
+		w := get(fmt.Sprintf("/ipfs/%s", root), nil)
+		assert.Equal(t, http.StatusInternalServerError, w.Code)
+		w = get(fmt.Sprintf("/piece/%s", pieceCid), nil)
+		assert.Equal(t, http.StatusInternalServerError, w.Code)
+	})
+
+	t.Run("method not allowed", func(t *testing.T) {
+		req := httptest.NewRequest(http.MethodPost, fmt.Sprintf("http://127.0.0.1:3030/piece/%s", pieceCid), nil)
+		w := httptest.NewRecorder()
+		gw.ServeHTTP(w, req)
+		assert.Equal(t, http.StatusMethodNotAllowed, w.Code)
+	})
+}
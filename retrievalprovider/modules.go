@@ -78,7 +78,9 @@ var RetrievalProviderOpts = func(cfg *config.MarketConfig) builder.Option {
 		builder.Override(new(rmnet.RetrievalMarketNetwork), RetrievalNetwork),
 		builder.Override(new(IRetrievalProvider), NewProvider), // save to metadata /retrievals/provider
 		builder.Override(new(config.RetrievalDealFilter), RetrievalDealFilter(nil)),
+		builder.Override(new(config.RetrievalPricingFunc), NewRetrievalPricingFunc),
 		builder.Override(HandleRetrievalKey, HandleRetrieval),
+		builder.Override(new(*HttpRetrievalGateway), NewHttpRetrievalGateway),
 		builder.If(cfg.RetrievalFilter != "",
 			builder.Override(new(config.RetrievalDealFilter), RetrievalDealFilter(dealfilter.CliRetrievalDealFilter(cfg.RetrievalFilter))),
 		),
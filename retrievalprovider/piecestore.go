@@ -4,19 +4,40 @@ import (
 	"context"
 	"errors"
 	"fmt"
+	"io/ioutil"
 
 	"github.com/filecoin-project/go-address"
 
 	"github.com/filecoin-project/go-fil-markets/stores"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	"github.com/filecoin-project/venus-market/v2/storageprovider"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	lru "github.com/hashicorp/golang-lru"
 	"github.com/ipfs/go-cid"
+	"github.com/ipld/go-car"
+	selectorparse "github.com/ipld/go-ipld-prime/traversal/selector/parse"
 )
 
+// payloadSizeCacheSize is the number of payload sizes traversed from shards to keep
+const payloadSizeCacheSize = 1024
+
 type PieceInfo struct {
-	dagstore stores.DAGStoreWrapper
-	dealRepo repo.StorageDealRepo
+	dagstore        stores.DAGStoreWrapper
+	dealRepo        repo.StorageDealRepo
+	pieceStorageMgr *piecestorage.PieceStorageManager
+	// sizeCache maps piece cid and payload cid to the size traversed from shard
+	sizeCache *lru.Cache
+}
+
+func NewPieceInfo(dagStore stores.DAGStoreWrapper, dealRepo repo.StorageDealRepo, pieceStorageMgr *piecestorage.PieceStorageManager) *PieceInfo {
+	sizeCache, _ := lru.New(payloadSizeCacheSize)
+	return &PieceInfo{
+		dagstore:        dagStore,
+		dealRepo:        dealRepo,
+		pieceStorageMgr: pieceStorageMgr,
+		sizeCache:       sizeCache,
+	}
 }
 
 // GetPieceInfoFromCid take `pieceCid` priority, then `payloadCid`
@@ -70,3 +91,47 @@ func (pinfo *PieceInfo) GetPieceInfoFromCid(ctx context.Context, payloadCID cid.
 	}
 	return nil, fmt.Errorf("unable to find ready data for payload (%s), %w", payloadCID, repo.ErrNotFound)
 }
+
+// GetPayloadSize return the size of data retrieved from the piece of deals with payloadCID as root.
+// payload size recorded in deal is used when payloadCID is the root of deal, otherwise the size
+// is summed up from blocks traversed in the shard and cached. the shard is traversed only when
+// the piece is in piece storage, so that a query never unseals a sector.
+// the size is of the whole dag under payloadCID regardless of the selector of retrieval, so it's an
+// upper bound of the data transferred when a selector picks only part of the dag
+func (pinfo *PieceInfo) GetPayloadSize(ctx context.Context, payloadCID cid.Cid, deals []*types.MinerDeal) (uint64, error) {
+	if len(deals) == 0 {
+		return 0, fmt.Errorf("no deal to get size of payload %s", payloadCID)
+	}
+	for _, deal := range deals {
+		if deal.Ref != nil && deal.Ref.Root.Equals(payloadCID) && deal.PayloadSize > 0 {
+			return deal.PayloadSize, nil
+		}
+	}
+
+	pieceCid := deals[0].Proposal.PieceCID
+	cacheKey := pieceCid.String() + "/" + payloadCID.String()
+	if size, ok := pinfo.sizeCache.Get(cacheKey); ok {
+		return size.(uint64), nil
+	}
+	if len(pinfo.pieceStorageMgr.FindStoragesForRead(ctx, pieceCid.String())) == 0 {
+		return 0, fmt.Errorf("piece %s is not in piece storage, size of %s is unknown without unsealing", pieceCid, payloadCID)
+	}
+
+	bs, err := pinfo.dagstore.LoadShard(ctx, pieceCid)
+	if err != nil {
+		return 0, fmt.Errorf("load shard of piece %s: %w", pieceCid, err)
+	}
+	defer bs.Close() //nolint:errcheck
+
+	var size uint64
+	sc := car.NewSelectiveCar(ctx, bs, []car.Dag{{Root: payloadCID, Selector: selectorparse.CommonSelector_ExploreAllRecursively}}, car.TraverseLinksOnlyOnce())
+	err = sc.Write(ioutil.Discard, func(block car.Block) error {
+		size += uint64(len(block.Data))
+		return nil
+	})
+	if err != nil {
+		return 0, fmt.Errorf("traverse %s in piece %s: %w", payloadCID, pieceCid, err)
+	}
+	pinfo.sizeCache.Add(cacheKey, size)
+	return size, nil
+}
@@ -4,9 +4,12 @@ import (
 	"bytes"
 	"context"
 	"math/rand"
+	"os"
+	"path/filepath"
 	"testing"
 	"time"
 
+	dagstore2 "github.com/filecoin-project/dagstore"
 	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	"github.com/filecoin-project/go-state-types/abi"
@@ -21,8 +24,14 @@ import (
 	"github.com/filecoin-project/venus/venus-shared/types/market"
 
 	"github.com/ipfs/go-cid"
+	format "github.com/ipfs/go-ipld-format"
+	"github.com/ipfs/go-merkledag"
+	"github.com/ipld/go-car"
+	carutil "github.com/ipld/go-car/util"
 
+	"github.com/filecoin-project/venus-market/v2/config"
 	"github.com/filecoin-project/venus-market/v2/dagstore"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
 
 	market2 "github.com/filecoin-project/go-state-types/builtin/v8/market"
 	"github.com/filecoin-project/venus-market/v2/models"
@@ -102,6 +111,69 @@ func TestPieceInfo_DistinctDeals(t *testing.T) {
 	assert.Len(t, deals, 1)
 }
 
+func TestPieceInfo_GetPayloadSize(t *testing.T) {
+	ctx := context.Background()
+
+	// root -> (leafA, leafB)
+	leafA := merkledag.NewRawNode([]byte("leaf a"))
+	leafB := merkledag.NewRawNode([]byte("leaf bb"))
+	root := merkledag.NodeWithData([]byte("root"))
+	assert.Nil(t, root.AddNodeLink("a", leafA))
+	assert.Nil(t, root.AddNodeLink("b", leafB))
+
+	carPath := filepath.Join(t.TempDir(), "payload.car")
+	carFile, err := os.Create(carPath)
+	assert.Nil(t, err)
+	assert.Nil(t, car.WriteHeader(&car.CarHeader{Roots: []cid.Cid{root.Cid()}, Version: 1}, carFile))
+	for _, nd := range []format.Node{root, leafA, leafB} {
+		assert.Nil(t, carutil.LdWrite(carFile, nd.Cid().Bytes(), nd.RawData()))
+	}
+	assert.Nil(t, carFile.Close())
+
+	dagStore := dagstore.NewMockDagStoreWrapper()
+	pieceStorageMgr, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+	assert.Nil(t, err)
+	memStore := piecestorage.NewMemPieceStore("memtest", nil)
+	pieceStorageMgr.AddMemPieceStorage(memStore)
+	pieceInfo := NewPieceInfo(dagStore, models.NewInMemoryRepo().StorageDealRepo(), pieceStorageMgr)
+	pieceCid := randCid(t)
+	assert.Nil(t, dagStore.RegisterShard(ctx, pieceCid, carPath, false, make(chan dagstore2.ShardResult, 1)))
+	_, err = memStore.SaveTo(ctx, pieceCid.String(), bytes.NewBufferString("mock piece content"))
+	assert.Nil(t, err)
+
+	deal := getTestMinerDeal(t, root.Cid(), pieceCid)
+
+	// recorded payload size of deal
+	deal.PayloadSize = 12345
+	size, err := pieceInfo.GetPayloadSize(ctx, root.Cid(), []*market.MinerDeal{deal})
+	assert.Nil(t, err)
+	assert.Equal(t, uint64(12345), size)
+
+	// traverse shard without recorded size
+	deal.PayloadSize = 0
+	size, err = pieceInfo.GetPayloadSize(ctx, root.Cid(), []*market.MinerDeal{deal})
+	assert.Nil(t, err)
+	assert.Equal(t, uint64(len(root.RawData())+len(leafA.RawData())+len(leafB.RawData())), size)
+
+	// sub dag
+	deal.PayloadSize = 12345
+	size, err = pieceInfo.GetPayloadSize(ctx, leafB.Cid(), []*market.MinerDeal{deal})
+	assert.Nil(t, err)
+	assert.Equal(t, uint64(len(leafB.RawData())), size)
+
+	// payload not in the piece
+	_, err = pieceInfo.GetPayloadSize(ctx, randCid(t), []*market.MinerDeal{deal})
+	assert.NotNil(t, err)
+
+	// the shard is not traversed once the piece is out of piece storage, traversed sizes are cached
+	assert.Nil(t, memStore.Delete(ctx, pieceCid.String()))
+	size, err = pieceInfo.GetPayloadSize(ctx, leafB.Cid(), []*market.MinerDeal{deal})
+	assert.Nil(t, err)
+	assert.Equal(t, uint64(len(leafB.RawData())), size)
+	_, err = pieceInfo.GetPayloadSize(ctx, leafA.Cid(), []*market.MinerDeal{deal})
+	assert.NotNil(t, err)
+}
+
 func getTestMinerDeal(t *testing.T, datacid, pieceCid cid.Cid) *market.MinerDeal {
 	c := randCid(t)
 	pid, err := peer.Decode("12D3KooWG8tR9PHjjXcMknbNPVWT75BuXXA2RaYx3fMwwg2oPZXd")
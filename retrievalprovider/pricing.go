@@ -0,0 +1,188 @@
+package retrievalprovider
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os/exec"
+	"sort"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/retrievalmarket"
+	"github.com/filecoin-project/go-state-types/big"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/dagstore"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
+)
+
+// NewRetrievalPricingFunc select the pricing function by the strategy in config
+func NewRetrievalPricingFunc(cfg *config.MarketConfig) config.RetrievalPricingFunc {
+	pricing := cfg.RetrievalPricing
+	if pricing == nil {
+		return DefaultPricingFunc(true)
+	}
+
+	if pricing.Strategy == config.RetrievalPricingExternalMode && pricing.External != nil {
+		return ExternalRetrievalPricingFunc(pricing.External.Path)
+	}
+	return DefaultPricingFunc(pricing.Default == nil || pricing.Default.VerifiedDealsFreeTransfer)
+}
+
+// DefaultPricingFunc is the default pricing policy that will be used to price retrieval deals.
+// It quotes the current ask, unseal price is zero if the piece has been unsealed, and transfer is free for
+// data of verified deals if `VerifiedDealsFreeTransfer` is on.
+func DefaultPricingFunc(verifiedDealsFreeTransfer bool) config.RetrievalPricingFunc {
+	return func(ctx context.Context, pricingInput retrievalmarket.PricingInput) (retrievalmarket.Ask, error) {
+		ask := pricingInput.CurrentAsk
+
+		if pricingInput.Unsealed {
+			ask.UnsealPrice = big.Zero()
+		}
+
+		if pricingInput.VerifiedDeal && verifiedDealsFreeTransfer {
+			ask.PricePerByte = big.Zero()
+		}
+
+		return ask, nil
+	}
+}
+
+// ExternalRetrievalPricingFunc run the script with pricing input as json in stdin, and take the ask printed
+// to stdout as json
+func ExternalRetrievalPricingFunc(cmd string) config.RetrievalPricingFunc {
+	return func(ctx context.Context, pricingInput retrievalmarket.PricingInput) (retrievalmarket.Ask, error) {
+		j, err := json.Marshal(pricingInput)
+		if err != nil {
+			return retrievalmarket.Ask{}, err
+		}
+
+		var out, errOut bytes.Buffer
+		c := exec.CommandContext(ctx, "sh", "-c", cmd)
+		c.Stdin = bytes.NewReader(j)
+		c.Stdout = &out
+		c.Stderr = &errOut
+
+		switch err := c.Run().(type) {
+		case nil:
+			var ask retrievalmarket.Ask
+			if err := json.Unmarshal(out.Bytes(), &ask); err != nil {
+				return retrievalmarket.Ask{}, fmt.Errorf("failed to parse pricing output %s: %w", out.String(), err)
+			}
+			return ask, nil
+		case *exec.ExitError:
+			return retrievalmarket.Ask{}, fmt.Errorf("pricing script exited with error: %s", errOut.String())
+		default:
+			return retrievalmarket.Ask{}, fmt.Errorf("pricing script run error: %w", err)
+		}
+	}
+}
+
+// RetrievalPricer price retrieval of data in a piece, shared by query handler and deal validator
+type RetrievalPricer struct {
+	askRepo     repo.IRetrievalAskRepo
+	marketAPI   dagstore.MarketAPI
+	pricingFunc config.RetrievalPricingFunc
+	cfg         *config.MarketConfig
+}
+
+func NewRetrievalPricer(askRepo repo.IRetrievalAskRepo, marketAPI dagstore.MarketAPI, pricingFunc config.RetrievalPricingFunc, cfg *config.MarketConfig) *RetrievalPricer {
+	return &RetrievalPricer{askRepo: askRepo, marketAPI: marketAPI, pricingFunc: pricingFunc, cfg: cfg}
+}
+
+// PaymentAddress return the address to receive payment of retrievals for data stored by the miner
+func (rp *RetrievalPricer) PaymentAddress(miner address.Address) address.Address {
+	return rp.cfg.RetrievalPaymentAddressOf(miner)
+}
+
+// DefaultPaymentAddress return the payment address of the market
+func (rp *RetrievalPricer) DefaultPaymentAddress() address.Address {
+	return address.Address(rp.cfg.RetrievalPaymentAddress.Addr)
+}
+
+// getAsk return the retrieval ask of miner, fallback to the ask of default payment address if miner has no ask
+func (rp *RetrievalPricer) getAsk(ctx context.Context, miner address.Address) (*types.RetrievalAsk, error) {
+	ask, err := rp.askRepo.GetAsk(ctx, miner)
+	if err == nil {
+		return ask, nil
+	}
+	if !errors.Is(err, repo.ErrNotFound) {
+		return nil, fmt.Errorf("get ask of miner %s: %w", miner, err)
+	}
+
+	defaultAddr := rp.DefaultPaymentAddress()
+	ask, err = rp.askRepo.GetAsk(ctx, defaultAddr)
+	if err != nil {
+		return nil, fmt.Errorf("miner %s has no ask, get default ask of %s: %w", miner, defaultAddr, err)
+	}
+	return ask, nil
+}
+
+// GetDynamicAsk compute ask of the retrieval from the ask of the miner storing the data, the deals and availability of the piece.
+// deals must be storage deals of the same piece, the first deal decide the miner
+func (rp *RetrievalPricer) GetDynamicAsk(ctx context.Context, input retrievalmarket.PricingInput, deals []*types.MinerDeal) (*types.RetrievalAsk, error) {
+	if len(deals) == 0 {
+		return nil, fmt.Errorf("no deal of piece %s", input.PieceCID)
+	}
+	miner := deals[0].Proposal.Provider
+	ask, err := rp.getAsk(ctx, miner)
+	if err != nil {
+		return nil, err
+	}
+
+	input.CurrentAsk = retrievalmarket.Ask{
+		PricePerByte:            ask.PricePerByte,
+		UnsealPrice:             ask.UnsealPrice,
+		PaymentInterval:         ask.PaymentInterval,
+		PaymentIntervalIncrease: ask.PaymentIntervalIncrease,
+	}
+	for _, deal := range deals {
+		if deal.Proposal.VerifiedDeal {
+			input.VerifiedDeal = true
+		}
+	}
+	input.PieceSize = deals[0].Proposal.PieceSize.Unpadded()
+
+	input.Unsealed, err = rp.marketAPI.IsUnsealed(ctx, input.PieceCID)
+	if err != nil {
+		// treat as sealed, the client would pay for unseal at most
+		log.Warnf("check unseal state of piece %s: %v", input.PieceCID, err)
+	}
+
+	price, err := rp.pricingFunc(ctx, input)
+	if err != nil {
+		return nil, fmt.Errorf("price retrieval of piece %s: %w", input.PieceCID, err)
+	}
+
+	return &types.RetrievalAsk{
+		Miner:                   miner,
+		PricePerByte:            price.PricePerByte,
+		UnsealPrice:             price.UnsealPrice,
+		PaymentInterval:         price.PaymentInterval,
+		PaymentIntervalIncrease: price.PaymentIntervalIncrease,
+	}, nil
+}
+
+// dealsOfPiece select a piece from deals and return the deals of it, deals are sorted by proposal cid,
+// so query and deal validation always resolve the same piece and miner for a payload
+func dealsOfPiece(deals []*types.MinerDeal) []*types.MinerDeal {
+	if len(deals) == 0 {
+		return nil
+	}
+
+	deals = append([]*types.MinerDeal(nil), deals...)
+	sort.Slice(deals, func(i, j int) bool {
+		return deals[i].ProposalCid.String() < deals[j].ProposalCid.String()
+	})
+	pieceCid := deals[0].Proposal.PieceCID
+	var filtered []*types.MinerDeal
+	for _, deal := range deals {
+		if deal.Proposal.PieceCID.Equals(pieceCid) {
+			filtered = append(filtered, deal)
+		}
+	}
+	return filtered
+}
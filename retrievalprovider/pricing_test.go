@@ -0,0 +1,212 @@
+package retrievalprovider
+
+import (
+	"context"
+	"io/ioutil"
+	"path/filepath"
+	"testing"
+
+	"github.com/filecoin-project/dagstore/mount"
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/retrievalmarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-cid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models"
+)
+
+type mockUnsealMarketAPI struct {
+	unsealed bool
+}
+
+func (m *mockUnsealMarketAPI) FetchUnsealedPiece(ctx context.Context, pieceCid cid.Cid) (mount.Reader, error) {
+	panic("implement me")
+}
+
+func (m *mockUnsealMarketAPI) GetUnpaddedCARSize(ctx context.Context, pieceCid cid.Cid) (uint64, error) {
+	panic("implement me")
+}
+
+func (m *mockUnsealMarketAPI) IsUnsealed(ctx context.Context, pieceCid cid.Cid) (bool, error) {
+	return m.unsealed, nil
+}
+
+func (m *mockUnsealMarketAPI) Start(ctx context.Context) error {
+	return nil
+}
+
+func TestDefaultPricingFunc(t *testing.T) {
+	ctx := context.Background()
+	currentAsk := retrievalmarket.Ask{
+		PricePerByte:            abi.NewTokenAmount(10),
+		UnsealPrice:             abi.NewTokenAmount(100),
+		PaymentInterval:         1 << 20,
+		PaymentIntervalIncrease: 1 << 20,
+	}
+
+	testCases := []struct {
+		name              string
+		freeTransfer      bool
+		verified          bool
+		unsealed          bool
+		expectPricePerBye abi.TokenAmount
+		expectUnsealPrice abi.TokenAmount
+	}{
+		{"sealed unverified", true, false, false, currentAsk.PricePerByte, currentAsk.UnsealPrice},
+		{"unsealed", true, false, true, currentAsk.PricePerByte, big.Zero()},
+		{"verified free transfer", true, true, false, big.Zero(), currentAsk.UnsealPrice},
+		{"verified without free transfer", false, true, true, currentAsk.PricePerByte, big.Zero()},
+	}
+	for _, tc := range testCases {
+		t.Run(tc.name, func(t *testing.T) {
+			ask, err := DefaultPricingFunc(tc.freeTransfer)(ctx, retrievalmarket.PricingInput{
+				VerifiedDeal: tc.verified,
+				Unsealed:     tc.unsealed,
+				CurrentAsk:   currentAsk,
+			})
+			require.NoError(t, err)
+			assert.Equal(t, tc.expectPricePerBye, ask.PricePerByte)
+			assert.Equal(t, tc.expectUnsealPrice, ask.UnsealPrice)
+			assert.Equal(t, currentAsk.PaymentInterval, ask.PaymentInterval)
+		})
+	}
+}
+
+func TestExternalRetrievalPricingFunc(t *testing.T) {
+	ctx := context.Background()
+	dir := t.TempDir()
+
+	t.Run("price by script", func(t *testing.T) {
+		// free for verified deal, otherwise take the current ask
+		script := filepath.Join(dir, "pricing.sh")
+		require.NoError(t, ioutil.WriteFile(script, []byte(`#!/bin/sh
+input=$(cat)
+if echo "$input" | grep -q '"VerifiedDeal":true'; then
+  echo '{"PricePerByte":"0","UnsealPrice":"0","PaymentInterval":1,"PaymentIntervalIncrease":2}'
+else
+  echo '{"PricePerByte":"10","UnsealPrice":"100","PaymentInterval":1,"PaymentIntervalIncrease":2}'
+fi
+`), 0755))
+
+		pricing := ExternalRetrievalPricingFunc(script)
+		ask, err := pricing(ctx, retrievalmarket.PricingInput{VerifiedDeal: true})
+		require.NoError(t, err)
+		assert.Equal(t, big.Zero(), ask.PricePerByte)
+		assert.Equal(t, big.Zero(), ask.UnsealPrice)
+		assert.Equal(t, uint64(1), ask.PaymentInterval)
+		assert.Equal(t, uint64(2), ask.PaymentIntervalIncrease)
+
+		ask, err = pricing(ctx, retrievalmarket.PricingInput{})
+		require.NoError(t, err)
+		assert.Equal(t, abi.NewTokenAmount(10), ask.PricePerByte)
+		assert.Equal(t, abi.NewTokenAmount(100), ask.UnsealPrice)
+	})
+
+	t.Run("script fail", func(t *testing.T) {
+		_, err := ExternalRetrievalPricingFunc("echo 'no price' >&2; exit 1")(ctx, retrievalmarket.PricingInput{})
+		require.Error(t, err)
+		assert.Contains(t, err.Error(), "no price")
+	})
+
+	t.Run("invalid output", func(t *testing.T) {
+		_, err := ExternalRetrievalPricingFunc("echo 'not json'")(ctx, retrievalmarket.PricingInput{})
+		assert.Error(t, err)
+	})
+}
+
+func TestRetrievalPricer(t *testing.T) {
+	ctx := context.Background()
+	defaultPaymentAddr, err := address.NewIDAddress(1000)
+	require.NoError(t, err)
+	minerPaymentAddr, err := address.NewIDAddress(1001)
+	require.NoError(t, err)
+
+	pieceCid := randCid(t)
+	deal := getTestMinerDeal(t, randCid(t), pieceCid)
+	verifiedDeal := getTestMinerDeal(t, randCid(t), pieceCid)
+	verifiedDeal.Proposal.VerifiedDeal = true
+	otherDeal := getTestMinerDeal(t, randCid(t), randCid(t))
+
+	cfg := *config.DefaultMarketConfig
+	cfg.RetrievalPaymentAddress = config.User{Addr: config.Address(defaultPaymentAddr)}
+	cfg.MinerRetrievalPaymentAddresses = []config.MinerPaymentAddress{
+		{Miner: config.Address(deal.Proposal.Provider), Addr: config.Address(minerPaymentAddr)},
+	}
+
+	askRepo := models.NewInMemoryRepo().RetrievalAskRepo()
+	marketAPI := &mockUnsealMarketAPI{}
+	pricer := NewRetrievalPricer(askRepo, marketAPI, NewRetrievalPricingFunc(&cfg), &cfg)
+
+	t.Run("payment address", func(t *testing.T) {
+		assert.Equal(t, minerPaymentAddr, pricer.PaymentAddress(deal.Proposal.Provider))
+		assert.Equal(t, defaultPaymentAddr, pricer.PaymentAddress(otherDeal.Proposal.Provider))
+	})
+
+	t.Run("no ask", func(t *testing.T) {
+		_, err := pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{PieceCID: pieceCid}, []*market.MinerDeal{deal})
+		assert.Error(t, err)
+	})
+
+	require.NoError(t, askRepo.SetAsk(ctx, &market.RetrievalAsk{
+		Miner:                   defaultPaymentAddr,
+		PricePerByte:            abi.NewTokenAmount(1),
+		UnsealPrice:             abi.NewTokenAmount(2),
+		PaymentInterval:         1 << 20,
+		PaymentIntervalIncrease: 1 << 20,
+	}))
+	require.NoError(t, askRepo.SetAsk(ctx, &market.RetrievalAsk{
+		Miner:                   deal.Proposal.Provider,
+		PricePerByte:            abi.NewTokenAmount(10),
+		UnsealPrice:             abi.NewTokenAmount(100),
+		PaymentInterval:         1 << 20,
+		PaymentIntervalIncrease: 1 << 20,
+	}))
+
+	t.Run("miner ask", func(t *testing.T) {
+		ask, err := pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{PieceCID: pieceCid}, []*market.MinerDeal{deal})
+		require.NoError(t, err)
+		assert.Equal(t, deal.Proposal.Provider, ask.Miner)
+		assert.Equal(t, abi.NewTokenAmount(10), ask.PricePerByte)
+		assert.Equal(t, abi.NewTokenAmount(100), ask.UnsealPrice)
+	})
+
+	t.Run("fallback to default ask", func(t *testing.T) {
+		ask, err := pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{PieceCID: otherDeal.Proposal.PieceCID}, []*market.MinerDeal{otherDeal})
+		require.NoError(t, err)
+		assert.Equal(t, otherDeal.Proposal.Provider, ask.Miner)
+		assert.Equal(t, abi.NewTokenAmount(1), ask.PricePerByte)
+		assert.Equal(t, abi.NewTokenAmount(2), ask.UnsealPrice)
+	})
+
+	t.Run("verified and unsealed", func(t *testing.T) {
+		marketAPI.unsealed = true
+		defer func() { marketAPI.unsealed = false }()
+
+		ask, err := pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{PieceCID: pieceCid}, []*market.MinerDeal{deal, verifiedDeal})
+		require.NoError(t, err)
+		assert.Equal(t, big.Zero(), ask.PricePerByte)
+		assert.Equal(t, big.Zero(), ask.UnsealPrice)
+	})
+}
+
+func TestDealsOfPiece(t *testing.T) {
+	pieceCid := randCid(t)
+	deals := []*market.MinerDeal{
+		getTestMinerDeal(t, randCid(t), randCid(t)),
+		getTestMinerDeal(t, randCid(t), pieceCid),
+		getTestMinerDeal(t, randCid(t), pieceCid),
+	}
+
+	// the selected deals don't depend on order of input
+	selected := dealsOfPiece(deals)
+	reversed := dealsOfPiece([]*market.MinerDeal{deals[2], deals[1], deals[0]})
+	assert.Equal(t, selected, reversed)
+	for _, deal := range selected {
+		assert.Equal(t, selected[0].Proposal.PieceCID, deal.Proposal.PieceCID)
+	}
+}
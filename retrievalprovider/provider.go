@@ -5,9 +5,10 @@ import (
 	"math"
 	"time"
 
-	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/dagstore"
 	"github.com/filecoin-project/venus-market/v2/paychmgr"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 
@@ -18,6 +19,7 @@ import (
 	"github.com/filecoin-project/go-fil-markets/stores"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/network"
+	"github.com/filecoin-project/venus-market/v2/notify"
 	logging "github.com/ipfs/go-log/v2"
 )
 
@@ -57,12 +59,17 @@ func NewProvider(network rmnet.RetrievalMarketNetwork,
 	payAPI *paychmgr.PaychAPI,
 	repo repo.Repo,
 	cfg *config.MarketConfig,
+	marketAPI dagstore.MarketAPI,
+	pieceStorageMgr *piecestorage.PieceStorageManager,
+	pricingFunc config.RetrievalPricingFunc,
+	notifier *notify.WebhookNotifier,
 ) (*RetrievalProvider, error) {
 	storageDealsRepo := repo.StorageDealRepo()
 	retrievalDealRepo := repo.RetrievalDealRepo()
 	retrievalAskRepo := repo.RetrievalAskRepo()
 
-	pieceInfo := &PieceInfo{dagStore, storageDealsRepo}
+	pieceInfo := NewPieceInfo(dagStore, storageDealsRepo, pieceStorageMgr)
+	pricer := NewRetrievalPricer(retrievalAskRepo, marketAPI, pricingFunc, cfg)
 	p := &RetrievalProvider{
 		dataTransfer:           dataTransfer,
 		network:                network,
@@ -70,11 +77,11 @@ func NewProvider(network rmnet.RetrievalMarketNetwork,
 		retrievalDealRepo:      retrievalDealRepo,
 		storageDealRepo:        storageDealsRepo,
 		stores:                 stores.NewReadOnlyBlockstores(),
-		retrievalStreamHandler: NewRetrievalStreamHandler(retrievalAskRepo, retrievalDealRepo, storageDealsRepo, pieceInfo, address.Address(cfg.RetrievalPaymentAddress.Addr)),
+		retrievalStreamHandler: NewRetrievalStreamHandler(pricer, retrievalDealRepo, storageDealsRepo, pieceInfo),
 	}
 
-	retrievalHandler := NewRetrievalDealHandler(&providerDealEnvironment{p}, retrievalDealRepo, storageDealsRepo)
-	p.requestValidator = NewProviderRequestValidator(address.Address(cfg.RetrievalPaymentAddress.Addr), storageDealsRepo, retrievalDealRepo, retrievalAskRepo, pieceInfo)
+	retrievalHandler := NewRetrievalDealHandler(&providerDealEnvironment{p}, retrievalDealRepo, storageDealsRepo, notifier)
+	p.requestValidator = NewProviderRequestValidator(storageDealsRepo, retrievalDealRepo, pricer, pieceInfo)
 	transportConfigurer := dtutils.TransportConfigurer(network.ID(), &providerStoreGetter{retrievalDealRepo, p.stores})
 	p.reValidator = NewProviderRevalidator(fullNode, payAPI, retrievalDealRepo, retrievalHandler)
 
@@ -6,8 +6,8 @@ import (
 	"errors"
 	"time"
 
-	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 
@@ -36,16 +36,15 @@ func init() {
 
 // ProviderRequestValidator validates incoming requests for the Retrieval Provider
 type ProviderRequestValidator struct {
-	paymentAddr   address.Address
 	storageDeals  repo.StorageDealRepo
 	pieceInfo     *PieceInfo
 	retrievalDeal repo.IRetrievalDealRepo
-	retrievalAsk  repo.IRetrievalAskRepo
+	pricer        *RetrievalPricer
 }
 
 // NewProviderRequestValidator returns a new instance of the ProviderRequestValidator
-func NewProviderRequestValidator(paymentAddr address.Address, storageDeals repo.StorageDealRepo, retrievalDeal repo.IRetrievalDealRepo, retrievalAsk repo.IRetrievalAskRepo, pieceInfo *PieceInfo) *ProviderRequestValidator {
-	return &ProviderRequestValidator{paymentAddr: paymentAddr, storageDeals: storageDeals, retrievalDeal: retrievalDeal, retrievalAsk: retrievalAsk, pieceInfo: pieceInfo}
+func NewProviderRequestValidator(storageDeals repo.StorageDealRepo, retrievalDeal repo.IRetrievalDealRepo, pricer *RetrievalPricer, pieceInfo *PieceInfo) *ProviderRequestValidator {
+	return &ProviderRequestValidator{storageDeals: storageDeals, retrievalDeal: retrievalDeal, pricer: pricer, pieceInfo: pieceInfo}
 }
 
 // ValidatePush validates a push request received from the peer that will send data
@@ -154,6 +153,7 @@ func (rv *ProviderRequestValidator) validatePull(ctx context.Context, isRestart
 		response.Message = err.Error()
 		return &response, err
 	}
+	metrics.Record(ctx, nil, metrics.RetrievalDealStarted.M(1))
 
 	// Pause the data transfer while unsealing the data.
 	// The state machine will unpause the transfer when unsealing completes.
@@ -173,8 +173,13 @@ func (rv *ProviderRequestValidator) acceptDeal(ctx context.Context, deal *types.
 	defer cancel()
 
 	//todo how to select deal
+	minerdeals = dealsOfPiece(minerdeals)
 	deal.SelStorageProposalCid = minerdeals[0].ProposalCid
-	ask, err := rv.retrievalAsk.GetAsk(ctx, rv.paymentAddr)
+	ask, err := rv.pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{
+		PayloadCID: deal.PayloadCID,
+		PieceCID:   minerdeals[0].Proposal.PieceCID,
+		Client:     deal.Receiver,
+	}, minerdeals)
 	if err != nil {
 		return retrievalmarket.DealStatusErrored, err
 	}
@@ -8,7 +8,9 @@ import (
 
 	rm "github.com/filecoin-project/go-fil-markets/retrievalmarket"
 	"github.com/filecoin-project/go-statemachine"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/notify"
 )
 
 type IRetrievalHandler interface {
@@ -24,10 +26,11 @@ type RetrievalDealHandler struct {
 	env                ProviderDealEnvironment
 	retrievalDealStore repo.IRetrievalDealRepo
 	storageDealRepo    repo.StorageDealRepo
+	notifier           *notify.WebhookNotifier
 }
 
-func NewRetrievalDealHandler(env ProviderDealEnvironment, retrievalDealStore repo.IRetrievalDealRepo, storageDealRepo repo.StorageDealRepo) IRetrievalHandler {
-	return &RetrievalDealHandler{env: env, retrievalDealStore: retrievalDealStore, storageDealRepo: storageDealRepo}
+func NewRetrievalDealHandler(env ProviderDealEnvironment, retrievalDealStore repo.IRetrievalDealRepo, storageDealRepo repo.StorageDealRepo, notifier *notify.WebhookNotifier) IRetrievalHandler {
+	return &RetrievalDealHandler{env: env, retrievalDealStore: retrievalDealStore, storageDealRepo: storageDealRepo, notifier: notifier}
 }
 
 func (p *RetrievalDealHandler) UnsealData(ctx context.Context, deal *types.ProviderDealState) error {
@@ -88,7 +91,12 @@ func (p *RetrievalDealHandler) CleanupDeal(ctx context.Context, deal *types.Prov
 		return p.Error(ctx, deal, nil)
 	}
 	deal.Status = rm.DealStatusCompleted
-	return p.retrievalDealStore.SaveDeal(ctx, deal)
+	if err := p.retrievalDealStore.SaveDeal(ctx, deal); err != nil {
+		return err
+	}
+	metrics.Record(ctx, nil, metrics.RetrievalDealCompleted.M(1))
+	p.notifier.RetrievalDealCompleted(ctx, deal)
+	return nil
 }
 
 func (p *RetrievalDealHandler) Error(ctx context.Context, deal *types.ProviderDealState, err error) error {
@@ -96,5 +104,6 @@ func (p *RetrievalDealHandler) Error(ctx context.Context, deal *types.ProviderDe
 	if err != nil {
 		deal.Message = err.Error()
 	}
+	metrics.Record(ctx, nil, metrics.RetrievalDealFailed.M(1))
 	return p.retrievalDealStore.SaveDeal(ctx, deal)
 }
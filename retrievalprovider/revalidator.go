@@ -7,6 +7,7 @@ import (
 	datatransfer "github.com/filecoin-project/go-data-transfer"
 	"github.com/filecoin-project/go-state-types/abi"
 	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/paychmgr"
 	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
@@ -186,6 +187,8 @@ func errorDealResponse(dealID retrievalmarket.ProviderDealIdentifier, err error)
 // other errors will terminate the request
 func (pr *ProviderRevalidator) OnPullDataSent(chid datatransfer.ChannelID, additionalBytesSent uint64) (bool, datatransfer.VoucherResult, error) {
 	ctx := context.TODO()
+	metrics.Record(ctx, nil, metrics.RetrievalBytesSent.M(int64(additionalBytesSent)))
+
 	deal, err := pr.deals.GetDealByTransferId(ctx, chid)
 	if err != nil {
 		if err == repo.ErrNotFound {
@@ -19,15 +19,14 @@ type IRetrievalStream interface {
 var _ IRetrievalStream = (*RetrievalStreamHandler)(nil)
 
 type RetrievalStreamHandler struct {
-	askRepo            repo.IRetrievalAskRepo
+	pricer             *RetrievalPricer
 	retrievalDealStore repo.IRetrievalDealRepo
 	storageDealStore   repo.StorageDealRepo
 	pieceInfo          *PieceInfo
-	paymentAddr        address.Address
 }
 
-func NewRetrievalStreamHandler(askRepo repo.IRetrievalAskRepo, retrievalDealStore repo.IRetrievalDealRepo, storageDealStore repo.StorageDealRepo, pieceInfo *PieceInfo, paymentAddr address.Address) *RetrievalStreamHandler {
-	return &RetrievalStreamHandler{askRepo: askRepo, retrievalDealStore: retrievalDealStore, storageDealStore: storageDealStore, pieceInfo: pieceInfo, paymentAddr: paymentAddr}
+func NewRetrievalStreamHandler(pricer *RetrievalPricer, retrievalDealStore repo.IRetrievalDealRepo, storageDealStore repo.StorageDealRepo, pieceInfo *PieceInfo) *RetrievalStreamHandler {
+	return &RetrievalStreamHandler{pricer: pricer, retrievalDealStore: retrievalDealStore, storageDealStore: storageDealStore, pieceInfo: pieceInfo}
 }
 
 /*
@@ -59,15 +58,14 @@ func (p *RetrievalStreamHandler) HandleQueryStream(stream rmnet.RetrievalQuerySt
 		return
 	}
 
-	// since 'paymentAddr' is empty, to `cborMarshal` a struct with empty address would get an error,
-	// it is impossible to `WriteQueryResponse` success.
-	// just return and output a log.
-	if p.paymentAddr == address.Undef {
-		log.Errorf("'RetrievalPaymentAddress' configuration is not set")
-		return
-	}
-
 	sendResp := func(resp retrievalmarket.QueryResponse) {
+		// since 'PaymentAddress' is empty, to `cborMarshal` a struct with empty address would get an error,
+		// it is impossible to `WriteQueryResponse` success.
+		// just return and output a log.
+		if resp.PaymentAddress == address.Undef {
+			log.Errorf("retrieval payment address of query %s is not configured", query.PayloadCID)
+			return
+		}
 		if err := stream.WriteQueryResponse(resp); err != nil {
 			log.Errorf("Retrieval query: writing query response: %s", err)
 		}
@@ -78,7 +76,7 @@ func (p *RetrievalStreamHandler) HandleQueryStream(stream rmnet.RetrievalQuerySt
 		PieceCIDFound:   retrievalmarket.QueryItemUnavailable,
 		MinPricePerByte: big.Zero(),
 		UnsealPrice:     big.Zero(),
-		PaymentAddress:  p.paymentAddr,
+		PaymentAddress:  p.pricer.DefaultPaymentAddress(),
 	}
 
 	minerDeals, err := p.pieceInfo.GetPieceInfoFromCid(ctx, query.PayloadCID, query.PieceCID)
@@ -95,16 +93,24 @@ func (p *RetrievalStreamHandler) HandleQueryStream(stream rmnet.RetrievalQuerySt
 		return
 	}
 
+	minerDeals = dealsOfPiece(minerDeals)
 	answer.Status = retrievalmarket.QueryResponseAvailable
-	//todo payload size maybe different with real piece size.
-	answer.Size = uint64(minerDeals[0].Proposal.PieceSize.Unpadded()) // TODO: verify on intermediate
+	answer.Size, err = p.pieceInfo.GetPayloadSize(ctx, query.PayloadCID, minerDeals)
+	if err != nil {
+		// client may lock more funds than needed, but the retrieval can still go on
+		log.Warnf("Retrieval query: get size of payload %s: %s, use piece size instead", query.PayloadCID, err)
+		answer.Size = uint64(minerDeals[0].Proposal.PieceSize.Unpadded())
+	}
 	answer.PieceCIDFound = retrievalmarket.QueryItemAvailable
-	answer.PaymentAddress = p.paymentAddr
-
-	//todo use market ask maybe need miner ask list for future
-	ask, err := p.askRepo.GetAsk(ctx, p.paymentAddr)
+	// quote the ask and payment address of the miner storing the data
+	answer.PaymentAddress = p.pricer.PaymentAddress(minerDeals[0].Proposal.Provider)
+	ask, err := p.pricer.GetDynamicAsk(ctx, retrievalmarket.PricingInput{
+		PayloadCID: query.PayloadCID,
+		PieceCID:   minerDeals[0].Proposal.PieceCID,
+		Client:     stream.RemotePeer(),
+	}, minerDeals)
 	if err != nil {
-		log.Errorf("Retrieval query: GetAsk: %s", err)
+		log.Errorf("Retrieval query: GetDynamicAsk: %s", err)
 		answer.Status = retrievalmarket.QueryResponseError
 		answer.Message = fmt.Sprintf("failed to price deal: %s", err)
 		sendResp(answer)
@@ -2,27 +2,43 @@ package rpc
 
 import (
 	"context"
+	"crypto/hmac"
 	"crypto/rand"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
 	"io"
 	"io/ioutil"
+	"time"
 
 	auth2 "github.com/filecoin-project/go-jsonrpc/auth"
 	"github.com/filecoin-project/venus-auth/auth"
 	"github.com/filecoin-project/venus-auth/cmd/jwtclient"
 	"github.com/filecoin-project/venus-auth/core"
+	"github.com/filecoin-project/venus-market/v2/config"
 	jwt3 "github.com/gbrlsnchs/jwt/v3"
 )
 
 type JwtClient struct {
-	alg *jwt3.HMACSHA
+	alg    *jwt3.HMACSHA
+	urlAlg *jwt3.HMACSHA
 }
 
 func NewJwtClient(secret []byte) *JwtClient {
 	return &JwtClient{
-		alg: jwt3.NewHS256(secret),
+		alg:    jwt3.NewHS256(secret),
+		urlAlg: jwt3.NewHS256(pieceUrlSecret(secret)),
 	}
 }
 
+// pieceUrlSecret derive the key of signed piece urls from the api secret, so that a signed url can't be used
+// as an api token, and an api token can't be used as a signed url
+func pieceUrlSecret(secret []byte) []byte {
+	mac := hmac.New(sha256.New, secret)
+	_, _ = mac.Write([]byte("piece-url"))
+	return mac.Sum(nil)
+}
+
 func (c *JwtClient) Verify(ctx context.Context, token string) ([]auth2.Permission, error) {
 	var payload auth.JWTPayload
 	_, err := jwt3.Verify([]byte(token), c.alg, &payload)
@@ -40,6 +56,51 @@ func (c *JwtClient) NewAuth(payload auth.JWTPayload) ([]byte, error) {
 	return jwt3.Sign(payload, c.alg)
 }
 
+// NewLocalJwtClient create jwt client with the secret of api config, if secret is not set,
+// a random one is generated and saved to config, so that tokens keep valid after restart
+func NewLocalJwtClient(home config.IHome, cfg *config.API) (*JwtClient, error) {
+	secKey, err := makeSecret(cfg)
+	if err != nil {
+		return nil, err
+	}
+	if len(cfg.Secret) == 0 {
+		cfg.Secret = hex.EncodeToString(secKey)
+		if err := config.SaveConfig(home); err != nil {
+			return nil, fmt.Errorf("save config failed:%s", err.Error())
+		}
+	}
+	return NewJwtClient(secKey), nil
+}
+
+// PieceUrlPayload is the claims of a signed piece url, it only grants access to one piece of one miner until expired
+type PieceUrlPayload struct {
+	PieceCID string `json:"piece"`
+	Miner    string `json:"miner"`
+	Expire   int64  `json:"exp"`
+}
+
+func (c *JwtClient) SignPieceUrl(payload PieceUrlPayload) (string, error) {
+	token, err := jwt3.Sign(payload, c.urlAlg)
+	if err != nil {
+		return "", err
+	}
+	return string(token), nil
+}
+
+func (c *JwtClient) VerifyPieceUrl(token string) (*PieceUrlPayload, error) {
+	var payload PieceUrlPayload
+	if _, err := jwt3.Verify([]byte(token), c.urlAlg, &payload); err != nil {
+		return nil, err
+	}
+	if len(payload.PieceCID) == 0 || len(payload.Miner) == 0 {
+		return nil, fmt.Errorf("not a piece url signature")
+	}
+	if time.Now().Unix() > payload.Expire {
+		return nil, fmt.Errorf("signature expired at %s", time.Unix(payload.Expire, 0))
+	}
+	return &payload, nil
+}
+
 func RandSecret() ([]byte, error) {
 	return ioutil.ReadAll(io.LimitReader(rand.Reader, 32))
 }
@@ -1,30 +1,76 @@
 package rpc
 
 import (
+	"context"
+	"errors"
 	"fmt"
 	"io"
 
+	"github.com/filecoin-project/dagstore/mount"
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-commp-utils/writer"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-jsonrpc/auth"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus-auth/core"
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	"github.com/filecoin-project/venus-market/v2/storageprovider"
+	"github.com/filecoin-project/venus-market/v2/utils"
+	"github.com/ipfs/go-cid"
 	logging "github.com/ipfs/go-log/v2"
 
 	"net/http"
 	"strconv"
+	"sync"
+	"time"
 )
 
 var resourceLog = logging.Logger("resource")
 
 var _ http.Handler = (*PieceStorageServer)(nil)
 
+// uploadableDealStatus are the deal states in which piece data could be uploaded to piece storage
+var uploadableDealStatus = []storagemarket.StorageDealStatus{
+	storagemarket.StorageDealWaitingForData,
+	storagemarket.StorageDealVerifyData,
+	storagemarket.StorageDealReserveProviderFunds,
+	storagemarket.StorageDealProviderFunding,
+	storagemarket.StorageDealPublish,
+	storagemarket.StorageDealPublishing,
+	storagemarket.StorageDealStaged,
+	storagemarket.StorageDealAwaitingPreCommit,
+	storagemarket.StorageDealSealing,
+	storagemarket.StorageDealActive,
+}
+
 type PieceStorageServer struct {
 	pieceStorageMgr *piecestorage.PieceStorageManager
+	repairWorker    *piecestorage.RepairWorker
+	storageDealRepo repo.StorageDealRepo
+	jwtClient       *JwtClient
+	cfg             *config.PieceStorage
+
+	uploadingLk sync.Mutex
+	uploading   map[string]struct{}
 }
 
-func NewPieceStorageServer(pieceStorageMgr *piecestorage.PieceStorageManager) *PieceStorageServer {
-	return &PieceStorageServer{pieceStorageMgr: pieceStorageMgr}
+func NewPieceStorageServer(pieceStorageMgr *piecestorage.PieceStorageManager, repairWorker *piecestorage.RepairWorker, storageDealRepo repo.StorageDealRepo, jwtClient *JwtClient, cfg *config.PieceStorage) *PieceStorageServer {
+	return &PieceStorageServer{
+		pieceStorageMgr: pieceStorageMgr,
+		repairWorker:    repairWorker,
+		storageDealRepo: storageDealRepo,
+		jwtClient:       jwtClient,
+		cfg:             cfg,
+		uploading:       map[string]struct{}{},
+	}
 }
 
 func (p *PieceStorageServer) ServeHTTP(res http.ResponseWriter, req *http.Request) {
-	if req.Method != http.MethodGet {
+	switch req.Method {
+	case http.MethodGet, http.MethodHead, http.MethodPut, http.MethodPost:
+	default:
 		logErrorAndResonse(res, http.StatusText(http.StatusMethodNotAllowed), http.StatusMethodNotAllowed)
 		return
 	}
@@ -34,8 +80,22 @@ func (p *PieceStorageServer) ServeHTTP(res http.ResponseWriter, req *http.Reques
 		logErrorAndResonse(res, "resource is empty", http.StatusBadRequest)
 		return
 	}
+
+	if req.Method == http.MethodPut || req.Method == http.MethodPost {
+		p.upload(res, req, resourceID)
+		return
+	}
+	p.download(res, req, resourceID)
+}
+
+func (p *PieceStorageServer) download(res http.ResponseWriter, req *http.Request, resourceID string) {
 	ctx := req.Context()
 
+	if code, err := p.authorize(req, resourceID, auth.Permission(core.PermRead)); err != nil {
+		logErrorAndResonse(res, fmt.Sprintf("access resource %s: %s", resourceID, err), code)
+		return
+	}
+
 	//todo consider priority strategy, priority oss, priority market transfer directly
 	pieceStorage, err := p.pieceStorageMgr.FindStorageForRead(ctx, resourceID)
 	if err != nil {
@@ -60,24 +120,304 @@ func (p *PieceStorageServer) ServeHTTP(res http.ResponseWriter, req *http.Reques
 		logErrorAndResonse(res, fmt.Sprintf("call piecestore.Len for %s: %s", resourceID, err), http.StatusInternalServerError)
 		return
 	}
-	res.Header().Set("Content-Length", strconv.FormatInt(flen, 10))
-
-	r, err := pieceStorage.GetReaderCloser(req.Context(), resourceID)
-	if err != nil {
-		logErrorAndResonse(res, fmt.Sprintf("failed to open reader for %s: %s", resourceID, err), http.StatusInternalServerError)
-		return
-	}
 
+	r := newPieceReadSeeker(ctx, pieceStorage, resourceID, flen)
 	defer func() {
 		if err = r.Close(); err != nil {
 			log.Errorf("unable to close http %v", err)
 		}
 	}()
 
-	// TODO:
-	// as we can not override http response headers after body transfer has began
-	// we can only log the error info here
-	_, _ = io.Copy(res, r)
+	// resource id is the piece cid, so the content never changes and can be used as a strong etag.
+	// ServeContent handles HEAD, Range and If-Range requests
+	res.Header().Set("Content-Type", "application/octet-stream")
+	res.Header().Set("ETag", strconv.Quote(resourceID))
+	http.ServeContent(res, req, "", time.Time{}, r)
+}
+
+// pieceReadChunkSize is the size of each read from storage when serving a range of piece
+const pieceReadChunkSize = 4 << 20
+
+// pieceReadSeeker serve a piece to http.ServeContent with few requests to storage, which matters for s3 storages
+// where each read is a request. Reading from the start streams the whole piece through one reader, reading from
+// other offsets, which happens for range requests, goes through ReadAt in large chunks.
+type pieceReadSeeker struct {
+	ctx        context.Context
+	storage    piecestorage.IPieceStorage
+	resourceID string
+	size       int64
+	offset     int64
+
+	stream io.ReadCloser
+	ra     mount.Reader
+	buf    []byte
+	bufOff int64
+}
+
+func newPieceReadSeeker(ctx context.Context, storage piecestorage.IPieceStorage, resourceID string, size int64) *pieceReadSeeker {
+	return &pieceReadSeeker{ctx: ctx, storage: storage, resourceID: resourceID, size: size}
+}
+
+func (r *pieceReadSeeker) Read(p []byte) (int, error) {
+	if r.offset >= r.size {
+		return 0, io.EOF
+	}
+
+	if r.stream != nil || (r.offset == 0 && r.ra == nil) {
+		if r.stream == nil {
+			stream, err := r.storage.GetReaderCloser(r.ctx, r.resourceID)
+			if err != nil {
+				return 0, err
+			}
+			r.stream = stream
+		}
+		n, err := r.stream.Read(p)
+		r.offset += int64(n)
+		return n, err
+	}
+
+	if r.offset < r.bufOff || r.offset >= r.bufOff+int64(len(r.buf)) {
+		if r.ra == nil {
+			ra, err := r.storage.GetMountReader(r.ctx, r.resourceID)
+			if err != nil {
+				return 0, err
+			}
+			r.ra = ra
+		}
+		size := r.size - r.offset
+		if size > pieceReadChunkSize {
+			size = pieceReadChunkSize
+		}
+		if int64(cap(r.buf)) < size {
+			r.buf = make([]byte, size)
+		}
+		n, err := r.ra.ReadAt(r.buf[:size], r.offset)
+		if err != nil && !(err == io.EOF && n > 0) {
+			r.buf = r.buf[:0]
+			return 0, err
+		}
+		r.buf, r.bufOff = r.buf[:n], r.offset
+	}
+
+	n := copy(p, r.buf[r.offset-r.bufOff:])
+	r.offset += int64(n)
+	return n, nil
+}
+
+func (r *pieceReadSeeker) Seek(offset int64, whence int) (int64, error) {
+	switch whence {
+	case io.SeekStart:
+	case io.SeekCurrent:
+		offset += r.offset
+	case io.SeekEnd:
+		offset += r.size
+	default:
+		return 0, fmt.Errorf("invalid whence %d", whence)
+	}
+	if offset < 0 {
+		return 0, fmt.Errorf("negative offset %d", offset)
+	}
+
+	// the stream can only go forward from where it is
+	if offset != r.offset && r.stream != nil {
+		if err := r.stream.Close(); err != nil {
+			resourceLog.Warnf("close reader of %s: %v", r.resourceID, err)
+		}
+		r.stream = nil
+	}
+	r.offset = offset
+	return offset, nil
+}
+
+func (r *pieceReadSeeker) Close() error {
+	var err error
+	if r.stream != nil {
+		err = r.stream.Close()
+	}
+	if r.ra != nil {
+		if cerr := r.ra.Close(); cerr != nil {
+			err = cerr
+		}
+	}
+	return err
+}
+
+// upload save the piece data in request body to piece storage, the data must match the piece cid of an existing deal.
+// data is verified while streaming to storage, storage would discard the data if verification fails
+func (p *PieceStorageServer) upload(res http.ResponseWriter, req *http.Request, resourceID string) {
+	ctx := req.Context()
+
+	// signed url is only for download, upload require a token with write permission if auth is required
+	if p.cfg.RequireAuth && !auth.HasPerm(ctx, nil, auth.Permission(core.PermWrite)) {
+		logErrorAndResonse(res, fmt.Sprintf("upload resource %s: require token with %s permission", resourceID, core.PermWrite), http.StatusUnauthorized)
+		return
+	}
+
+	pieceCid, err := cid.Decode(resourceID)
+	if err != nil {
+		logErrorAndResonse(res, fmt.Sprintf("resource %s is not a valid piece cid: %s", resourceID, err), http.StatusBadRequest)
+		return
+	}
+
+	deals, err := p.storageDealRepo.GetDealsByPieceCidAndStatus(ctx, pieceCid, uploadableDealStatus...)
+	if err != nil {
+		if errors.Is(err, repo.ErrNotFound) {
+			logErrorAndResonse(res, fmt.Sprintf("no deal is waiting for piece %s", resourceID), http.StatusNotFound)
+			return
+		}
+		logErrorAndResonse(res, fmt.Sprintf("get deals of piece %s: %s", resourceID, err), http.StatusInternalServerError)
+		return
+	}
+	pieceSize := deals[0].Proposal.PieceSize
+
+	if !p.startUpload(resourceID) {
+		logErrorAndResonse(res, fmt.Sprintf("piece %s is being uploaded", resourceID), http.StatusConflict)
+		return
+	}
+	defer p.finishUpload(resourceID)
+
+	if _, err = p.pieceStorageMgr.FindStorageForRead(ctx, resourceID); err == nil {
+		logErrorAndResonse(res, fmt.Sprintf("piece %s already exists", resourceID), http.StatusConflict)
+		return
+	}
+
+	maxSize := int64(pieceSize.Unpadded())
+	if req.ContentLength > maxSize {
+		logErrorAndResonse(res, fmt.Sprintf("piece %s size %d exceeds %d", resourceID, req.ContentLength, maxSize), http.StatusRequestEntityTooLarge)
+		return
+	}
+	allocSize := maxSize
+	if req.ContentLength > 0 {
+		allocSize = req.ContentLength
+	}
+
+	pieceStorage, err := p.pieceStorageMgr.FindStorageForWrite(allocSize)
+	if err != nil {
+		logErrorAndResonse(res, fmt.Sprintf("find storage for piece %s: %s", resourceID, err), http.StatusInsufficientStorage)
+		return
+	}
+
+	vr := newCommPVerifyReader(req.Body, pieceCid, pieceSize)
+	size, err := pieceStorage.SaveTo(ctx, resourceID, vr)
+	if err != nil {
+		if vr.err != nil {
+			logErrorAndResonse(res, fmt.Sprintf("verify piece %s: %s", resourceID, vr.err), http.StatusBadRequest)
+			return
+		}
+		logErrorAndResonse(res, fmt.Sprintf("save piece %s to %s: %s", resourceID, pieceStorage.GetName(), err), http.StatusInternalServerError)
+		return
+	}
+	resourceLog.Infof("upload piece %s to storage %s, size %d", resourceID, pieceStorage.GetName(), size)
+
+	p.repairWorker.Queue(resourceID)
+	res.WriteHeader(http.StatusCreated)
+}
+
+// startUpload mark the piece as being uploaded, return false if another upload of the piece is in progress
+func (p *PieceStorageServer) startUpload(resourceID string) bool {
+	p.uploadingLk.Lock()
+	defer p.uploadingLk.Unlock()
+	if _, ok := p.uploading[resourceID]; ok {
+		return false
+	}
+	p.uploading[resourceID] = struct{}{}
+	return true
+}
+
+func (p *PieceStorageServer) finishUpload(resourceID string) {
+	p.uploadingLk.Lock()
+	defer p.uploadingLk.Unlock()
+	delete(p.uploading, resourceID)
+}
+
+// commPVerifyReader compute commP of data read through it, and return an error instead of io.EOF
+// if the data exceeds the piece size or doesn't match the piece cid
+type commPVerifyReader struct {
+	r         io.Reader
+	w         *writer.Writer
+	pieceCid  cid.Cid
+	pieceSize abi.PaddedPieceSize
+	read      int64
+	err       error
+}
+
+func newCommPVerifyReader(r io.Reader, pieceCid cid.Cid, pieceSize abi.PaddedPieceSize) *commPVerifyReader {
+	return &commPVerifyReader{r: r, w: &writer.Writer{}, pieceCid: pieceCid, pieceSize: pieceSize}
+}
+
+func (vr *commPVerifyReader) Read(p []byte) (int, error) {
+	if vr.err != nil {
+		return 0, vr.err
+	}
+
+	n, err := vr.r.Read(p)
+	if n > 0 {
+		vr.read += int64(n)
+		if vr.read > int64(vr.pieceSize.Unpadded()) {
+			vr.err = fmt.Errorf("data exceeds max size %d of piece", vr.pieceSize.Unpadded())
+			return n, vr.err
+		}
+		if _, werr := vr.w.Write(p[:n]); werr != nil {
+			vr.err = fmt.Errorf("write data to CommP writer: %w", werr)
+			return n, vr.err
+		}
+	}
+
+	if err == io.EOF {
+		commP, cerr := utils.PaddedCommP(vr.w, vr.pieceSize)
+		if cerr != nil {
+			vr.err = cerr
+			return n, vr.err
+		}
+		if !commP.Equals(vr.pieceCid) {
+			vr.err = fmt.Errorf("commP mismatch, expect %s, actual %s", vr.pieceCid, commP)
+			return n, vr.err
+		}
+	}
+	return n, err
+}
+
+// authorize check whether request is allowed to access the resource, the bearer token in header has been verified
+// by auth mux with both local and remote jwt client, request without token should carry a signed url of the resource
+func (p *PieceStorageServer) authorize(req *http.Request, resourceID string, perm auth.Permission) (int, error) {
+	if !p.cfg.RequireAuth {
+		return http.StatusOK, nil
+	}
+	if auth.HasPerm(req.Context(), nil, perm) {
+		return http.StatusOK, nil
+	}
+
+	signature := req.URL.Query().Get("signature")
+	if len(signature) == 0 {
+		return http.StatusUnauthorized, fmt.Errorf("require token with %s permission or signed url", perm)
+	}
+	payload, err := p.jwtClient.VerifyPieceUrl(signature)
+	if err != nil {
+		return http.StatusUnauthorized, fmt.Errorf("invalid signature: %w", err)
+	}
+	if payload.PieceCID != resourceID {
+		return http.StatusForbidden, fmt.Errorf("signature is issued for piece %s", payload.PieceCID)
+	}
+
+	// the miner of signature must still have a deal of the piece
+	pieceCid, err := cid.Decode(resourceID)
+	if err != nil {
+		return http.StatusBadRequest, fmt.Errorf("invalid piece cid %s: %w", resourceID, err)
+	}
+	miner, err := address.NewFromString(payload.Miner)
+	if err != nil {
+		return http.StatusUnauthorized, fmt.Errorf("invalid miner %s in signature: %w", payload.Miner, err)
+	}
+	deals, err := p.storageDealRepo.GetDealsByPieceCidAndStatus(req.Context(), pieceCid, storageprovider.ReadyRetrievalDealStatus...)
+	if err != nil && !errors.Is(err, repo.ErrNotFound) {
+		return http.StatusInternalServerError, fmt.Errorf("get deals of piece %s: %w", resourceID, err)
+	}
+	for _, deal := range deals {
+		if deal.Proposal.Provider == miner {
+			return http.StatusOK, nil
+		}
+	}
+	return http.StatusForbidden, fmt.Errorf("miner %s has no deal of piece %s", miner, resourceID)
 }
 
 func logErrorAndResonse(res http.ResponseWriter, err string, code int) {
@@ -4,30 +4,78 @@ import (
 	"bytes"
 	"context"
 	"fmt"
-
+	"io"
 	"io/ioutil"
+	"math/rand"
 	"net/http"
 	"net/http/httptest"
+	"net/url"
 	"testing"
+	"time"
 
+	"github.com/filecoin-project/dagstore/mount"
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/builtin/v8/market"
+	"github.com/filecoin-project/go-state-types/crypto"
+	auth2 "github.com/filecoin-project/venus-auth/auth"
+	"github.com/filecoin-project/venus-auth/cmd/jwtclient"
+	"github.com/filecoin-project/venus-auth/core"
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/badger"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	"github.com/filecoin-project/venus-market/v2/utils"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-datastore"
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
 )
 
-func setupTestServer(t *testing.T) (*piecestorage.MemPieceStore, http.Handler) {
-	pm, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+func setupTestServer(t *testing.T, cfg *config.PieceStorage) (*piecestorage.MemPieceStore, repo.StorageDealRepo, *JwtClient, http.Handler) {
+	pm, err := piecestorage.NewPieceStorageManager(cfg)
 	require.NoError(t, err)
 	ps := piecestorage.NewMemPieceStore("memtest", nil)
 	pm.AddMemPieceStorage(ps)
-	pss := NewPieceStorageServer(pm)
-	return ps, pss
+	dealRepo := badger.NewStorageDealRepo(badger.NewStorageDealsDS(datastore.NewMapDatastore()))
+	secret, err := RandSecret()
+	require.NoError(t, err)
+	jwtClient := NewJwtClient(secret)
+	pss := NewPieceStorageServer(pm, nil, dealRepo, jwtClient, cfg)
+	return ps, dealRepo, jwtClient, jwtclient.NewAuthMux(jwtClient, nil, pss)
+}
+
+func newTestDeal(t *testing.T, pieceCid cid.Cid, pieceSize abi.PaddedPieceSize, state storagemarket.StorageDealStatus) *types.MinerDeal {
+	provider, err := address.NewIDAddress(1000)
+	require.NoError(t, err)
+	client, err := address.NewIDAddress(1001)
+	require.NoError(t, err)
+	label, err := market.NewLabelFromString("label")
+	require.NoError(t, err)
+	return &types.MinerDeal{
+		ClientDealProposal: market.ClientDealProposal{
+			Proposal: market.DealProposal{
+				PieceCID:  pieceCid,
+				PieceSize: pieceSize,
+				Client:    client,
+				Provider:  provider,
+				Label:     label,
+			},
+			ClientSignature: crypto.Signature{
+				Type: crypto.SigTypeBLS,
+				Data: []byte("bls"),
+			},
+		},
+		ProposalCid: pieceCid,
+		State:       state,
+	}
 }
 
 func TestResouceDownload(t *testing.T) {
 	ctx := context.Background()
-	ps, psm := setupTestServer(t)
+	ps, _, _, psm := setupTestServer(t, &config.PieceStorage{})
 
 	resourceId := "s1"
 	_, err := ps.SaveTo(ctx, resourceId, bytes.NewBufferString("mock resource1 content"))
@@ -63,6 +111,327 @@ func TestResouceDownload(t *testing.T) {
 		result, err := ioutil.ReadAll(w.Body)
 		assert.Nil(t, err)
 		assert.Equal(t, "mock resource2 content", string(result))
+		assert.Equal(t, "bytes", w.Header().Get("Accept-Ranges"))
+		assert.Equal(t, fmt.Sprintf("\"%s\"", resourceId2), w.Header().Get("ETag"))
+	})
+
+	t.Run("range", func(t *testing.T) {
+		path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", resourceId2)
+		req := httptest.NewRequest(http.MethodGet, path, nil)
+		req.Header.Set("Range", "bytes=5-13")
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+
+		assert.Equal(t, http.StatusPartialContent, w.Code)
+		assert.Equal(t, "bytes 5-13/22", w.Header().Get("Content-Range"))
+		result, err := ioutil.ReadAll(w.Body)
+		assert.Nil(t, err)
+		assert.Equal(t, "resource2", string(result))
+	})
+
+	t.Run("if-range", func(t *testing.T) {
+		path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", resourceId2)
+		req := httptest.NewRequest(http.MethodGet, path, nil)
+		req.Header.Set("Range", "bytes=5-13")
+		req.Header.Set("If-Range", "\"other\"")
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+
+		// etag not match, return the whole content
+		assert.Equal(t, http.StatusOK, w.Code)
+		result, err := ioutil.ReadAll(w.Body)
+		assert.Nil(t, err)
+		assert.Equal(t, "mock resource2 content", string(result))
+	})
+
+	t.Run("head", func(t *testing.T) {
+		path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", resourceId2)
+		req := httptest.NewRequest(http.MethodHead, path, nil)
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+
+		assert.Equal(t, http.StatusOK, w.Code)
+		assert.Equal(t, "22", w.Header().Get("Content-Length"))
+		assert.Equal(t, 0, w.Body.Len())
+	})
+
+	t.Run("method not allowed", func(t *testing.T) {
+		path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", resourceId2)
+		req := httptest.NewRequest(http.MethodDelete, path, nil)
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+
+		assert.Equal(t, http.StatusMethodNotAllowed, w.Code)
+	})
+}
+
+func TestResouceAuth(t *testing.T) {
+	ctx := context.Background()
+	ps, dealRepo, jwtClient, psm := setupTestServer(t, &config.PieceStorage{RequireAuth: true})
+
+	pieceSize := abi.PaddedPieceSize(4096)
+	data := []byte("mock resource1 content")
+	pieceCid, _, err := utils.GenerateCommPFromReader(bytes.NewReader(data), pieceSize)
+	require.NoError(t, err)
+	resourceId := pieceCid.String()
+	_, err = ps.SaveTo(ctx, resourceId, bytes.NewReader(data))
+	assert.Nil(t, err)
+	require.NoError(t, dealRepo.SaveDeal(ctx, newTestDeal(t, pieceCid, pieceSize, storagemarket.StorageDealActive)))
+
+	download := func(query url.Values, token string) *httptest.ResponseRecorder {
+		query.Set("resource-id", resourceId)
+		req := httptest.NewRequest(http.MethodGet, "http://127.0.0.1:3030?"+query.Encode(), nil)
+		if len(token) > 0 {
+			req.Header.Set("Authorization", "Bearer "+token)
+		}
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+		return w
+	}
+	sign := func(payload PieceUrlPayload) string {
+		signature, err := jwtClient.SignPieceUrl(payload)
+		require.NoError(t, err)
+		return signature
+	}
+	expire := time.Now().Add(time.Hour).Unix()
+
+	t.Run("anonymous", func(t *testing.T) {
+		w := download(url.Values{}, "")
+		assert.Equal(t, http.StatusUnauthorized, w.Code)
+	})
+
+	t.Run("bearer token", func(t *testing.T) {
+		token, err := jwtClient.NewAuth(auth2.JWTPayload{Perm: core.PermRead, Name: "reader"})
+		require.NoError(t, err)
+		w := download(url.Values{}, string(token))
+		assert.Equal(t, http.StatusOK, w.Code)
+		assert.Equal(t, "mock resource1 content", w.Body.String())
+	})
+
+	t.Run("signed url", func(t *testing.T) {
+		query := url.Values{}
+		query.Set("signature", sign(PieceUrlPayload{PieceCID: resourceId, Miner: "t01000", Expire: expire}))
+		w := download(query, "")
+		assert.Equal(t, http.StatusOK, w.Code)
+		assert.Equal(t, "mock resource1 content", w.Body.String())
+	})
+
+	t.Run("signed url of other miner", func(t *testing.T) {
+		query := url.Values{}
+		query.Set("signature", sign(PieceUrlPayload{PieceCID: resourceId, Miner: "t01002", Expire: expire}))
+		w := download(query, "")
+		assert.Equal(t, http.StatusForbidden, w.Code)
+	})
+
+	t.Run("api token as signature", func(t *testing.T) {
+		token, err := jwtClient.NewAuth(auth2.JWTPayload{Perm: core.PermRead, Name: "reader"})
+		require.NoError(t, err)
+		query := url.Values{}
+		query.Set("signature", string(token))
+		w := download(query, "")
+		assert.Equal(t, http.StatusUnauthorized, w.Code)
+	})
+
+	t.Run("signed url of other piece", func(t *testing.T) {
+		query := url.Values{}
+		query.Set("signature", sign(PieceUrlPayload{PieceCID: "s2", Miner: "t01000", Expire: expire}))
+		w := download(query, "")
+		assert.Equal(t, http.StatusForbidden, w.Code)
 	})
 
+	t.Run("expired signed url", func(t *testing.T) {
+		query := url.Values{}
+		query.Set("signature", sign(PieceUrlPayload{PieceCID: resourceId, Miner: "t01000", Expire: time.Now().Add(-time.Minute).Unix()}))
+		w := download(query, "")
+		assert.Equal(t, http.StatusUnauthorized, w.Code)
+	})
+
+	t.Run("signature by other secret", func(t *testing.T) {
+		secret, err := RandSecret()
+		require.NoError(t, err)
+		signature, err := NewJwtClient(secret).SignPieceUrl(PieceUrlPayload{PieceCID: resourceId, Miner: "t01000", Expire: expire})
+		require.NoError(t, err)
+
+		query := url.Values{}
+		query.Set("signature", signature)
+		w := download(query, "")
+		assert.Equal(t, http.StatusUnauthorized, w.Code)
+	})
+}
+
+func TestResourceUpload(t *testing.T) {
+	ctx := context.Background()
+	ps, dealRepo, jwtClient, psm := setupTestServer(t, &config.PieceStorage{RequireAuth: true})
+
+	pieceSize := abi.PaddedPieceSize(4096)
+	data := make([]byte, 2000)
+	rand.New(rand.NewSource(1)).Read(data)
+	pieceCid, _, err := utils.GenerateCommPFromReader(bytes.NewReader(data), pieceSize)
+	require.NoError(t, err)
+
+	require.NoError(t, dealRepo.SaveDeal(ctx, newTestDeal(t, pieceCid, pieceSize, storagemarket.StorageDealWaitingForData)))
+
+	token, err := jwtClient.NewAuth(auth2.JWTPayload{Perm: core.PermWrite, Name: "writer"})
+	require.NoError(t, err)
+
+	upload := func(resourceId string, body []byte, token string) *httptest.ResponseRecorder {
+		path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", resourceId)
+		req := httptest.NewRequest(http.MethodPut, path, bytes.NewReader(body))
+		if len(token) > 0 {
+			req.Header.Set("Authorization", "Bearer "+token)
+		}
+		w := httptest.NewRecorder()
+		psm.ServeHTTP(w, req)
+		return w
+	}
+
+	t.Run("without token", func(t *testing.T) {
+		w := upload(pieceCid.String(), data, "")
+		assert.Equal(t, http.StatusUnauthorized, w.Code)
+	})
+
+	t.Run("unknown piece", func(t *testing.T) {
+		w := upload("not-a-cid", data, string(token))
+		assert.Equal(t, http.StatusBadRequest, w.Code)
+
+		otherCid, _, err := utils.GenerateCommPFromReader(bytes.NewReader(data[:1000]), pieceSize)
+		require.NoError(t, err)
+		w = upload(otherCid.String(), data[:1000], string(token))
+		assert.Equal(t, http.StatusNotFound, w.Code)
+	})
+
+	t.Run("too large", func(t *testing.T) {
+		w := upload(pieceCid.String(), make([]byte, pieceSize.Unpadded()+1), string(token))
+		assert.Equal(t, http.StatusRequestEntityTooLarge, w.Code)
+	})
+
+	t.Run("commP mismatch", func(t *testing.T) {
+		corrupt := append([]byte{}, data...)
+		corrupt[0]++
+		w := upload(pieceCid.String(), corrupt, string(token))
+		assert.Equal(t, http.StatusBadRequest, w.Code)
+
+		has, err := ps.Has(ctx, pieceCid.String())
+		require.NoError(t, err)
+		assert.False(t, has)
+	})
+
+	t.Run("success", func(t *testing.T) {
+		w := upload(pieceCid.String(), data, string(token))
+		assert.Equal(t, http.StatusCreated, w.Code)
+
+		r, err := ps.GetReaderCloser(ctx, pieceCid.String())
+		require.NoError(t, err)
+		stored, err := ioutil.ReadAll(r)
+		require.NoError(t, err)
+		assert.Equal(t, data, stored)
+
+		w = upload(pieceCid.String(), data, string(token))
+		assert.Equal(t, http.StatusConflict, w.Code)
+	})
+}
+
+func TestResourceUploadWithoutAuth(t *testing.T) {
+	ctx := context.Background()
+	ps, dealRepo, _, psm := setupTestServer(t, &config.PieceStorage{})
+
+	pieceSize := abi.PaddedPieceSize(4096)
+	data := make([]byte, 2000)
+	rand.New(rand.NewSource(1)).Read(data)
+	pieceCid, _, err := utils.GenerateCommPFromReader(bytes.NewReader(data), pieceSize)
+	require.NoError(t, err)
+	require.NoError(t, dealRepo.SaveDeal(ctx, newTestDeal(t, pieceCid, pieceSize, storagemarket.StorageDealWaitingForData)))
+
+	path := fmt.Sprintf("http://127.0.0.1:3030?resource-id=%s", pieceCid)
+	req := httptest.NewRequest(http.MethodPut, path, bytes.NewReader(data))
+	w := httptest.NewRecorder()
+	psm.ServeHTTP(w, req)
+	assert.Equal(t, http.StatusCreated, w.Code)
+
+	has, err := ps.Has(ctx, pieceCid.String())
+	require.NoError(t, err)
+	assert.True(t, has)
+}
+
+func TestConcurrentUpload(t *testing.T) {
+	pss := NewPieceStorageServer(nil, nil, nil, nil, &config.PieceStorage{})
+	assert.True(t, pss.startUpload("s1"))
+	assert.False(t, pss.startUpload("s1"))
+	assert.True(t, pss.startUpload("s2"))
+
+	pss.finishUpload("s1")
+	assert.True(t, pss.startUpload("s1"))
+}
+
+type countingMountReader struct {
+	mount.Reader
+	readAt *int
+}
+
+func (r countingMountReader) ReadAt(p []byte, off int64) (int, error) {
+	*r.readAt++
+	return r.Reader.ReadAt(p, off)
+}
+
+type countingPieceStore struct {
+	piecestorage.IPieceStorage
+	streams int
+	readAt  int
+}
+
+func (s *countingPieceStore) GetReaderCloser(ctx context.Context, resourceId string) (io.ReadCloser, error) {
+	s.streams++
+	return s.IPieceStorage.GetReaderCloser(ctx, resourceId)
+}
+
+func (s *countingPieceStore) GetMountReader(ctx context.Context, resourceId string) (mount.Reader, error) {
+	r, err := s.IPieceStorage.GetMountReader(ctx, resourceId)
+	if err != nil {
+		return nil, err
+	}
+	return countingMountReader{Reader: r, readAt: &s.readAt}, nil
+}
+
+func TestPieceReadSeeker(t *testing.T) {
+	ctx := context.Background()
+	data := make([]byte, 3*pieceReadChunkSize+100)
+	rand.New(rand.NewSource(1)).Read(data) //nolint:gosec
+	ps := piecestorage.NewMemPieceStore("memtest", nil)
+	_, err := ps.SaveTo(ctx, "s1", bytes.NewReader(data))
+	require.NoError(t, err)
+
+	t.Run("whole piece", func(t *testing.T) {
+		st := &countingPieceStore{IPieceStorage: ps}
+		r := newPieceReadSeeker(ctx, st, "s1", int64(len(data)))
+		defer r.Close() //nolint:errcheck
+
+		result, err := ioutil.ReadAll(r)
+		require.NoError(t, err)
+		assert.Equal(t, data, result)
+		assert.Equal(t, 1, st.streams)
+		assert.Equal(t, 0, st.readAt)
+	})
+
+	t.Run("range", func(t *testing.T) {
+		st := &countingPieceStore{IPieceStorage: ps}
+		r := newPieceReadSeeker(ctx, st, "s1", int64(len(data)))
+		defer r.Close() //nolint:errcheck
+
+		start := int64(pieceReadChunkSize - 10)
+		length := int64(2*pieceReadChunkSize + 50)
+		_, err := r.Seek(start, io.SeekStart)
+		require.NoError(t, err)
+		result, err := ioutil.ReadAll(io.LimitReader(r, length))
+		require.NoError(t, err)
+		assert.Equal(t, data[start:start+length], result)
+		assert.Equal(t, 0, st.streams)
+		assert.Equal(t, 3, st.readAt)
+
+		// read the tail after seeking from end
+		_, err = r.Seek(-20, io.SeekEnd)
+		require.NoError(t, err)
+		result, err = ioutil.ReadAll(r)
+		require.NoError(t, err)
+		assert.Equal(t, data[len(data)-20:], result)
+	})
 }
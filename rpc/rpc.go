@@ -15,6 +15,7 @@ import (
 	"github.com/filecoin-project/venus-auth/cmd/jwtclient"
 	"github.com/filecoin-project/venus-auth/core"
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	"github.com/gorilla/mux"
 	logging "github.com/ipfs/go-log/v2"
 	manet "github.com/multiformats/go-multiaddr/net"
@@ -23,22 +24,20 @@ import (
 var log = logging.Logger("modules")
 
 func ServeRPC(ctx context.Context, home config.IHome, cfg *config.API, mux *mux.Router, maxRequestSize int64,
-	namespace string, authClient *jwtclient.AuthClient, api interface{}, shutdownCh <-chan struct{}) error {
+	namespace string, localJwtClient *JwtClient, authClient *jwtclient.AuthClient, apis []interface{}, shutdownCh <-chan struct{}) error {
 	serverOptions := make([]jsonrpc.ServerOption, 0)
 	if maxRequestSize != 0 { // config set
 		serverOptions = append(serverOptions, jsonrpc.WithMaxRequestSize(maxRequestSize))
 	}
 
 	rpcServer := jsonrpc.NewServer(serverOptions...)
-	rpcServer.Register(namespace, api)
+	// all apis share the same namespace, so that clients of each api can call the same endpoint
+	for _, api := range apis {
+		rpcServer.Register(namespace, api)
+	}
 	mux.Handle("/rpc/v0", rpcServer)
 	mux.PathPrefix("/").Handler(http.DefaultServeMux)
 
-	secKey, err := makeSecret(cfg)
-	if err != nil {
-		return err
-	}
-	localJwtClient := NewJwtClient(secKey)
 	token, err := localJwtClient.NewAuth(auth2.JWTPayload{
 		Perm: core.PermAdmin,
 		Name: "MarketLocalToken",
@@ -46,7 +45,7 @@ func ServeRPC(ctx context.Context, home config.IHome, cfg *config.API, mux *mux.
 	if err != nil {
 		return err
 	}
-	if err = saveAPIInfo(home, cfg, secKey, token); err != nil {
+	if err = saveAPIInfo(home, cfg, token); err != nil {
 		return err
 	}
 
@@ -56,7 +55,16 @@ func ServeRPC(ctx context.Context, home config.IHome, cfg *config.API, mux *mux.
 	} else {
 		handler = jwtclient.NewAuthMux(localJwtClient, nil, mux)
 	}
-	srv := &http.Server{Handler: handler}
+
+	exporter, err := metrics.Exporter()
+	if err != nil {
+		return err
+	}
+	// metrics are mounted outside the auth mux, so that prometheus can scrape them without a token
+	rootMux := http.NewServeMux()
+	rootMux.Handle("/debug/metrics", exporter)
+	rootMux.Handle("/", handler)
+	srv := &http.Server{Handler: rootMux}
 
 	go func() {
 		select {
@@ -99,14 +107,7 @@ func makeSecret(apiCfg *config.API) ([]byte, error) {
 	return RandSecret()
 }
 
-func saveAPIInfo(home config.IHome, apiCfg *config.API, secKey, token []byte) error {
-	if len(apiCfg.Secret) == 0 {
-		apiCfg.Secret = hex.EncodeToString(secKey)
-		err := config.SaveConfig(home)
-		if err != nil {
-			return fmt.Errorf("save config failed:%s", err.Error())
-		}
-	}
+func saveAPIInfo(home config.IHome, apiCfg *config.API, token []byte) error {
 	homePath, err := home.HomePath()
 	if err != nil {
 		return fmt.Errorf("unable to home path to save api/token")
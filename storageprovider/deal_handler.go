@@ -2,17 +2,16 @@ package storageprovider
 
 import (
 	"context"
+	"errors"
 	"fmt"
 	"io"
 	"os"
 
 	"github.com/ipfs/go-cid"
 	carv2 "github.com/ipld/go-car/v2"
+	"go.opencensus.io/tag"
 
 	"github.com/filecoin-project/go-address"
-	"github.com/filecoin-project/go-commp-utils/writer"
-	commcid "github.com/filecoin-project/go-fil-commcid"
-	commp "github.com/filecoin-project/go-fil-commp-hashhash"
 	"github.com/filecoin-project/go-fil-markets/filestore"
 	"github.com/filecoin-project/go-fil-markets/storagemarket"
 	"github.com/filecoin-project/go-fil-markets/storagemarket/impl/connmanager"
@@ -27,10 +26,13 @@ import (
 	"github.com/filecoin-project/specs-actors/v7/actors/builtin/market"
 	"github.com/filecoin-project/specs-actors/v7/actors/builtin/miner"
 
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	minermgr2 "github.com/filecoin-project/venus-market/v2/minermgr"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	network2 "github.com/filecoin-project/venus-market/v2/network"
+	"github.com/filecoin-project/venus-market/v2/notify"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	"github.com/filecoin-project/venus-market/v2/utils"
 	vTypes "github.com/filecoin-project/venus/venus-shared/types"
 	types "github.com/filecoin-project/venus/venus-shared/types/market"
 )
@@ -59,6 +61,8 @@ type StorageDealProcessImpl struct {
 
 	minerMgr        minermgr2.IAddrMgr
 	pieceStorageMgr *piecestorage.PieceStorageManager
+	repairWorker    *piecestorage.RepairWorker
+	notifier        *notify.WebhookNotifier
 }
 
 // NewStorageDealProcessImpl returns a new deal process instance
@@ -72,8 +76,10 @@ func NewStorageDealProcessImpl(
 	minerMgr minermgr2.IAddrMgr,
 	repo repo.Repo,
 	pieceStorageMgr *piecestorage.PieceStorageManager,
+	repairWorker *piecestorage.RepairWorker,
 	dataTransfer network2.ProviderDataTransfer,
 	dagStore stores.DAGStoreWrapper,
+	notifier *notify.WebhookNotifier,
 ) (StorageDealHandler, error) {
 	stores := stores.NewReadWriteBlockstores()
 
@@ -99,7 +105,9 @@ func NewStorageDealProcessImpl(
 		minerMgr: minerMgr,
 
 		pieceStorageMgr: pieceStorageMgr,
+		repairWorker:    repairWorker,
 		dagStore:        dagStore,
+		notifier:        notifier,
 	}, nil
 }
 
@@ -271,7 +279,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 
 		deal.State = storagemarket.StorageDealReserveProviderFunds
 
-		err = storageDealPorcess.deals.SaveDeal(ctx, deal)
+		err = storageDealPorcess.saveDealState(ctx, deal)
 		if err != nil {
 			deal.PiecePath = filestore.Path("")
 			deal.MetadataPath = filestore.Path("")
@@ -312,7 +320,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 			deal.State = storagemarket.StorageDealPublish // PublishDeal
 		}
 
-		err = storageDealPorcess.deals.SaveDeal(ctx, deal)
+		err = storageDealPorcess.saveDealState(ctx, deal)
 		if err != nil {
 			return storageDealPorcess.HandleError(ctx, deal, fmt.Errorf("fail to save deal to database"))
 		}
@@ -329,7 +337,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 			}
 			deal.State = storagemarket.StorageDealPublish
 
-			err = storageDealPorcess.deals.SaveDeal(ctx, deal)
+			err = storageDealPorcess.saveDealState(ctx, deal)
 			if err != nil {
 				return storageDealPorcess.HandleError(ctx, deal, fmt.Errorf("fail to save deal to database"))
 			}
@@ -359,7 +367,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 		deal.PublishCid = &pdMCid
 
 		deal.State = storagemarket.StorageDealPublishing
-		err = storageDealPorcess.deals.SaveDeal(ctx, deal)
+		err = storageDealPorcess.saveDealState(ctx, deal)
 		if err != nil {
 			return storageDealPorcess.HandleError(ctx, deal, fmt.Errorf("fail to save deal to database"))
 		}
@@ -379,7 +387,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 			deal.DealID = res.DealID
 			deal.PublishCid = &res.FinalCid
 			deal.State = storagemarket.StorageDealStaged
-			err = storageDealPorcess.deals.SaveDeal(ctx, deal)
+			err = storageDealPorcess.saveDealState(ctx, deal)
 			if err != nil {
 				return storageDealPorcess.HandleError(ctx, deal, fmt.Errorf("fail to save deal to database"))
 			}
@@ -456,7 +464,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleOff(ctx context.Context,
 		log.Infow("successfully handed off deal to sealing subsystem", "pieceCid", deal.Proposal.PieceCID, "proposalCid", deal.ProposalCid)
 		deal.AvailableForRetrieval = true
 		deal.State = storagemarket.StorageDealAwaitingPreCommit
-		if err := storageDealPorcess.deals.SaveDeal(ctx, deal); err != nil {
+		if err := storageDealPorcess.saveDealState(ctx, deal); err != nil {
 			return storageDealPorcess.HandleError(ctx, deal, fmt.Errorf("fail to save deal to database"))
 		}
 	}
@@ -483,6 +491,9 @@ func (storageDealPorcess *StorageDealProcessImpl) savePieceFile(ctx context.Cont
 		}
 		log.Infof("success to write file %s to piece storage", pieceCid)
 	}
+
+	// replicate in background so that a slow replica doesn't block the deal
+	storageDealPorcess.repairWorker.Queue(pieceCid.String())
 	return nil
 }
 
@@ -539,7 +550,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleReject(ctx context.Conte
 
 	storageDealPorcess.peerTagger.UntagPeer(deal.Client, deal.ProposalCid.String())
 
-	return storageDealPorcess.deals.SaveDeal(ctx, deal)
+	return storageDealPorcess.saveDealState(ctx, deal)
 }
 
 func (storageDealPorcess *StorageDealProcessImpl) HandleError(ctx context.Context, deal *types.MinerDeal, err error) error {
@@ -575,7 +586,7 @@ func (storageDealPorcess *StorageDealProcessImpl) HandleError(ctx context.Contex
 
 	storageDealPorcess.releaseReservedFunds(context.TODO(), deal)
 
-	return storageDealPorcess.deals.SaveDeal(ctx, deal)
+	return storageDealPorcess.saveDealState(ctx, deal)
 }
 
 func (storageDealPorcess *StorageDealProcessImpl) releaseReservedFunds(ctx context.Context, deal *types.MinerDeal) {
@@ -592,7 +603,32 @@ func (storageDealPorcess *StorageDealProcessImpl) releaseReservedFunds(ctx conte
 
 func (storageDealPorcess *StorageDealProcessImpl) SaveState(ctx context.Context, deal *types.MinerDeal, event storagemarket.StorageDealStatus) error {
 	deal.State = event
-	return storageDealPorcess.deals.SaveDeal(ctx, deal)
+	return storageDealPorcess.saveDealState(ctx, deal)
+}
+
+// saveDealState save deal, record the transition and notify webhooks only when the state of deal changed
+func (storageDealPorcess *StorageDealProcessImpl) saveDealState(ctx context.Context, deal *types.MinerDeal) error {
+	// failing to read the old deal should not fail the save, take the state as changed then
+	stateChanged := true
+	if old, err := storageDealPorcess.deals.GetDeal(ctx, deal.ProposalCid); err == nil {
+		stateChanged = old.State != deal.State
+	} else if !errors.Is(err, repo.ErrNotFound) {
+		log.Warnf("get deal %s before saving: %v", deal.ProposalCid, err)
+	}
+
+	if err := storageDealPorcess.deals.SaveDeal(ctx, deal); err != nil {
+		return err
+	}
+	if !stateChanged {
+		return nil
+	}
+
+	metrics.Record(ctx, []tag.Mutator{
+		tag.Upsert(metrics.Miner, deal.Proposal.Provider.String()),
+		tag.Upsert(metrics.DealState, storagemarket.DealStates[deal.State]),
+	}, metrics.StorageDealStateTransition.M(1))
+	storageDealPorcess.notifier.StorageDealStateChanged(ctx, deal)
+	return nil
 }
 
 func (storageDealPorcess *StorageDealProcessImpl) ReadCAR(path string) (*carv2.Reader, error) {
@@ -650,33 +686,13 @@ func (storageDealPorcess *StorageDealProcessImpl) GeneratePieceCommitment(propos
 	}()
 
 	// dump the CARv1 payload of the CARv2 file to the Commp Writer and get back the CommP.
-	w := &writer.Writer{}
-	written, err := io.Copy(w, rd.DataReader())
+	pieceCid, written, err := utils.GenerateCommPFromReader(rd.DataReader(), dealSize)
 	if err != nil {
-		return cid.Undef, "", fmt.Errorf("failed to write to CommP writer: %w", err)
+		return cid.Undef, "", err
 	}
 	if written != int64(rd.Header.DataSize) {
 		return cid.Undef, "", fmt.Errorf("number of bytes written to CommP writer %d not equal to the CARv1 payload size %d", written, rd.Header.DataSize)
 	}
 
-	cidAndSize, err := w.Sum()
-	if err != nil {
-		return cid.Undef, "", fmt.Errorf("failed to get CommP: %w", err)
-	}
-
-	if cidAndSize.PieceSize < dealSize {
-		// need to pad up!
-		rawPaddedCommp, err := commp.PadCommP(
-			// we know how long a pieceCid "hash" is, just blindly extract the trailing 32 bytes
-			cidAndSize.PieceCID.Hash()[len(cidAndSize.PieceCID.Hash())-32:],
-			uint64(cidAndSize.PieceSize),
-			uint64(dealSize),
-		)
-		if err != nil {
-			return cid.Undef, "", err
-		}
-		cidAndSize.PieceCID, _ = commcid.DataCommitmentV1ToCID(rawPaddedCommp)
-	}
-
-	return cidAndSize.PieceCID, filestore.Path(""), err
+	return pieceCid, filestore.Path(""), nil
 }
@@ -3,37 +3,72 @@ package storageprovider
 import (
 	"context"
 	"errors"
+	"fmt"
 	"strings"
 	"time"
 
 	"github.com/filecoin-project/go-address"
 	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/ipfs/go-cid"
 	"go.uber.org/fx"
 
 	"github.com/filecoin-project/venus-market/v2/minermgr"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/notify"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	"github.com/filecoin-project/venus/pkg/events/state"
+	"github.com/filecoin-project/venus/venus-shared/actors/builtin/market"
 	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
 
 	vTypes "github.com/filecoin-project/venus/venus-shared/types"
-	"github.com/filecoin-project/venus/venus-shared/types/market"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
 
 	"github.com/ipfs-force-community/venus-common-utils/metrics"
 )
 
+// dealScanInterval is how many epochs the deals past their start or end epoch are looked up in the repo
+const dealScanInterval = abi.ChainEpoch(10)
+
+// DealTracker follows the head changes of chain, and updates the deals whose state in market actor or whose
+// precommit changed between the last handled tipset and the new head. Deals are fully scanned when the tracker
+// starts following the chain, or when the diff fails, at most once per retryDelay. Since the diff is computed between
+// two tipsets instead of replaying each head change, the state reverted by a reorg is diffed as any other change.
 type DealTracker struct {
-	period      time.Duration // TODO: Preferably configurable?
+	retryDelay  time.Duration
 	storageRepo repo.StorageDealRepo
 	minerMgr    minermgr.IAddrMgr
 	fullNode    v1api.FullNode
+	notifier    *notify.WebhookNotifier
+	spn         StorageProviderNode
+	dsc         dealStateChangesAPI
+	dpc         diffPreCommitsAPI
+
+	lastTs       *vTypes.TipSet
+	lastScan     abi.ChainEpoch
+	lastFallback time.Time
+	// dealIndex maps the ids of tracked deals to their proposals, it is rebuilt whenever all tracked deals are read
+	dealIndex map[abi.DealID]cid.Cid
 }
 
 var ReadyRetrievalDealStatus = []storagemarket.StorageDealStatus{storagemarket.StorageDealAwaitingPreCommit, storagemarket.StorageDealSealing, storagemarket.StorageDealActive}
 
-func NewDealTracker(lc fx.Lifecycle, r repo.Repo, minerMgr minermgr.IAddrMgr, fullNode v1api.FullNode) *DealTracker {
-	tracker := &DealTracker{period: time.Minute, storageRepo: r.StorageDealRepo(), minerMgr: minerMgr, fullNode: fullNode}
+func NewDealTracker(mctx metrics.MetricsCtx, lc fx.Lifecycle, r repo.Repo, minerMgr minermgr.IAddrMgr, fullNode v1api.FullNode, spn StorageProviderNode, notifier *notify.WebhookNotifier) *DealTracker {
+	tracker := &DealTracker{
+		retryDelay:  time.Minute,
+		storageRepo: r.StorageDealRepo(),
+		minerMgr:    minerMgr,
+		fullNode:    fullNode,
+		notifier:    notifier,
+		spn:         spn,
+		dsc:         &predsDealStateChanges{preds: state.NewStatePredicates(state.WrapFastAPI(fullNode))},
+		dpc:         &apiWrapper{api: fullNode},
+	}
 
+	ctx := metrics.LifecycleCtx(mctx, lc)
 	lc.Append(fx.Hook{
-		OnStart: func(ctx context.Context) error {
+		OnStart: func(context.Context) error {
 			go tracker.Start(ctx)
 			return nil
 		},
@@ -42,12 +77,12 @@ func NewDealTracker(lc fx.Lifecycle, r repo.Repo, minerMgr minermgr.IAddrMgr, fu
 }
 
 func (dealTracker *DealTracker) Start(ctx metrics.MetricsCtx) {
-	dealTracker.scanDeal(ctx)
-	ticker := time.NewTicker(dealTracker.period)
 	for {
+		if err := dealTracker.follow(ctx); err != nil {
+			log.Warnf("follow chain head for tracking deals: %v", err)
+		}
 		select {
-		case <-ticker.C:
-			dealTracker.scanDeal(ctx)
+		case <-time.After(dealTracker.retryDelay):
 		case <-ctx.Done():
 			log.Warnf("exit deal tracker by context")
 			return
@@ -55,24 +90,492 @@ func (dealTracker *DealTracker) Start(ctx metrics.MetricsCtx) {
 	}
 }
 
-func (dealTracker *DealTracker) scanDeal(ctx metrics.MetricsCtx) {
+// follow handle head changes until the notification channel is closed
+func (dealTracker *DealTracker) follow(ctx metrics.MetricsCtx) error {
+	notifs, err := dealTracker.fullNode.ChainNotify(ctx)
+	if err != nil {
+		return fmt.Errorf("subscribe head changes: %w", err)
+	}
+	// head changes may be missed before subscribing, scan all deals on the first head
+	dealTracker.lastTs = nil
+
+	for {
+		select {
+		case changes, ok := <-notifs:
+			if !ok {
+				return fmt.Errorf("head change channel closed")
+			}
+			if head := appliedHead(changes); head != nil {
+				dealTracker.onHead(ctx, head)
+			}
+		case <-ctx.Done():
+			return nil
+		}
+	}
+}
+
+// appliedHead returns the head after the changes, nil if the changes only revert tipsets
+func appliedHead(changes []*vTypes.HeadChange) *vTypes.TipSet {
+	var head *vTypes.TipSet
+	for _, change := range changes {
+		if change.Type != vTypes.HCRevert {
+			head = change.Val
+		}
+	}
+	return head
+}
+
+func (dealTracker *DealTracker) onHead(ctx metrics.MetricsCtx, head *vTypes.TipSet) {
+	if dealTracker.lastTs == nil {
+		dealTracker.scanDeal(ctx, head)
+		dealTracker.lastTs = head
+		dealTracker.lastScan = head.Height()
+		return
+	}
+	if dealTracker.lastTs.Key().Equals(head.Key()) {
+		return
+	}
+
+	if err := dealTracker.diffDeals(ctx, dealTracker.lastTs, head); err != nil {
+		// keep lastTs until the fallback runs, so that the changes are diffed again on the next head
+		if time.Since(dealTracker.lastFallback) < dealTracker.retryDelay {
+			log.Warnf("diff deals from %d to %d: %v", dealTracker.lastTs.Height(), head.Height(), err)
+			return
+		}
+		log.Warnf("diff deals from %d to %d, fall back to full scan: %v", dealTracker.lastTs.Height(), head.Height(), err)
+		dealTracker.scanDeal(ctx, head)
+		dealTracker.lastScan = head.Height()
+		dealTracker.lastFallback = time.Now()
+	}
+	dealTracker.lastTs = head
+}
+
+// scanDeal check every deal against the state of head
+func (dealTracker *DealTracker) scanDeal(ctx metrics.MetricsCtx, head *vTypes.TipSet) {
 	addrs, err := dealTracker.minerMgr.ActorAddress(ctx)
 	if err != nil {
 		log.Errorf("get miners list %w", err)
 	}
-	head, err := dealTracker.fullNode.ChainHead(ctx)
+
+	for _, addr := range addrs {
+		deals, err := dealTracker.storageRepo.GetDealByAddrAndStatus(ctx, addr, storagemarket.StorageDealActive)
+		if err != nil && !errors.Is(err, repo.ErrNotFound) {
+			log.Errorf("get miner %s storage deals for check expire %w", addr, err)
+		}
+		dealTracker.checkExpire(ctx, deals, head.Height())
+		dealTracker.checkSlash(ctx, addr, head.Key())
+		dealTracker.checkPreCommitAndCommit(ctx, addr, head)
+	}
+}
+
+// diffDeals only check the deals changed in market actor between pre and cur, and the deals whose precommits
+// changed. The changed deals are loaded through dealIndex, and all tracked deals are only read every dealScanInterval
+// epochs to find the deals past their start or end epoch, which are not reflected in the state of market actor until
+// its cron runs, or when a deal not indexed yet is precommitted.
+func (dealTracker *DealTracker) diffDeals(ctx metrics.MetricsCtx, pre, cur *vTypes.TipSet) error {
+	changed, err := dealTracker.dsc.changedDealIDs(ctx, pre.Key(), cur.Key())
+	if err != nil {
+		return err
+	}
+	addrs, err := dealTracker.minerMgr.ActorAddress(ctx)
+	if err != nil {
+		return fmt.Errorf("get miners list: %w", err)
+	}
+	preCommits, err := dealTracker.preCommitChanges(ctx, addrs, pre, cur)
+	if err != nil {
+		return err
+	}
+
+	// a reorg to a lower height also triggers the scan, so that the throttle is not stuck until the old height
+	scan := cur.Height() < dealTracker.lastScan || cur.Height()-dealTracker.lastScan >= dealScanInterval
+	if len(changed) == 0 && preCommits.empty() && !scan {
+		return nil
+	}
+
+	var deals []*types.MinerDeal
+	if scan || dealTracker.dealIndex == nil || !dealTracker.indexed(preCommits) {
+		deals, err = dealTracker.trackedDeals(ctx, addrs)
+	} else {
+		deals, err = dealTracker.indexedDeals(ctx, changed, preCommits)
+	}
 	if err != nil {
-		log.Errorf("get chain head %w", err)
+		return err
+	}
+	if len(changed) > 0 {
+		if err := dealTracker.checkChangedDeals(ctx, deals, cur, changed); err != nil {
+			return err
+		}
+	}
+	dealTracker.checkPreCommitChanges(ctx, deals, cur, preCommits)
+	if scan {
+		dealTracker.checkExpire(ctx, deals, cur.Height())
+		if err := dealTracker.checkStartEpochPassed(ctx, deals, cur); err != nil {
+			return err
+		}
+		dealTracker.lastScan = cur.Height()
+	}
+	return nil
+}
+
+// trackedDealStates are the states of deals followed on chain
+var trackedDealStates = []storagemarket.StorageDealStatus{storagemarket.StorageDealAwaitingPreCommit,
+	storagemarket.StorageDealSealing, storagemarket.StorageDealActive, storagemarket.StorageDealSlashed}
+
+func isTrackedDealState(state storagemarket.StorageDealStatus) bool {
+	for _, tracked := range trackedDealStates {
+		if state == tracked {
+			return true
+		}
 	}
+	return false
+}
 
+// trackedDeals returns the deals of miners whose states are followed on chain, with one read of the repo for all
+// miners, and rebuilds dealIndex from them
+func (dealTracker *DealTracker) trackedDeals(ctx context.Context, addrs []address.Address) ([]*types.MinerDeal, error) {
+	deals, err := dealTracker.storageRepo.GetDealByAddrAndStatus(ctx, address.Undef, trackedDealStates...)
+	if err != nil && !errors.Is(err, repo.ErrNotFound) {
+		return nil, fmt.Errorf("get storage deals: %w", err)
+	}
+
+	miners := make(map[address.Address]struct{}, len(addrs))
 	for _, addr := range addrs {
-		dealTracker.checkSlash(ctx, addr, head.Key())
-		dealTracker.checkPreCommitAndCommit(ctx, addr, head.Key())
-		//todo check expire
+		miners[addr] = struct{}{}
+	}
+	tracked := deals[:0]
+	index := make(map[abi.DealID]cid.Cid, len(deals))
+	for _, deal := range deals {
+		if _, ok := miners[deal.ClientDealProposal.Proposal.Provider]; ok {
+			tracked = append(tracked, deal)
+			if deal.DealID != 0 {
+				index[deal.DealID] = deal.ProposalCid
+			}
+		}
+	}
+	dealTracker.dealIndex = index
+	return tracked, nil
+}
+
+// indexed returns whether all deals precommitted are in dealIndex. Deals in the changes of market actor are not
+// checked since most of them belong to other miners, and the deals of this market are indexed long before their
+// activation as they must be precommitted first.
+func (dealTracker *DealTracker) indexed(preCommits *dealPreCommitChanges) bool {
+	for dealID := range preCommits.added {
+		if _, ok := dealTracker.dealIndex[dealID]; !ok {
+			return false
+		}
+	}
+	return true
+}
+
+// indexedDeals loads the tracked deals among the changed ones through dealIndex, deals left the tracked states are
+// removed from the index
+func (dealTracker *DealTracker) indexedDeals(ctx context.Context, changed map[abi.DealID]struct{}, preCommits *dealPreCommitChanges) ([]*types.MinerDeal, error) {
+	proposals := make(map[abi.DealID]cid.Cid)
+	collect := func(dealID abi.DealID) {
+		if proposalCid, ok := dealTracker.dealIndex[dealID]; ok {
+			proposals[dealID] = proposalCid
+		}
+	}
+	for dealID := range changed {
+		collect(dealID)
+	}
+	for dealID := range preCommits.added {
+		collect(dealID)
+	}
+	for dealID := range preCommits.removed {
+		collect(dealID)
+	}
+
+	deals := make([]*types.MinerDeal, 0, len(proposals))
+	for dealID, proposalCid := range proposals {
+		deal, err := dealTracker.storageRepo.GetDeal(ctx, proposalCid)
+		if err != nil && !errors.Is(err, repo.ErrNotFound) {
+			return nil, fmt.Errorf("get storage deal %s: %w", proposalCid, err)
+		}
+		if err != nil || !isTrackedDealState(deal.State) {
+			delete(dealTracker.dealIndex, dealID)
+			continue
+		}
+		deals = append(deals, deal)
+	}
+	return deals, nil
+}
+
+type dealStateChangesAPI interface {
+	changedDealIDs(ctx context.Context, pre, cur vTypes.TipSetKey) (map[abi.DealID]struct{}, error)
+}
+
+type predsDealStateChanges struct {
+	preds *state.StatePredicates
+}
+
+// changedDealIDs returns the deals whose state is added, modified or removed, or whose proposal is removed
+func (p *predsDealStateChanges) changedDealIDs(ctx context.Context, pre, cur vTypes.TipSetKey) (map[abi.DealID]struct{}, error) {
+	ids := make(map[abi.DealID]struct{})
+
+	diffStates := p.preds.OnStorageMarketActorChanged(p.preds.OnDealStateChanged(p.preds.OnDealStateAmtChanged()))
+	changed, user, err := diffStates(ctx, pre, cur)
+	if err != nil {
+		return nil, fmt.Errorf("diff deal states: %w", err)
+	}
+	if changed {
+		changes := user.(*market.DealStateChanges)
+		for _, added := range changes.Added {
+			ids[added.ID] = struct{}{}
+		}
+		for _, modified := range changes.Modified {
+			ids[modified.ID] = struct{}{}
+		}
+		for _, removed := range changes.Removed {
+			ids[removed.ID] = struct{}{}
+		}
+	}
+
+	diffProposals := p.preds.OnStorageMarketActorChanged(p.preds.OnDealProposalChanged(p.preds.OnDealProposalAmtChanged()))
+	changed, user, err = diffProposals(ctx, pre, cur)
+	if err != nil {
+		return nil, fmt.Errorf("diff deal proposals: %w", err)
+	}
+	if changed {
+		for _, removed := range user.(*market.DealProposalChanges).Removed {
+			ids[removed.ID] = struct{}{}
+		}
+	}
+	return ids, nil
+}
+
+// checkChangedDeals update the tracked deals which are changed, with the state of market actor at ts
+func (dealTracker *DealTracker) checkChangedDeals(ctx metrics.MetricsCtx, deals []*types.MinerDeal, ts *vTypes.TipSet, changed map[abi.DealID]struct{}) error {
+	for _, deal := range deals {
+		if _, ok := changed[deal.DealID]; !ok || deal.DealID == 0 {
+			continue
+		}
+		var onChain *vTypes.MarketDeal
+		md, err := dealTracker.fullNode.StateMarketStorageDeal(ctx, deal.DealID, ts.Key())
+		if err == nil {
+			onChain = md
+		} else if !strings.Contains(err.Error(), "not found") {
+			return fmt.Errorf("get market deal %d of miner %s: %w", deal.DealID, deal.ClientDealProposal.Proposal.Provider, err)
+		}
+
+		if reason := unactivatedDealFailure(deal, onChain, ts.Height()); len(reason) > 0 {
+			dealTracker.failDeal(ctx, deal, reason)
+			continue
+		}
+		status, ok := trackedDealStatus(deal, onChain, ts.Height())
+		if !ok {
+			continue
+		}
+		dealTracker.updateDealStatus(ctx, deal, status, onChain, ts.Height())
+	}
+	return nil
+}
+
+// trackedDealStatus returns the status a deal should be in according to its state in market actor, which is nil if
+// the deal no longer exists, and whether the status is different from the current one. States are moved backward
+// when a reorg reverts the activation or the slash of deals.
+func trackedDealStatus(deal *types.MinerDeal, onChain *vTypes.MarketDeal, height abi.ChainEpoch) (storagemarket.StorageDealStatus, bool) {
+	status := deal.State
+	switch {
+	case onChain == nil:
+		// the deal is removed by cron after it ends or its sector is terminated
+		if deal.State == storagemarket.StorageDealActive {
+			if height > deal.ClientDealProposal.Proposal.EndEpoch {
+				status = storagemarket.StorageDealExpired
+			} else {
+				status = storagemarket.StorageDealSlashed
+			}
+		}
+	case onChain.State.SlashEpoch > -1:
+		status = storagemarket.StorageDealSlashed
+	case onChain.State.SectorStartEpoch > -1:
+		status = storagemarket.StorageDealActive
+	case deal.State == storagemarket.StorageDealActive || deal.State == storagemarket.StorageDealSlashed:
+		status = storagemarket.StorageDealSealing
+	}
+	return status, status != deal.State
+}
+
+func (dealTracker *DealTracker) updateDealStatus(ctx metrics.MetricsCtx, deal *types.MinerDeal, status storagemarket.StorageDealStatus, onChain *vTypes.MarketDeal, height abi.ChainEpoch) {
+	var pieceStatus types.PieceStatus
+	switch status {
+	case storagemarket.StorageDealActive:
+		pieceStatus = types.Proving
+	case storagemarket.StorageDealSealing:
+		pieceStatus = types.Packing
+	}
+
+	oldState := deal.State
+	var err error
+	if status == storagemarket.StorageDealSlashed {
+		// the slash epoch is kept for piece gc to count the grace period from
+		deal.SlashEpoch = height
+		if onChain != nil {
+			deal.SlashEpoch = onChain.State.SlashEpoch
+		}
+		deal.State = status
+		err = dealTracker.storageRepo.SaveDeal(ctx, deal)
+	} else {
+		err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, status, pieceStatus)
+	}
+	if err != nil {
+		log.Errorf("update deal %d of miner %s from %s to %s %v", deal.DealID, deal.ClientDealProposal.Proposal.Provider,
+			storagemarket.DealStates[oldState], storagemarket.DealStates[status], err)
+		return
+	}
+	log.Infof("deal %d of miner %s moved from %s to %s", deal.DealID, deal.ClientDealProposal.Proposal.Provider,
+		storagemarket.DealStates[oldState], storagemarket.DealStates[status])
+
+	deal.State = status
+	dealTracker.notifier.StorageDealStateChanged(ctx, deal)
+	if status == storagemarket.StorageDealSlashed {
+		dealTracker.notifier.StorageDealSlashed(ctx, deal, deal.SlashEpoch)
+	}
+}
+
+// checkStartEpochPassed fail the deals which are not activated before their start epoch, without waiting for the
+// cron of market actor to remove them
+func (dealTracker *DealTracker) checkStartEpochPassed(ctx metrics.MetricsCtx, deals []*types.MinerDeal, ts *vTypes.TipSet) error {
+	for _, deal := range deals {
+		if deal.State != storagemarket.StorageDealAwaitingPreCommit && deal.State != storagemarket.StorageDealSealing {
+			continue
+		}
+		if deal.DealID == 0 || ts.Height() <= deal.ClientDealProposal.Proposal.StartEpoch {
+			continue
+		}
+		var onChain *vTypes.MarketDeal
+		md, err := dealTracker.fullNode.StateMarketStorageDeal(ctx, deal.DealID, ts.Key())
+		if err == nil {
+			onChain = md
+		} else if !strings.Contains(err.Error(), "not found") {
+			return fmt.Errorf("get market deal %d of miner %s: %w", deal.DealID, deal.ClientDealProposal.Proposal.Provider, err)
+		}
+
+		if reason := unactivatedDealFailure(deal, onChain, ts.Height()); len(reason) > 0 {
+			dealTracker.failDeal(ctx, deal, reason)
+		} else if status, ok := trackedDealStatus(deal, onChain, ts.Height()); ok {
+			dealTracker.updateDealStatus(ctx, deal, status, onChain, ts.Height())
+		}
+	}
+	return nil
+}
+
+// unactivatedDealFailure returns why a deal waiting for being sealed has failed, which is empty if the deal can
+// still be activated. A deal fails if it is removed from market actor, which happens when the publish message is
+// reverted or the deal times out, or if its start epoch has passed without activation.
+func unactivatedDealFailure(deal *types.MinerDeal, onChain *vTypes.MarketDeal, height abi.ChainEpoch) string {
+	if deal.State != storagemarket.StorageDealAwaitingPreCommit && deal.State != storagemarket.StorageDealSealing {
+		return ""
+	}
+	if onChain == nil {
+		return fmt.Sprintf("deal %d not found on chain", deal.DealID)
+	}
+	if onChain.State.SectorStartEpoch < 0 && height > deal.ClientDealProposal.Proposal.StartEpoch {
+		return fmt.Sprintf("deal %d not activated before start epoch %d", deal.DealID, deal.ClientDealProposal.Proposal.StartEpoch)
+	}
+	return ""
+}
+
+// failDeal move a deal which never gets sealed to a terminal state, release its reserved funds, and mark its piece as
+// failed so that it is no longer assigned to sectors
+func (dealTracker *DealTracker) failDeal(ctx metrics.MetricsCtx, deal *types.MinerDeal, reason string) {
+	if !deal.FundsReserved.Nil() && !deal.FundsReserved.IsZero() {
+		if err := dealTracker.spn.ReleaseFunds(ctx, deal.ClientDealProposal.Proposal.Provider, deal.FundsReserved); err != nil {
+			// nonfatal error
+			log.Warnf("failed to release funds of deal %d: %s", deal.DealID, err)
+		}
+		deal.FundsReserved = big.Zero()
+	}
+
+	deal.State = storagemarket.StorageDealError
+	deal.PieceStatus = types2.PieceFailed
+	deal.Message = reason
+	if err := dealTracker.storageRepo.SaveDeal(ctx, deal); err != nil {
+		log.Errorf("save failed deal %d of miner %s %v", deal.DealID, deal.ClientDealProposal.Proposal.Provider, err)
+		return
+	}
+	log.Warnf("deal %d of miner %s failed: %s", deal.DealID, deal.ClientDealProposal.Proposal.Provider, reason)
+	dealTracker.notifier.StorageDealStateChanged(ctx, deal)
+}
+
+// dealPreCommitChanges are the deals whose precommits are added or removed, added deals are mapped to the sectors
+// precommitted
+type dealPreCommitChanges struct {
+	added   map[abi.DealID]abi.SectorNumber
+	removed map[abi.DealID]struct{}
+}
+
+func (changes *dealPreCommitChanges) empty() bool {
+	return len(changes.added) == 0 && len(changes.removed) == 0
+}
+
+// preCommitChanges collects the deals in precommits of miners which are added or removed between pre and cur
+func (dealTracker *DealTracker) preCommitChanges(ctx context.Context, addrs []address.Address, pre, cur *vTypes.TipSet) (*dealPreCommitChanges, error) {
+	changes := &dealPreCommitChanges{
+		added:   make(map[abi.DealID]abi.SectorNumber),
+		removed: make(map[abi.DealID]struct{}),
+	}
+	for _, addr := range addrs {
+		diff, err := dealTracker.dpc.diffPreCommits(ctx, addr, pre.Key(), cur.Key())
+		if err != nil {
+			return nil, fmt.Errorf("diff precommits of miner %s: %w", addr, err)
+		}
+		for _, info := range diff.Added {
+			for _, dealID := range info.Info.DealIDs {
+				changes.added[dealID] = info.Info.SectorNumber
+			}
+		}
+		for _, info := range diff.Removed {
+			for _, dealID := range info.Info.DealIDs {
+				changes.removed[dealID] = struct{}{}
+			}
+		}
+	}
+	return changes, nil
+}
+
+// checkPreCommitChanges move deals waiting for precommit to sealing if their sectors are precommitted, and move
+// sealing deals back if the precommits are removed without activating the deals
+func (dealTracker *DealTracker) checkPreCommitChanges(ctx metrics.MetricsCtx, deals []*types.MinerDeal, cur *vTypes.TipSet, changes *dealPreCommitChanges) {
+	if changes.empty() {
+		return
+	}
+	for _, deal := range deals {
+		switch deal.State {
+		case storagemarket.StorageDealAwaitingPreCommit:
+			if sector, ok := changes.added[deal.DealID]; ok && sector == deal.SectorNumber {
+				dealTracker.updateDealStatus(ctx, deal, storagemarket.StorageDealSealing, nil, cur.Height())
+			}
+		case storagemarket.StorageDealSealing:
+			// deals activated by prove commit are already moved to active by the diff of deal states
+			if _, ok := changes.removed[deal.DealID]; ok {
+				if _, ok := changes.added[deal.DealID]; !ok {
+					dealTracker.updateDealStatus(ctx, deal, storagemarket.StorageDealAwaitingPreCommit, nil, cur.Height())
+				}
+			}
+		}
 	}
 }
 
-func (dealTracker *DealTracker) checkPreCommitAndCommit(ctx metrics.MetricsCtx, addr address.Address, tsk vTypes.TipSetKey) {
+// checkExpire moves active deals past their end epoch to StorageDealExpired
+func (dealTracker *DealTracker) checkExpire(ctx metrics.MetricsCtx, deals []*types.MinerDeal, height abi.ChainEpoch) {
+	for _, deal := range deals {
+		if deal.State != storagemarket.StorageDealActive || height <= deal.ClientDealProposal.Proposal.EndEpoch {
+			continue
+		}
+		err := dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealExpired, "")
+		if err != nil {
+			log.Errorf("update deal status to expired for deal %d of miner %s %w", deal.DealID, deal.ClientDealProposal.Proposal.Provider, err)
+		} else {
+			deal.State = storagemarket.StorageDealExpired
+			dealTracker.notifier.StorageDealStateChanged(ctx, deal)
+		}
+	}
+}
+
+func (dealTracker *DealTracker) checkPreCommitAndCommit(ctx metrics.MetricsCtx, addr address.Address, head *vTypes.TipSet) {
+	tsk := head.Key()
 	deals, err := dealTracker.storageRepo.GetDealByAddrAndStatus(ctx, addr, storagemarket.StorageDealAwaitingPreCommit, storagemarket.StorageDealSealing)
 	if err != nil && !errors.Is(err, repo.ErrNotFound) {
 		log.Errorf("get miner %s storage deals for check StorageDealAwaitingPreCommit %w", addr, err)
@@ -81,17 +584,27 @@ func (dealTracker *DealTracker) checkPreCommitAndCommit(ctx metrics.MetricsCtx,
 	for _, deal := range deals {
 		dealProposal, err := dealTracker.fullNode.StateMarketStorageDeal(ctx, deal.DealID, tsk)
 		if err != nil {
-			//todo if deal not found maybe need to market storage deal as error
+			if strings.Contains(err.Error(), "not found") {
+				dealTracker.failDeal(ctx, deal, unactivatedDealFailure(deal, nil, head.Height()))
+				continue
+			}
 			log.Errorf("get market deal for sector %d of miner %s %w", deal.SectorNumber, addr, err)
 			continue
 		}
 		if dealProposal.State.SectorStartEpoch > -1 { //include in sector
-			err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealActive, market.Proving)
+			err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealActive, types.Proving)
 			if err != nil {
 				log.Errorf("update deal status to active for sector %d of miner %s %w", deal.SectorNumber, addr, err)
+			} else {
+				deal.State = storagemarket.StorageDealActive
+				dealTracker.notifier.StorageDealStateChanged(ctx, deal)
 			}
 			continue
 		}
+		if reason := unactivatedDealFailure(deal, dealProposal, head.Height()); len(reason) > 0 {
+			dealTracker.failDeal(ctx, deal, reason)
+			continue
+		}
 
 		if deal.State == storagemarket.StorageDealAwaitingPreCommit {
 			preInfo, err := dealTracker.fullNode.StateSectorPreCommitInfo(ctx, addr, deal.SectorNumber, tsk)
@@ -115,9 +628,12 @@ func (dealTracker *DealTracker) checkPreCommitAndCommit(ctx metrics.MetricsCtx,
 				continue
 			}
 
-			err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealSealing, market.Packing)
+			err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealSealing, types.Packing)
 			if err != nil {
 				log.Errorf("update deal status to sealing for sector %d of miner %s %w", deal.SectorNumber, addr, err)
+			} else {
+				deal.State = storagemarket.StorageDealSealing
+				dealTracker.notifier.StorageDealStateChanged(ctx, deal)
 			}
 		}
 
@@ -138,9 +654,14 @@ func (dealTracker *DealTracker) checkSlash(ctx metrics.MetricsCtx, addr address.
 			continue
 		}
 		if dealProposal.State.SlashEpoch > -1 { //include in sector
-			err = dealTracker.storageRepo.UpdateDealStatus(ctx, deal.ProposalCid, storagemarket.StorageDealSlashed, "")
+			deal.State = storagemarket.StorageDealSlashed
+			deal.SlashEpoch = dealProposal.State.SlashEpoch
+			err = dealTracker.storageRepo.SaveDeal(ctx, deal)
 			if err != nil {
 				log.Errorf("update deal status to slash for sector %d of miner %s %w", deal.SectorNumber, addr, err)
+			} else {
+				dealTracker.notifier.StorageDealStateChanged(ctx, deal)
+				dealTracker.notifier.StorageDealSlashed(ctx, deal, dealProposal.State.SlashEpoch)
 			}
 		}
 	}
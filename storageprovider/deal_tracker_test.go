@@ -0,0 +1,335 @@
+package storageprovider
+
+import (
+	"context"
+	"fmt"
+	"testing"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/ipfs/go-datastore"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/models/badger"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/utils/test_helper"
+	"github.com/filecoin-project/venus/pkg/testhelpers"
+	lminer "github.com/filecoin-project/venus/venus-shared/actors/builtin/miner"
+	"github.com/filecoin-project/venus/venus-shared/api/chain/v1/mock"
+	vTypes "github.com/filecoin-project/venus/venus-shared/types"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+)
+
+func TestTrackedDealStatus(t *testing.T) {
+	deal := func(state storagemarket.StorageDealStatus) *types.MinerDeal {
+		d := &types.MinerDeal{State: state}
+		d.ClientDealProposal.Proposal.EndEpoch = 1000
+		return d
+	}
+	onChain := func(start, slash abi.ChainEpoch) *vTypes.MarketDeal {
+		md := &vTypes.MarketDeal{}
+		md.State.SectorStartEpoch = start
+		md.State.SlashEpoch = slash
+		return md
+	}
+
+	cases := []struct {
+		name    string
+		deal    *types.MinerDeal
+		onChain *vTypes.MarketDeal
+		height  abi.ChainEpoch
+		expect  storagemarket.StorageDealStatus
+		changed bool
+	}{
+		{"activated", deal(storagemarket.StorageDealSealing), onChain(10, -1), 20, storagemarket.StorageDealActive, true},
+		{"activated before precommit seen", deal(storagemarket.StorageDealAwaitingPreCommit), onChain(10, -1), 20, storagemarket.StorageDealActive, true},
+		{"still active", deal(storagemarket.StorageDealActive), onChain(10, -1), 20, storagemarket.StorageDealActive, false},
+		{"slashed", deal(storagemarket.StorageDealActive), onChain(10, 30), 40, storagemarket.StorageDealSlashed, true},
+		{"terminated and removed", deal(storagemarket.StorageDealActive), nil, 40, storagemarket.StorageDealSlashed, true},
+		{"ended and removed", deal(storagemarket.StorageDealActive), nil, 1001, storagemarket.StorageDealExpired, true},
+		{"activation reverted", deal(storagemarket.StorageDealActive), onChain(-1, -1), 20, storagemarket.StorageDealSealing, true},
+		{"slash reverted", deal(storagemarket.StorageDealSlashed), onChain(10, -1), 40, storagemarket.StorageDealActive, true},
+		{"not activated", deal(storagemarket.StorageDealSealing), onChain(-1, -1), 20, storagemarket.StorageDealSealing, false},
+		{"not found before activated", deal(storagemarket.StorageDealSealing), nil, 20, storagemarket.StorageDealSealing, false},
+	}
+	for _, c := range cases {
+		status, changed := trackedDealStatus(c.deal, c.onChain, c.height)
+		assert.Equal(t, storagemarket.DealStates[c.expect], storagemarket.DealStates[status], c.name)
+		assert.Equal(t, c.changed, changed, c.name)
+	}
+}
+
+func TestUnactivatedDealFailure(t *testing.T) {
+	deal := func(state storagemarket.StorageDealStatus) *types.MinerDeal {
+		d := &types.MinerDeal{State: state, DealID: 1}
+		d.ClientDealProposal.Proposal.StartEpoch = 100
+		return d
+	}
+	onChain := func(start abi.ChainEpoch) *vTypes.MarketDeal {
+		md := &vTypes.MarketDeal{}
+		md.State.SectorStartEpoch = start
+		md.State.SlashEpoch = -1
+		return md
+	}
+
+	assert.Empty(t, unactivatedDealFailure(deal(storagemarket.StorageDealAwaitingPreCommit), onChain(-1), 100))
+	assert.Empty(t, unactivatedDealFailure(deal(storagemarket.StorageDealSealing), onChain(90), 120))
+	assert.NotEmpty(t, unactivatedDealFailure(deal(storagemarket.StorageDealAwaitingPreCommit), onChain(-1), 101))
+	assert.NotEmpty(t, unactivatedDealFailure(deal(storagemarket.StorageDealSealing), nil, 50))
+	// active deals are not failed
+	assert.Empty(t, unactivatedDealFailure(deal(storagemarket.StorageDealActive), nil, 120))
+}
+
+type trackerAddrMgr struct {
+	mockAddrMgr
+	miners []address.Address
+}
+
+func (m trackerAddrMgr) ActorAddress(ctx context.Context) ([]address.Address, error) {
+	return m.miners, nil
+}
+
+type trackerFullNode struct {
+	mock.MockFullNode
+	deals map[abi.DealID]*vTypes.MarketDeal
+}
+
+func (m *trackerFullNode) StateMarketStorageDeal(ctx context.Context, dealID abi.DealID, tsk vTypes.TipSetKey) (*vTypes.MarketDeal, error) {
+	if md, ok := m.deals[dealID]; ok {
+		return md, nil
+	}
+	return nil, fmt.Errorf("deal %d not found", dealID)
+}
+
+type mockDealStateChanges struct {
+	changed map[abi.DealID]struct{}
+}
+
+func (m *mockDealStateChanges) changedDealIDs(ctx context.Context, pre, cur vTypes.TipSetKey) (map[abi.DealID]struct{}, error) {
+	return m.changed, nil
+}
+
+type mockDiffPreCommits struct {
+	changes map[address.Address]*lminer.PreCommitChanges
+}
+
+func (m *mockDiffPreCommits) diffPreCommits(ctx context.Context, actor address.Address, pre, cur vTypes.TipSetKey) (*lminer.PreCommitChanges, error) {
+	if diff, ok := m.changes[actor]; ok {
+		return diff, nil
+	}
+	return &lminer.PreCommitChanges{}, nil
+}
+
+// countingDealRepo counts the scans of deals
+type countingDealRepo struct {
+	repo.StorageDealRepo
+	scans int
+}
+
+func (r *countingDealRepo) GetDealByAddrAndStatus(ctx context.Context, addr address.Address, status ...storagemarket.StorageDealStatus) ([]*types.MinerDeal, error) {
+	r.scans++
+	return r.StorageDealRepo.GetDealByAddrAndStatus(ctx, addr, status...)
+}
+
+func preCommit(sector abi.SectorNumber, dealIDs ...abi.DealID) lminer.SectorPreCommitOnChainInfo {
+	var info lminer.SectorPreCommitOnChainInfo
+	info.Info.SectorNumber = sector
+	info.Info.DealIDs = dealIDs
+	return info
+}
+
+func TestDealTrackerDiffDeals(t *testing.T) {
+	ctx := context.Background()
+	addrGetter := address.NewForTestGetter()
+	miner, other := addrGetter(), addrGetter()
+	tipset := func(height abi.ChainEpoch, name string) *vTypes.TipSet {
+		blk := test_helper.MakeTestBlock(t)
+		blk.Height = height
+		blk.ParentStateRoot = testhelpers.CidFromString(t, name)
+		return testhelpers.RequireNewTipSet(t, blk)
+	}
+	onChain := func(start abi.ChainEpoch) *vTypes.MarketDeal {
+		md := &vTypes.MarketDeal{}
+		md.State.SectorStartEpoch = start
+		md.State.SlashEpoch = -1
+		return md
+	}
+
+	storageRepo := &countingDealRepo{StorageDealRepo: badger.NewStorageDealRepo(badger.NewStorageDealsDS(datastore.NewMapDatastore()))}
+	proposals := shared_testutil.GenerateCids(6)
+	deal := func(i int, provider address.Address, dealID abi.DealID, sector abi.SectorNumber, state storagemarket.StorageDealStatus, end abi.ChainEpoch) {
+		d := &types.MinerDeal{ProposalCid: proposals[i], DealID: dealID, SectorNumber: sector, State: state}
+		d.ClientDealProposal.Proposal.Provider = provider
+		d.ClientDealProposal.Proposal.StartEpoch = 100
+		d.ClientDealProposal.Proposal.EndEpoch = end
+		require.NoError(t, storageRepo.SaveDeal(ctx, d))
+	}
+	deal(0, miner, 1, 1, storagemarket.StorageDealAwaitingPreCommit, 1000)
+	deal(1, miner, 2, 2, storagemarket.StorageDealSealing, 1000)
+	deal(2, miner, 3, 3, storagemarket.StorageDealSealing, 1000)
+	deal(3, miner, 4, 4, storagemarket.StorageDealActive, 15)
+	// not managed by this market
+	deal(4, other, 5, 1, storagemarket.StorageDealAwaitingPreCommit, 1000)
+	state := func(i int) string {
+		d, err := storageRepo.GetDeal(ctx, proposals[i])
+		require.NoError(t, err)
+		return storagemarket.DealStates[d.State]
+	}
+
+	node := &trackerFullNode{deals: map[abi.DealID]*vTypes.MarketDeal{
+		1: onChain(-1), 2: onChain(-1), 3: onChain(50), 4: onChain(5), 5: onChain(50),
+	}}
+	dsc := &mockDealStateChanges{}
+	dpc := &mockDiffPreCommits{}
+	tracker := &DealTracker{
+		storageRepo: storageRepo,
+		minerMgr:    trackerAddrMgr{miners: []address.Address{miner}},
+		fullNode:    node,
+		dsc:         dsc,
+		dpc:         dpc,
+		lastScan:    10,
+	}
+
+	// sector 1 precommitted, precommit of sector 2 expired, and deal 3 activated
+	dsc.changed = map[abi.DealID]struct{}{3: {}, 5: {}}
+	dpc.changes = map[address.Address]*lminer.PreCommitChanges{miner: {
+		Added:   []lminer.SectorPreCommitOnChainInfo{preCommit(1, 1)},
+		Removed: []lminer.SectorPreCommitOnChainInfo{preCommit(2, 2)},
+	}}
+	require.NoError(t, tracker.diffDeals(ctx, tipset(10, "a"), tipset(11, "b")))
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealSealing], state(0))
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealAwaitingPreCommit], state(1))
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealActive], state(2))
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealAwaitingPreCommit], state(4))
+
+	// a reorg reverts the precommit of sector 1 and the activation of deal 3, the changed deals are loaded through
+	// the index without reading all deals
+	scans := storageRepo.scans
+	node.deals[3] = onChain(-1)
+	dsc.changed = map[abi.DealID]struct{}{3: {}, 99: {}}
+	dpc.changes = map[address.Address]*lminer.PreCommitChanges{miner: {
+		Removed: []lminer.SectorPreCommitOnChainInfo{preCommit(1, 1)},
+	}}
+	require.NoError(t, tracker.diffDeals(ctx, tipset(11, "b"), tipset(11, "c")))
+	assert.Equal(t, scans, storageRepo.scans)
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealAwaitingPreCommit], state(0))
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealSealing], state(2))
+
+	// a deal not indexed yet is precommitted
+	deal(5, miner, 6, 6, storagemarket.StorageDealAwaitingPreCommit, 1000)
+	node.deals[6] = onChain(-1)
+	dsc.changed = nil
+	dpc.changes = map[address.Address]*lminer.PreCommitChanges{miner: {
+		Added: []lminer.SectorPreCommitOnChainInfo{preCommit(6, 6)},
+	}}
+	require.NoError(t, tracker.diffDeals(ctx, tipset(11, "c"), tipset(11, "d")))
+	assert.Equal(t, scans+1, storageRepo.scans)
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealSealing], state(5))
+
+	// the repo is not read if nothing changed until the scan is due
+	dsc.changed = nil
+	dpc.changes = nil
+	scans = storageRepo.scans
+	require.NoError(t, tracker.diffDeals(ctx, tipset(11, "d"), tipset(18, "d")))
+	assert.Equal(t, scans, storageRepo.scans)
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealActive], state(3))
+
+	require.NoError(t, tracker.diffDeals(ctx, tipset(18, "d"), tipset(20, "e")))
+	assert.Equal(t, scans+1, storageRepo.scans)
+	assert.Equal(t, storagemarket.DealStates[storagemarket.StorageDealExpired], state(3))
+	assert.Equal(t, abi.ChainEpoch(20), tracker.lastScan)
+
+	// a reorg to a lower height scans again
+	require.NoError(t, tracker.diffDeals(ctx, tipset(20, "e"), tipset(19, "f")))
+	assert.Equal(t, scans+2, storageRepo.scans)
+	assert.Equal(t, abi.ChainEpoch(19), tracker.lastScan)
+}
+
+type failingDealStateChanges struct{}
+
+func (failingDealStateChanges) changedDealIDs(ctx context.Context, pre, cur vTypes.TipSetKey) (map[abi.DealID]struct{}, error) {
+	return nil, fmt.Errorf("state not found")
+}
+
+func TestDealTrackerFallback(t *testing.T) {
+	ctx := context.Background()
+	miner := address.NewForTestGetter()()
+	tipset := func(height abi.ChainEpoch, name string) *vTypes.TipSet {
+		blk := test_helper.MakeTestBlock(t)
+		blk.Height = height
+		blk.ParentStateRoot = testhelpers.CidFromString(t, name)
+		return testhelpers.RequireNewTipSet(t, blk)
+	}
+
+	storageRepo := &countingDealRepo{StorageDealRepo: badger.NewStorageDealRepo(badger.NewStorageDealsDS(datastore.NewMapDatastore()))}
+	tracker := &DealTracker{
+		retryDelay:  time.Minute,
+		storageRepo: storageRepo,
+		minerMgr:    trackerAddrMgr{miners: []address.Address{miner}},
+		fullNode:    &trackerFullNode{},
+		dsc:         failingDealStateChanges{},
+		dpc:         &mockDiffPreCommits{},
+	}
+
+	first := tipset(10, "a")
+	tracker.onHead(ctx, first)
+	scans := storageRepo.scans
+
+	// the diff fails, and falls back to a full scan
+	tracker.onHead(ctx, tipset(11, "b"))
+	assert.Greater(t, storageRepo.scans, scans)
+	assert.Equal(t, abi.ChainEpoch(11), tracker.lastTs.Height())
+
+	// the fallback is not run again within retryDelay, and the failed range is diffed again on the next head
+	scans = storageRepo.scans
+	tracker.onHead(ctx, tipset(12, "c"))
+	assert.Equal(t, scans, storageRepo.scans)
+	assert.Equal(t, abi.ChainEpoch(11), tracker.lastTs.Height())
+
+	tracker.lastFallback = time.Now().Add(-time.Minute)
+	tracker.onHead(ctx, tipset(13, "d"))
+	assert.Greater(t, storageRepo.scans, scans)
+	assert.Equal(t, abi.ChainEpoch(13), tracker.lastTs.Height())
+}
+
+func TestCheckPreCommitChanges(t *testing.T) {
+	ctx := context.Background()
+	miner := address.NewForTestGetter()()
+	cur := test_helper.MakeTestTipset(t)
+
+	storageRepo := badger.NewStorageDealRepo(badger.NewStorageDealsDS(datastore.NewMapDatastore()))
+	proposals := shared_testutil.GenerateCids(4)
+	deals := make([]*types.MinerDeal, 0, len(proposals))
+	for i, state := range []storagemarket.StorageDealStatus{storagemarket.StorageDealAwaitingPreCommit,
+		storagemarket.StorageDealAwaitingPreCommit, storagemarket.StorageDealSealing, storagemarket.StorageDealSealing} {
+		d := &types.MinerDeal{ProposalCid: proposals[i], DealID: abi.DealID(i + 1), SectorNumber: abi.SectorNumber(i + 1), State: state}
+		d.ClientDealProposal.Proposal.Provider = miner
+		require.NoError(t, storageRepo.SaveDeal(ctx, d))
+		deals = append(deals, d)
+	}
+
+	tracker := &DealTracker{
+		storageRepo: storageRepo,
+		dpc: &mockDiffPreCommits{changes: map[address.Address]*lminer.PreCommitChanges{miner: {
+			// deal 2 is precommitted in a sector other than the assigned one
+			Added: []lminer.SectorPreCommitOnChainInfo{preCommit(1, 1), preCommit(9, 2), preCommit(5, 4)},
+			// deal 4 is precommitted again after its precommit expired
+			Removed: []lminer.SectorPreCommitOnChainInfo{preCommit(3, 3), preCommit(4, 4)},
+		}}},
+	}
+	changes, err := tracker.preCommitChanges(ctx, []address.Address{miner}, cur, cur)
+	require.NoError(t, err)
+	tracker.checkPreCommitChanges(ctx, deals, cur, changes)
+
+	expect := []storagemarket.StorageDealStatus{storagemarket.StorageDealSealing, storagemarket.StorageDealAwaitingPreCommit,
+		storagemarket.StorageDealAwaitingPreCommit, storagemarket.StorageDealSealing}
+	for i, state := range expect {
+		d, err := storageRepo.GetDeal(ctx, proposals[i])
+		require.NoError(t, err)
+		assert.Equal(t, storagemarket.DealStates[state], storagemarket.DealStates[d.State], "deal %d", i+1)
+	}
+}
@@ -12,9 +12,13 @@ import (
 	"github.com/filecoin-project/go-state-types/big"
 	"github.com/filecoin-project/go-state-types/builtin"
 	"github.com/filecoin-project/go-state-types/builtin/v8/market"
+	"github.com/filecoin-project/go-state-types/exitcode"
+	"github.com/filecoin-project/go-state-types/network"
 	"github.com/filecoin-project/venus-market/v2/api/clients"
 	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/metrics"
 	types2 "github.com/filecoin-project/venus-market/v2/types"
+	"github.com/filecoin-project/venus/pkg/constants"
 	"github.com/filecoin-project/venus/venus-shared/actors"
 	marketactor "github.com/filecoin-project/venus/venus-shared/actors/builtin/market"
 	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
@@ -27,13 +31,17 @@ import (
 type dealPublisherAPI interface {
 	ChainHead(context.Context) (*types.TipSet, error)
 	StateMinerInfo(context.Context, address.Address, types.TipSetKey) (types.MinerInfo, error)
+	StateNetworkVersion(context.Context, types.TipSetKey) (network.Version, error)
+	StateCall(context.Context, *types.Message, types.TipSetKey) (*types.InvocResult, error)
 
 	WalletBalance(context.Context, address.Address) (types.BigInt, error)
 	WalletHas(context.Context, address.Address) (bool, error)
 	StateAccountKey(context.Context, address.Address, types.TipSetKey) (address.Address, error)
 	StateLookupID(context.Context, address.Address, types.TipSetKey) (address.Address, error)
 
+	GasEstimateMessageGas(context.Context, *types.Message, *types.MessageSendSpec, types.TipSetKey) (*types.Message, error)
 	PushMessage(ctx context.Context, msg *types.Message, spec *types.MessageSendSpec) (cid.Cid, error)
+	WaitMsg(ctx context.Context, mCid cid.Cid, confidence uint64, loopBackLimit abi.ChainEpoch, allowReplaced bool) (*types.MsgLookup, error)
 }
 
 type DealPublisher struct {
@@ -59,12 +67,22 @@ func NewDealPublisherWrapper(
 			as:          as,
 			publishSpec: &types.MessageSendSpec{MaxFee: abi.TokenAmount(cfg.MaxPublishDealsFee)},
 			publishMsgCfg: PublishMsgConfig{
-				Period:         time.Duration(cfg.PublishMsgPeriod),
-				MaxDealsPerMsg: cfg.MaxDealsPerPublishMsg,
+				Period:               time.Duration(cfg.PublishMsgPeriod),
+				MaxDealsPerMsg:       cfg.MaxDealsPerPublishMsg,
+				ExpectedSealDuration: time.Duration(cfg.ExpectedSealDuration),
+				MaxBaseFee:           abi.TokenAmount(cfg.MaxPublishBaseFee),
 			},
 			publishers: map[address.Address]*singleDealPublisher{},
 		}
 		lc.Append(fx.Hook{
+			OnStart: func(ctx context.Context) error {
+				params, err := full.StateGetNetworkParams(ctx)
+				if err != nil {
+					return fmt.Errorf("get network params: %w", err)
+				}
+				dp.publishMsgCfg.BlockDelay = time.Duration(params.BlockDelaySecs) * time.Second
+				return nil
+			},
 			OnStop: func(ctx context.Context) error {
 				dp.lk.Lock()
 				for _, p := range dp.publishers {
@@ -106,6 +124,13 @@ func (p *DealPublisher) ForcePublishPendingDeals() {
 func (p *DealPublisher) Publish(ctx context.Context, deal market.ClientDealProposal) (cid.Cid, error) {
 	pdeal := newPendingDeal(ctx, deal)
 
+	// read the head before locking, so that a slow node doesn't block other deals
+	head, err := p.api.ChainHead(ctx)
+	if err != nil {
+		log.Warnf("get chain head for publishing deal with piece CID %s: %v", deal.Proposal.PieceCID, err)
+		head = nil
+	}
+
 	p.lk.Lock()
 	providerAddr := deal.Proposal.Provider
 	publisher, ok := p.publishers[providerAddr]
@@ -113,7 +138,7 @@ func (p *DealPublisher) Publish(ctx context.Context, deal market.ClientDealPropo
 		publisher = newDealPublisher(p.api, p.as, p.publishMsgCfg, p.publishSpec)
 		p.publishers[providerAddr] = publisher
 	}
-	publisher.processNewDeal(pdeal)
+	publisher.processNewDeal(pdeal, head)
 	p.lk.Unlock()
 	// Wait for the deal to be submitted
 	select {
@@ -124,6 +149,19 @@ func (p *DealPublisher) Publish(ctx context.Context, deal market.ClientDealPropo
 	}
 }
 
+// RetriedPublish returns the message which publishes the deal again after the publish message msgCid failed on
+// chain, and whether the deal is retried. Retries are only kept in memory, so the deals of a failed message are not
+// followed to its retry after a restart.
+func (p *DealPublisher) RetriedPublish(ctx context.Context, msgCid cid.Cid, proposal market.DealProposal) (cid.Cid, bool, error) {
+	p.lk.Lock()
+	publisher, ok := p.publishers[proposal.Provider]
+	p.lk.Unlock()
+	if !ok {
+		return cid.Undef, false, nil
+	}
+	return publisher.retriedPublish(ctx, msgCid, proposal)
+}
+
 // singleDealPublisher batches deal publishing so that many deals can be included in
 // a single publish message. This saves gas for miners that publish deals
 // frequently.
@@ -132,6 +170,11 @@ func (p *DealPublisher) Publish(ctx context.Context, deal market.ClientDealPropo
 // There is a configurable maximum number of deals that can be included in one
 // message. When the limit is reached the singleDealPublisher immediately submits a
 // publish message with all deals in the queue.
+// The deals are published before the period elapses if the nearest start epoch of
+// them is at risk given the expected seal duration, and are held back after the
+// period while the base fee is above the ceiling, until the deadline comes.
+// Deals are split into many messages if the estimated fee of one message exceeds
+// the max fee.
 type singleDealPublisher struct {
 	api dealPublisherAPI
 	as  *AddressSelector
@@ -141,12 +184,19 @@ type singleDealPublisher struct {
 
 	maxDealsPerPublishMsg  uint64
 	publishPeriod          time.Duration
+	expectedSealDuration   time.Duration
+	maxBaseFee             abi.TokenAmount
+	blockDelay             time.Duration
+	checkInterval          time.Duration
 	publishSpec            *types.MessageSendSpec
 	cancelWaitForMoreDeals context.CancelFunc
 	publishPeriodStart     time.Time
 
 	lk      sync.Mutex
 	pending []*pendingDeal
+
+	retryLk sync.Mutex
+	retries map[cid.Cid]*publishRetry
 }
 
 // A deal that is queued to be published
@@ -177,6 +227,13 @@ type PublishMsgConfig struct {
 	// The maximum number of deals to include in a single PublishStorageDeals
 	// message
 	MaxDealsPerMsg uint64
+	// The time needed to seal deals after they are published, deals are
+	// published early if the nearest start epoch is within it
+	ExpectedSealDuration time.Duration
+	// Deals are held back while the base fee is above it, zero means no limit
+	MaxBaseFee abi.TokenAmount
+	// The block delay of the network, which converts durations to epochs
+	BlockDelay time.Duration
 }
 
 func newDealPublisher(
@@ -193,7 +250,12 @@ func newDealPublisher(
 		Shutdown:              cancel,
 		maxDealsPerPublishMsg: publishMsgCfg.MaxDealsPerMsg,
 		publishPeriod:         publishMsgCfg.Period,
+		expectedSealDuration:  publishMsgCfg.ExpectedSealDuration,
+		maxBaseFee:            publishMsgCfg.MaxBaseFee,
+		blockDelay:            publishMsgCfg.BlockDelay,
+		checkInterval:         publishMsgCfg.BlockDelay,
 		publishSpec:           publishSpec,
+		retries:               map[cid.Cid]*publishRetry{},
 	}
 }
 
@@ -232,7 +294,8 @@ func (p *singleDealPublisher) forcePublishPendingDeals() {
 	p.publishAllDeals()
 }
 
-func (p *singleDealPublisher) processNewDeal(pdeal *pendingDeal) {
+// processNewDeal queue the deal, head is the current head read before locking, which is nil if it can't be read
+func (p *singleDealPublisher) processNewDeal(pdeal *pendingDeal, head *types.TipSet) {
 	p.lk.Lock()
 	defer p.lk.Unlock()
 
@@ -263,6 +326,13 @@ func (p *singleDealPublisher) processNewDeal(pdeal *pendingDeal) {
 		return
 	}
 
+	// The new deal may start sooner than the deals already in the queue
+	if head != nil && p.deadlineReached(head.Height()) {
+		log.Infof("deal with piece CID %s starts at %d, publishing deals", pdeal.deal.Proposal.PieceCID, pdeal.deal.Proposal.StartEpoch)
+		p.publishAllDeals()
+		return
+	}
+
 	// Otherwise wait for more deals to arrive or the timeout to be reached
 	p.waitForMoreDeals()
 }
@@ -284,20 +354,93 @@ func (p *singleDealPublisher) waitForMoreDeals() {
 
 	go func() {
 		timer := types2.Clock.NewTimer(p.publishPeriod)
-		select {
-		case <-ctx.Done():
-			timer.Stop()
-		case <-timer.Chan():
-			p.lk.Lock()
-			defer p.lk.Unlock()
-
-			// The timeout has expired so publish all pending deals
-			log.Infof("publish deals queue period of %s has expired, publishing deals", p.publishPeriod)
-			p.publishAllDeals()
+		defer timer.Stop()
+
+		periodExpired := false
+		for {
+			check := types2.Clock.NewTimer(p.checkInterval)
+			select {
+			case <-ctx.Done():
+				check.Stop()
+				return
+			case <-timer.Chan():
+				periodExpired = true
+			case <-check.Chan():
+			}
+			check.Stop()
+
+			if p.tryPublish(ctx, periodExpired) {
+				return
+			}
 		}
 	}()
 }
 
+// tryPublish publish all pending deals if the deadline of them is reached, or the period has expired and the
+// base fee is not above the ceiling, return true if the deals are published
+func (p *singleDealPublisher) tryPublish(ctx context.Context, periodExpired bool) bool {
+	head, err := p.api.ChainHead(p.ctx)
+
+	p.lk.Lock()
+	defer p.lk.Unlock()
+
+	// The deals have been published by other ways
+	if ctx.Err() != nil {
+		return true
+	}
+
+	if err != nil {
+		log.Warnf("get chain head for publishing deals: %v", err)
+		if periodExpired {
+			p.publishAllDeals()
+		}
+		return periodExpired
+	}
+
+	if p.deadlineReached(head.Height()) {
+		log.Infof("start epoch of pending deals is at risk at %d, publishing deals", head.Height())
+		p.publishAllDeals()
+		return true
+	}
+	if !periodExpired {
+		return false
+	}
+
+	if baseFee := head.Blocks()[0].ParentBaseFee; !p.maxBaseFee.Nil() && !p.maxBaseFee.IsZero() && baseFee.GreaterThan(p.maxBaseFee) {
+		log.Infof("base fee %s is above %s, holding back publishing %d deals", types.FIL(baseFee), types.FIL(p.maxBaseFee), len(p.pending))
+		return false
+	}
+
+	// The timeout has expired so publish all pending deals
+	log.Infof("publish deals queue period of %s has expired, publishing deals", p.publishPeriod)
+	p.publishAllDeals()
+	return true
+}
+
+// deadlineReached tells whether a pending deal may miss its start epoch if its publishing waits longer
+func (p *singleDealPublisher) deadlineReached(height abi.ChainEpoch) bool {
+	sealEpochs := abi.ChainEpoch(p.expectedSealDuration / p.blockDelay)
+	deadline, ok := publishDeadline(p.pending, sealEpochs)
+	return ok && height >= deadline
+}
+
+// publishDeadline returns the latest epoch to publish the deals, so that the deal starting first can be sealed
+// before its start epoch
+func publishDeadline(deals []*pendingDeal, sealEpochs abi.ChainEpoch) (abi.ChainEpoch, bool) {
+	var deadline abi.ChainEpoch
+	found := false
+	for _, pd := range deals {
+		if pd.ctx.Err() != nil {
+			continue
+		}
+		if dl := pd.deal.Proposal.StartEpoch - sealEpochs; !found || dl < deadline {
+			deadline = dl
+			found = true
+		}
+	}
+	return deadline, found
+}
+
 func (p *singleDealPublisher) publishAllDeals() {
 	// If the timeout hasn't yet been cancelled, cancel it
 	if p.cancelWaitForMoreDeals != nil {
@@ -337,7 +480,6 @@ func (p *singleDealPublisher) publishReady(ready []*pendingDeal) {
 
 	// Validate each deal to make sure it can be published
 	validated := make([]*pendingDeal, 0, len(ready))
-	deals := make([]market.ClientDealProposal, 0, len(ready))
 	for _, pd := range ready {
 		// Validate the deal
 		if err := p.validateDeal(pd.deal); err != nil {
@@ -347,16 +489,301 @@ func (p *singleDealPublisher) publishReady(ready []*pendingDeal) {
 		}
 
 		validated = append(validated, pd)
-		deals = append(deals, pd.deal)
+	}
+	if len(validated) == 0 {
+		return
 	}
 
-	// Send the publish message
-	msgCid, err := p.publishDealProposals(deals)
+	build, err := p.messageBuilder(validated[0].deal.Proposal.Provider)
+	if err != nil {
+		for _, pd := range validated {
+			go onComplete(pd, cid.Undef, err)
+		}
+		return
+	}
+
+	// Split the deals if publishing them in one message costs too much
+	estimate := func(deals []market.ClientDealProposal) (abi.TokenAmount, error) {
+		return p.estimatePublishFee(deals, build)
+	}
+	for _, batch := range splitByFee(validated, p.publishSpec.MaxFee, estimate) {
+		go p.publishBatch(batch, build, onComplete)
+	}
+}
+
+// publishBatch publish the deals in one message. The deals which fail the simulation are dropped before pushing the
+// message, since the messager may push it without estimating gas, and a failing batch is only told by its receipt.
+// The deals are completed once the message is pushed, and if the message fails on chain, the invalid deals are
+// dropped and the rest are retried once in a new message, which the deals waiting for the failed message follow.
+func (p *singleDealPublisher) publishBatch(batch []*pendingDeal, build publishMessageBuilder, onComplete func(*pendingDeal, cid.Cid, error)) {
+	batch, dropped := p.dropInvalidDeals(batch, build)
+	for pd, dropErr := range dropped {
+		go onComplete(pd, cid.Undef, dropErr)
+	}
+	if len(batch) == 0 {
+		return
+	}
+
+	msgCid, err := p.publishDealProposals(proposalsOf(batch), build)
+	if err != nil || len(batch) == 1 {
+		for _, pd := range batch {
+			go onComplete(pd, msgCid, err)
+		}
+		return
+	}
+
+	// the retry is registered before completing the deals, so that waiting for the message always finds it
+	retry := p.startRetry(msgCid)
+	for _, pd := range batch {
+		go onComplete(pd, msgCid, nil)
+	}
+	p.finishRetry(msgCid, retry, p.retryFailed(msgCid, batch, build))
+}
+
+// retryFailed wait for the publish message, and if it fails on chain, publish the deals which are still valid again
+// in a new message, return the new message of the retried deals by their proposal cids. The deals are not retried if
+// waiting for the message fails, since the message may still land, and the deals keep following it.
+func (p *singleDealPublisher) retryFailed(msgCid cid.Cid, batch []*pendingDeal, build publishMessageBuilder) map[cid.Cid]cid.Cid {
+	exitCode, err := p.waitPublished(msgCid)
+	if err != nil {
+		log.Warnf("wait publish message %s, %d deals in it are not retried: %v", msgCid, len(batch), err)
+		return nil
+	}
+	if exitCode == exitcode.Ok {
+		return nil
+	}
+
+	// publishing the same deals again would fail again
+	valid, dropped := p.dropInvalidDeals(batch, build)
+	if len(dropped) == 0 || len(valid) == 0 {
+		log.Warnf("publish message %s failed with exit code %s, no deal is retried", msgCid, exitCode)
+		return nil
+	}
+	log.Infof("publish message %s failed with exit code %s, retry publishing %d deals after dropping %d invalid deals",
+		msgCid, exitCode, len(valid), len(dropped))
+	retryCid, err := p.publishDealProposals(proposalsOf(valid), build)
+	if err != nil {
+		log.Errorf("retry publishing %d deals of failed message %s: %v", len(valid), msgCid, err)
+		return nil
+	}
+
+	retried := make(map[cid.Cid]cid.Cid, len(valid))
+	for _, pd := range valid {
+		proposalCid, err := pd.deal.Proposal.Cid()
+		if err != nil {
+			log.Errorf("get proposal cid of deal with piece CID %s: %v", pd.deal.Proposal.PieceCID, err)
+			continue
+		}
+		retried[proposalCid] = retryCid
+	}
+	return retried
+}
+
+// publishRetry is the retry of a publish message with many deals, done is closed once the message lands and the
+// deals to retry, if any, are pushed again
+type publishRetry struct {
+	done    chan struct{}
+	retried map[cid.Cid]cid.Cid
+}
+
+func (p *singleDealPublisher) startRetry(msgCid cid.Cid) *publishRetry {
+	retry := &publishRetry{done: make(chan struct{})}
+	p.retryLk.Lock()
+	p.retries[msgCid] = retry
+	p.retryLk.Unlock()
+	return retry
+}
+
+// finishRetry record the retried deals of the message, the retry is forgotten at once if no deal is retried
+func (p *singleDealPublisher) finishRetry(msgCid cid.Cid, retry *publishRetry, retried map[cid.Cid]cid.Cid) {
+	p.retryLk.Lock()
+	retry.retried = retried
+	if len(retried) == 0 {
+		delete(p.retries, msgCid)
+	}
+	p.retryLk.Unlock()
+	close(retry.done)
+}
+
+// retriedPublish returns the message which publishes the deal again after msgCid failed, and whether the deal is
+// retried. It waits until the publisher decides whether to retry msgCid. Each retried deal is forgotten once it is
+// looked up.
+func (p *singleDealPublisher) retriedPublish(ctx context.Context, msgCid cid.Cid, proposal market.DealProposal) (cid.Cid, bool, error) {
+	p.retryLk.Lock()
+	retry, ok := p.retries[msgCid]
+	p.retryLk.Unlock()
+	if !ok {
+		return cid.Undef, false, nil
+	}
+
+	select {
+	case <-ctx.Done():
+		return cid.Undef, false, ctx.Err()
+	case <-retry.done:
+	}
+
+	proposalCid, err := proposal.Cid()
+	if err != nil {
+		return cid.Undef, false, fmt.Errorf("get proposal cid: %w", err)
+	}
+	p.retryLk.Lock()
+	defer p.retryLk.Unlock()
+	retryCid, ok := retry.retried[proposalCid]
+	if ok {
+		delete(retry.retried, proposalCid)
+		if len(retry.retried) == 0 {
+			delete(p.retries, msgCid)
+		}
+	}
+	return retryCid, ok, nil
+}
+
+// dropInvalidDeals simulate publishing the deals against the current head, and split out the deals which can't be
+// published, with the error of each of them. All deals are kept if the simulation fails.
+func (p *singleDealPublisher) dropInvalidDeals(deals []*pendingDeal, build publishMessageBuilder) ([]*pendingDeal, map[*pendingDeal]error) {
+	head, err := p.api.ChainHead(p.ctx)
+	if err != nil {
+		log.Warnf("get chain head for validating deals: %v", err)
+		return deals, nil
+	}
+	nv, err := p.api.StateNetworkVersion(p.ctx, head.Key())
+	if err != nil {
+		log.Warnf("get network version for validating deals: %v", err)
+		return deals, nil
+	}
+
+	simulate := func(proposals []market.ClientDealProposal) (*types.InvocResult, error) {
+		msg, err := build(proposals)
+		if err != nil {
+			return nil, err
+		}
+		return p.api.StateCall(p.ctx, msg, head.Key())
+	}
+	decode := func(ret []byte) (marketactor.PublishStorageDealsReturn, error) {
+		return marketactor.DecodePublishStorageDealsReturn(ret, nv)
+	}
+
+	reasons, err := findInvalidDeals(deals, simulate, decode)
+	if err != nil {
+		log.Warnf("validate %d deals at %d: %v", len(deals), head.Height(), err)
+		return deals, nil
+	}
+	valid := make([]*pendingDeal, 0, len(deals))
+	invalid := make(map[*pendingDeal]error, len(reasons))
+	for _, pd := range deals {
+		if reason, ok := reasons[pd]; ok {
+			log.Warnf("drop deal with piece CID %s from publish message: %s", pd.deal.Proposal.PieceCID, reason)
+			invalid[pd] = fmt.Errorf("deal with piece CID %s is invalid at epoch %d: %s", pd.deal.Proposal.PieceCID, head.Height(), reason)
+		} else {
+			valid = append(valid, pd)
+		}
+	}
+	return valid, invalid
+}
 
-	// Signal that each deal has been published
-	for _, pd := range validated {
-		go onComplete(pd, msgCid, err)
+// findInvalidDeals returns the reasons of deals which can't be published. If publishing the deals succeeds, the
+// invalid deals are read from the valid deals bitfield in the return, which is only set by newer actors, otherwise
+// the deals are halved until each part succeeds or only has one deal, so that a few invalid deals in a large batch
+// are found with a number of simulations logarithmic in the size of the batch.
+func findInvalidDeals(
+	deals []*pendingDeal,
+	simulate func([]market.ClientDealProposal) (*types.InvocResult, error),
+	decode func([]byte) (marketactor.PublishStorageDealsReturn, error),
+) (map[*pendingDeal]string, error) {
+	invalid := make(map[*pendingDeal]string)
+	if err := collectInvalidDeals(deals, simulate, decode, invalid); err != nil {
+		return nil, err
 	}
+	return invalid, nil
+}
+
+func collectInvalidDeals(
+	deals []*pendingDeal,
+	simulate func([]market.ClientDealProposal) (*types.InvocResult, error),
+	decode func([]byte) (marketactor.PublishStorageDealsReturn, error),
+	invalid map[*pendingDeal]string,
+) error {
+	res, err := simulate(proposalsOf(deals))
+	if err != nil {
+		return err
+	}
+	if res.MsgRct != nil && res.MsgRct.ExitCode == exitcode.Ok {
+		ret, err := decode(res.MsgRct.Return)
+		if err != nil {
+			return fmt.Errorf("decoding publish storage deals return: %w", err)
+		}
+		for i, pd := range deals {
+			valid, _, err := ret.IsDealValid(uint64(i))
+			if err != nil {
+				return fmt.Errorf("determining deal validity: %w", err)
+			}
+			if !valid {
+				invalid[pd] = "deal is dropped by market actor"
+			}
+		}
+		return nil
+	}
+
+	if len(deals) == 1 {
+		reason := res.Error
+		if len(reason) == 0 && res.MsgRct != nil {
+			reason = fmt.Sprintf("exit code %s", res.MsgRct.ExitCode)
+		}
+		invalid[deals[0]] = reason
+		return nil
+	}
+	half := len(deals) / 2
+	if err := collectInvalidDeals(deals[:half], simulate, decode, invalid); err != nil {
+		return err
+	}
+	return collectInvalidDeals(deals[half:], simulate, decode, invalid)
+}
+
+func proposalsOf(deals []*pendingDeal) []market.ClientDealProposal {
+	proposals := make([]market.ClientDealProposal, 0, len(deals))
+	for _, pd := range deals {
+		proposals = append(proposals, pd.deal)
+	}
+	return proposals
+}
+
+// splitByFee halves the deals until the estimated fee of publishing each part is not above maxFee, or the part only
+// has one deal. The deals are not split if maxFee is zero or the fee can't be estimated.
+func splitByFee(deals []*pendingDeal, maxFee abi.TokenAmount, estimate func([]market.ClientDealProposal) (abi.TokenAmount, error)) [][]*pendingDeal {
+	if maxFee.Nil() || maxFee.IsZero() || len(deals) <= 1 {
+		return [][]*pendingDeal{deals}
+	}
+
+	fee, err := estimate(proposalsOf(deals))
+	if err != nil {
+		log.Warnf("estimate fee of publishing %d deals: %v", len(deals), err)
+		return [][]*pendingDeal{deals}
+	}
+	if fee.LessThanEqual(maxFee) {
+		return [][]*pendingDeal{deals}
+	}
+
+	log.Infof("estimated fee %s of publishing %d deals is above %s, splitting deals", types.FIL(fee), len(deals), types.FIL(maxFee))
+	half := len(deals) / 2
+	return append(splitByFee(deals[:half], maxFee, estimate), splitByFee(deals[half:], maxFee, estimate)...)
+}
+
+// estimatePublishFee estimate the fee of publishing the deals at the current base fee
+func (p *singleDealPublisher) estimatePublishFee(deals []market.ClientDealProposal, build publishMessageBuilder) (abi.TokenAmount, error) {
+	msg, err := build(deals)
+	if err != nil {
+		return big.Zero(), err
+	}
+	head, err := p.api.ChainHead(p.ctx)
+	if err != nil {
+		return big.Zero(), err
+	}
+	estimated, err := p.api.GasEstimateMessageGas(p.ctx, msg, nil, head.Key())
+	if err != nil {
+		return big.Zero(), err
+	}
+	feePerGas := big.Add(head.Blocks()[0].ParentBaseFee, estimated.GasPremium)
+	return big.Mul(feePerGas, big.NewInt(estimated.GasLimit)), nil
 }
 
 // validateDeal checks that the deal proposal start epoch hasn't already
@@ -375,54 +802,80 @@ func (p *singleDealPublisher) validateDeal(deal market.ClientDealProposal) error
 }
 
 // Sends the publish message
-func (p *singleDealPublisher) publishDealProposals(deals []market.ClientDealProposal) (cid.Cid, error) {
+func (p *singleDealPublisher) publishDealProposals(deals []market.ClientDealProposal, build publishMessageBuilder) (cid.Cid, error) {
 	if len(deals) == 0 {
 		return cid.Undef, nil
 	}
 
 	log.Infof("publishing %d deals in publish deals queue with piece CIDs: %s", len(deals), pieceCids(deals))
 
-	provider := deals[0].Proposal.Provider
-	for _, dl := range deals {
-		if dl.Proposal.Provider != provider {
-			msg := fmt.Sprintf("publishing %d deals failed: ", len(deals)) +
-				"not all deals are for same provider: " +
-				fmt.Sprintf("deal with piece CID %s is for provider %s ", deals[0].Proposal.PieceCID, deals[0].Proposal.Provider) +
-				fmt.Sprintf("but deal with piece CID %s is for provider %s", dl.Proposal.PieceCID, dl.Proposal.Provider)
-			return cid.Undef, fmt.Errorf(msg)
-		}
+	msg, err := build(deals)
+	if err != nil {
+		return cid.Undef, err
 	}
-
-	mi, err := p.api.StateMinerInfo(p.ctx, provider, types.EmptyTSK)
+	msgId, err := p.api.PushMessage(p.ctx, msg, p.publishSpec)
 	if err != nil {
 		return cid.Undef, err
 	}
 
-	params, err := actors.SerializeParams(&market.PublishStorageDealsParams{
-		Deals: deals,
-	})
+	metrics.Record(p.ctx, nil, metrics.DealPublishBatchSize.M(int64(len(deals))))
 
+	return msgId, nil
+}
+
+// publishMessageBuilder builds the PublishStorageDeals message of deals
+type publishMessageBuilder func([]market.ClientDealProposal) (*types.Message, error)
+
+// messageBuilder reads the miner info and selects the address to send from once, so that the messages built for
+// simulating, estimating and publishing the deals of a batch share them
+func (p *singleDealPublisher) messageBuilder(provider address.Address) (publishMessageBuilder, error) {
+	mi, err := p.api.StateMinerInfo(p.ctx, provider, types.EmptyTSK)
 	if err != nil {
-		return cid.Undef, fmt.Errorf("serializing PublishStorageDeals params failed: %w", err)
+		return nil, err
 	}
-
 	addr, _, err := p.as.AddressFor(p.ctx, p.api, mi, marketTypes.DealPublishAddr, big.Zero(), big.Zero())
 	if err != nil {
-		return cid.Undef, fmt.Errorf("selecting address for publishing deals: %w", err)
+		return nil, fmt.Errorf("selecting address for publishing deals: %w", err)
 	}
 
-	msgId, err := p.api.PushMessage(p.ctx, &types.Message{
-		To:     marketactor.Address,
-		From:   addr,
-		Value:  types.NewInt(0),
-		Method: builtin.MethodsMarket.PublishStorageDeals,
-		Params: params,
-	}, p.publishSpec)
+	return func(deals []market.ClientDealProposal) (*types.Message, error) {
+		for _, dl := range deals {
+			if dl.Proposal.Provider != provider {
+				msg := fmt.Sprintf("publishing %d deals failed: ", len(deals)) +
+					"not all deals are for same provider: " +
+					fmt.Sprintf("deal with piece CID %s is for provider %s ", deals[0].Proposal.PieceCID, provider) +
+					fmt.Sprintf("but deal with piece CID %s is for provider %s", dl.Proposal.PieceCID, dl.Proposal.Provider)
+				return nil, fmt.Errorf(msg)
+			}
+		}
+
+		params, err := actors.SerializeParams(&market.PublishStorageDealsParams{
+			Deals: deals,
+		})
+
+		if err != nil {
+			return nil, fmt.Errorf("serializing PublishStorageDeals params failed: %w", err)
+		}
+
+		return &types.Message{
+			To:     marketactor.Address,
+			From:   addr,
+			Value:  types.NewInt(0),
+			Method: builtin.MethodsMarket.PublishStorageDeals,
+			Params: params,
+		}, nil
+	}, nil
+}
 
+// waitPublished wait for the publish message to be landed on chain, record the gas it used, and return the exit code
+// of it. The deals are not failed by the wait itself, which is done again by the deal handler.
+func (p *singleDealPublisher) waitPublished(msgCid cid.Cid) (exitcode.ExitCode, error) {
+	lookup, err := p.api.WaitMsg(p.ctx, msgCid, constants.MessageConfidence, constants.LookbackNoLimit, true)
 	if err != nil {
-		return cid.Undef, err
+		return 0, err
 	}
-	return msgId, nil
+	metrics.Record(p.ctx, nil, metrics.DealPublishGasUsed.M(lookup.Receipt.GasUsed))
+	return lookup.Receipt.ExitCode, nil
 }
 
 func pieceCids(deals []market.ClientDealProposal) string {
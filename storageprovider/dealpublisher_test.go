@@ -0,0 +1,394 @@
+package storageprovider
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"fmt"
+	"sync"
+	"testing"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-bitfield"
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/go-state-types/big"
+	"github.com/filecoin-project/go-state-types/builtin/v8/market"
+	"github.com/filecoin-project/go-state-types/exitcode"
+	"github.com/filecoin-project/go-state-types/network"
+	"github.com/filecoin-project/venus-market/v2/utils/test_helper"
+	marketactor "github.com/filecoin-project/venus/venus-shared/actors/builtin/market"
+	"github.com/filecoin-project/venus/venus-shared/types"
+	"github.com/ipfs/go-cid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestPublishDeadline(t *testing.T) {
+	deal := func(ctx context.Context, start abi.ChainEpoch) *pendingDeal {
+		var proposal market.ClientDealProposal
+		proposal.Proposal.StartEpoch = start
+		return newPendingDeal(ctx, proposal)
+	}
+
+	_, ok := publishDeadline(nil, 100)
+	assert.False(t, ok)
+
+	cancelled, cancel := context.WithCancel(context.Background())
+	cancel()
+	deals := []*pendingDeal{
+		deal(context.Background(), 3000),
+		deal(cancelled, 1000),
+		deal(context.Background(), 2000),
+	}
+	deadline, ok := publishDeadline(deals, 100)
+	assert.True(t, ok)
+	assert.Equal(t, abi.ChainEpoch(1900), deadline)
+}
+
+func TestSplitByFee(t *testing.T) {
+	deals := make([]*pendingDeal, 7)
+	for i := range deals {
+		var proposal market.ClientDealProposal
+		proposal.Proposal.StartEpoch = abi.ChainEpoch(i)
+		deals[i] = newPendingDeal(context.Background(), proposal)
+	}
+	// each deal costs 10
+	estimate := func(proposals []market.ClientDealProposal) (abi.TokenAmount, error) {
+		return big.NewInt(int64(10 * len(proposals))), nil
+	}
+	sizes := func(batches [][]*pendingDeal) []int {
+		var out []int
+		for _, batch := range batches {
+			out = append(out, len(batch))
+		}
+		return out
+	}
+
+	assert.Equal(t, []int{7}, sizes(splitByFee(deals, big.Zero(), estimate)))
+	assert.Equal(t, []int{7}, sizes(splitByFee(deals, big.NewInt(70), estimate)))
+	assert.Equal(t, []int{3, 4}, sizes(splitByFee(deals, big.NewInt(40), estimate)))
+	assert.Equal(t, []int{1, 2, 2, 2}, sizes(splitByFee(deals, big.NewInt(25), estimate)))
+	assert.Equal(t, []int{1, 1, 1, 1, 1, 1, 1}, sizes(splitByFee(deals, big.NewInt(5), estimate)))
+
+	// deals are kept in order
+	batches := splitByFee(deals, big.NewInt(40), estimate)
+	assert.Equal(t, deals[:3], batches[0])
+	assert.Equal(t, deals[3:], batches[1])
+
+	// not split if the fee can't be estimated
+	failed := func([]market.ClientDealProposal) (abi.TokenAmount, error) {
+		return big.Zero(), fmt.Errorf("estimate failed")
+	}
+	assert.Equal(t, []int{7}, sizes(splitByFee(deals, big.NewInt(5), failed)))
+}
+
+type fakePublishReturn struct {
+	valid []bool
+}
+
+func (r fakePublishReturn) DealIDs() ([]abi.DealID, error) {
+	var ids []abi.DealID
+	for i, valid := range r.valid {
+		if valid {
+			ids = append(ids, abi.DealID(i))
+		}
+	}
+	return ids, nil
+}
+
+func (r fakePublishReturn) IsDealValid(index uint64) (bool, int, error) {
+	if index >= uint64(len(r.valid)) {
+		return false, 0, fmt.Errorf("index %d out of range", index)
+	}
+	outIdx := 0
+	for _, valid := range r.valid[:index] {
+		if valid {
+			outIdx++
+		}
+	}
+	return r.valid[index], outIdx, nil
+}
+
+func TestFindInvalidDeals(t *testing.T) {
+	// deals starting before epoch 0 are invalid
+	deals := make([]*pendingDeal, 4)
+	for i, start := range []abi.ChainEpoch{10, -1, 20, -1} {
+		var proposal market.ClientDealProposal
+		proposal.Proposal.StartEpoch = start
+		deals[i] = newPendingDeal(context.Background(), proposal)
+	}
+	validity := func(proposals []market.ClientDealProposal) []bool {
+		valid := make([]bool, len(proposals))
+		for i, proposal := range proposals {
+			valid[i] = proposal.Proposal.StartEpoch >= 0
+		}
+		return valid
+	}
+
+	t.Run("valid deals bitfield", func(t *testing.T) {
+		calls := 0
+		simulate := func(proposals []market.ClientDealProposal) (*types.InvocResult, error) {
+			calls++
+			ret, err := json.Marshal(validity(proposals))
+			return &types.InvocResult{MsgRct: &types.MessageReceipt{ExitCode: exitcode.Ok, Return: ret}}, err
+		}
+		decode := func(ret []byte) (marketactor.PublishStorageDealsReturn, error) {
+			var r fakePublishReturn
+			return r, json.Unmarshal(ret, &r.valid)
+		}
+
+		invalid, err := findInvalidDeals(deals, simulate, decode)
+		require.NoError(t, err)
+		assert.Equal(t, 1, calls)
+		assert.Len(t, invalid, 2)
+		assert.Contains(t, invalid, deals[1])
+		assert.Contains(t, invalid, deals[3])
+	})
+
+	// older actors fail the whole message if any deal is invalid
+	calls := 0
+	failAll := func(proposals []market.ClientDealProposal) (*types.InvocResult, error) {
+		calls++
+		valid := validity(proposals)
+		for _, v := range valid {
+			if !v {
+				return &types.InvocResult{MsgRct: &types.MessageReceipt{ExitCode: exitcode.ErrIllegalArgument}, Error: "start epoch passed"}, nil
+			}
+		}
+		ret, err := json.Marshal(valid)
+		return &types.InvocResult{MsgRct: &types.MessageReceipt{ExitCode: exitcode.Ok, Return: ret}}, err
+	}
+	decode := func(ret []byte) (marketactor.PublishStorageDealsReturn, error) {
+		var r fakePublishReturn
+		return r, json.Unmarshal(ret, &r.valid)
+	}
+
+	t.Run("halve failed deals", func(t *testing.T) {
+		calls = 0
+		invalid, err := findInvalidDeals(deals, failAll, decode)
+		require.NoError(t, err)
+		assert.Equal(t, map[*pendingDeal]string{deals[1]: "start epoch passed", deals[3]: "start epoch passed"}, invalid)
+	})
+
+	t.Run("few invalid deals in a large batch", func(t *testing.T) {
+		large := make([]*pendingDeal, 64)
+		for i := range large {
+			var proposal market.ClientDealProposal
+			proposal.Proposal.StartEpoch = abi.ChainEpoch(i)
+			if i == 37 {
+				proposal.Proposal.StartEpoch = -1
+			}
+			large[i] = newPendingDeal(context.Background(), proposal)
+		}
+
+		calls = 0
+		invalid, err := findInvalidDeals(large, failAll, decode)
+		require.NoError(t, err)
+		assert.Len(t, invalid, 1)
+		assert.Contains(t, invalid, large[37])
+		// the whole batch, and both halves at each of the 6 levels
+		assert.Equal(t, 13, calls)
+	})
+
+	t.Run("simulation error", func(t *testing.T) {
+		simulate := func([]market.ClientDealProposal) (*types.InvocResult, error) {
+			return nil, fmt.Errorf("node offline")
+		}
+		_, err := findInvalidDeals(deals, simulate, nil)
+		assert.Error(t, err)
+	})
+}
+
+// fakePublishAPI tells deals by their start epochs. Deals in invalidOnChain pass the simulation, but fail the
+// message after it lands, and are invalid since then.
+type fakePublishAPI struct {
+	dealPublisherAPI
+	head *types.TipSet
+
+	lk             sync.Mutex
+	invalid        map[abi.ChainEpoch]bool
+	invalidOnChain map[abi.ChainEpoch]bool
+	outOfGas       bool
+	waitErr        error
+	pushed         [][]abi.ChainEpoch
+	msgs           map[cid.Cid][]abi.ChainEpoch
+}
+
+func (f *fakePublishAPI) dealsOf(msg *types.Message) ([]abi.ChainEpoch, error) {
+	var params market.PublishStorageDealsParams
+	if err := params.UnmarshalCBOR(bytes.NewReader(msg.Params)); err != nil {
+		return nil, err
+	}
+	starts := make([]abi.ChainEpoch, 0, len(params.Deals))
+	for _, deal := range params.Deals {
+		starts = append(starts, deal.Proposal.StartEpoch)
+	}
+	return starts, nil
+}
+
+func (f *fakePublishAPI) ChainHead(context.Context) (*types.TipSet, error) {
+	return f.head, nil
+}
+
+func (f *fakePublishAPI) StateMinerInfo(context.Context, address.Address, types.TipSetKey) (types.MinerInfo, error) {
+	return types.MinerInfo{Worker: address.NewForTestGetter()()}, nil
+}
+
+func (f *fakePublishAPI) StateNetworkVersion(context.Context, types.TipSetKey) (network.Version, error) {
+	return network.Version16, nil
+}
+
+func (f *fakePublishAPI) StateCall(ctx context.Context, msg *types.Message, tsk types.TipSetKey) (*types.InvocResult, error) {
+	starts, err := f.dealsOf(msg)
+	if err != nil {
+		return nil, err
+	}
+	f.lk.Lock()
+	defer f.lk.Unlock()
+	for _, start := range starts {
+		if f.invalid[start] {
+			return &types.InvocResult{MsgRct: &types.MessageReceipt{ExitCode: exitcode.ErrIllegalArgument}, Error: "invalid deal"}, nil
+		}
+	}
+
+	ret := market.PublishStorageDealsReturn{}
+	valid := make([]uint64, 0, len(starts))
+	for i := range starts {
+		ret.IDs = append(ret.IDs, abi.DealID(i))
+		valid = append(valid, uint64(i))
+	}
+	ret.ValidDeals = bitfield.NewFromSet(valid)
+	buf := new(bytes.Buffer)
+	if err := ret.MarshalCBOR(buf); err != nil {
+		return nil, err
+	}
+	return &types.InvocResult{MsgRct: &types.MessageReceipt{ExitCode: exitcode.Ok, Return: buf.Bytes()}}, nil
+}
+
+func (f *fakePublishAPI) PushMessage(ctx context.Context, msg *types.Message, spec *types.MessageSendSpec) (cid.Cid, error) {
+	starts, err := f.dealsOf(msg)
+	if err != nil {
+		return cid.Undef, err
+	}
+	f.lk.Lock()
+	defer f.lk.Unlock()
+	msgCid := shared_testutil.GenerateCids(1)[0]
+	f.pushed = append(f.pushed, starts)
+	f.msgs[msgCid] = starts
+	return msgCid, nil
+}
+
+func (f *fakePublishAPI) WaitMsg(ctx context.Context, mCid cid.Cid, confidence uint64, loopBackLimit abi.ChainEpoch, allowReplaced bool) (*types.MsgLookup, error) {
+	f.lk.Lock()
+	defer f.lk.Unlock()
+	if f.waitErr != nil {
+		return nil, f.waitErr
+	}
+	code := exitcode.Ok
+	if f.outOfGas {
+		code = exitcode.SysErrOutOfGas
+	}
+	for _, start := range f.msgs[mCid] {
+		if f.invalidOnChain[start] {
+			code = exitcode.ErrIllegalArgument
+			f.invalid[start] = true
+		}
+	}
+	return &types.MsgLookup{Message: mCid, Receipt: types.MessageReceipt{ExitCode: code}}, nil
+}
+
+func TestPublishBatch(t *testing.T) {
+	ctx := context.Background()
+	addrGetter := address.NewForTestGetter()
+	client, provider := addrGetter(), addrGetter()
+	pieces := shared_testutil.GenerateCids(3)
+
+	publish := func(api *fakePublishAPI) (*singleDealPublisher, []*pendingDeal, []publishResult) {
+		api.head = test_helper.MakeTestTipset(t)
+		api.msgs = make(map[cid.Cid][]abi.ChainEpoch)
+		p := newDealPublisher(api, nil, PublishMsgConfig{}, &types.MessageSendSpec{})
+
+		deals := make([]*pendingDeal, 3)
+		for i := range deals {
+			var proposal market.ClientDealProposal
+			proposal.Proposal.PieceCID = pieces[i]
+			proposal.Proposal.Client = client
+			proposal.Proposal.Provider = provider
+			proposal.Proposal.StartEpoch = abi.ChainEpoch(i + 1)
+			deals[i] = newPendingDeal(ctx, proposal)
+		}
+		build, err := p.messageBuilder(provider)
+		require.NoError(t, err)
+		go p.publishBatch(deals, build, func(pd *pendingDeal, msgCid cid.Cid, err error) {
+			pd.Result <- publishResult{msgCid: msgCid, err: err}
+		})
+
+		results := make([]publishResult, len(deals))
+		for i, pd := range deals {
+			results[i] = <-pd.Result
+		}
+		return p, deals, results
+	}
+	retried := func(p *singleDealPublisher, pd *pendingDeal, msgCid cid.Cid) (cid.Cid, bool) {
+		retryCid, ok, err := p.retriedPublish(ctx, msgCid, pd.deal.Proposal)
+		require.NoError(t, err)
+		return retryCid, ok
+	}
+
+	t.Run("drop before pushing", func(t *testing.T) {
+		api := &fakePublishAPI{invalid: map[abi.ChainEpoch]bool{2: true}}
+		p, deals, results := publish(api)
+		assert.Equal(t, [][]abi.ChainEpoch{{1, 3}}, api.pushed)
+		assert.Error(t, results[1].err)
+		for _, i := range []int{0, 2} {
+			assert.NoError(t, results[i].err)
+			assert.Contains(t, api.msgs, results[i].msgCid)
+			_, ok := retried(p, deals[i], results[i].msgCid)
+			assert.False(t, ok)
+		}
+	})
+
+	t.Run("retry after the message failed", func(t *testing.T) {
+		api := &fakePublishAPI{invalid: map[abi.ChainEpoch]bool{}, invalidOnChain: map[abi.ChainEpoch]bool{2: true}}
+		p, deals, results := publish(api)
+		// deals are completed with the first message once it is pushed
+		for _, res := range results {
+			assert.NoError(t, res.err)
+			assert.Equal(t, []abi.ChainEpoch{1, 2, 3}, api.msgs[res.msgCid])
+		}
+
+		for _, i := range []int{0, 2} {
+			retryCid, ok := retried(p, deals[i], results[i].msgCid)
+			require.True(t, ok)
+			assert.Equal(t, []abi.ChainEpoch{1, 3}, api.msgs[retryCid])
+		}
+		_, ok := retried(p, deals[1], results[1].msgCid)
+		assert.False(t, ok)
+		require.Equal(t, [][]abi.ChainEpoch{{1, 2, 3}, {1, 3}}, api.pushed)
+		// the retry is forgotten once all retried deals look it up
+		assert.Empty(t, p.retries)
+	})
+
+	t.Run("message failed without invalid deals", func(t *testing.T) {
+		api := &fakePublishAPI{invalid: map[abi.ChainEpoch]bool{}, outOfGas: true}
+		p, deals, results := publish(api)
+		for i, res := range results {
+			assert.NoError(t, res.err)
+			_, ok := retried(p, deals[i], res.msgCid)
+			assert.False(t, ok)
+		}
+		assert.Len(t, api.pushed, 1)
+	})
+
+	t.Run("wait message failed", func(t *testing.T) {
+		api := &fakePublishAPI{invalid: map[abi.ChainEpoch]bool{}, invalidOnChain: map[abi.ChainEpoch]bool{2: true}, waitErr: fmt.Errorf("node offline")}
+		p, deals, results := publish(api)
+		for i, res := range results {
+			assert.NoError(t, res.err)
+			_, ok := retried(p, deals[i], res.msgCid)
+			assert.False(t, ok)
+		}
+		assert.Len(t, api.pushed, 1)
+	})
+}
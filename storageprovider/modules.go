@@ -197,6 +197,8 @@ var StorageProviderOpts = func(cfg *config.MarketConfig) builder.Option {
 		builder.Override(new(StorageProviderNode), NewProviderNodeAdapter(cfg)),
 		builder.Override(new(DealAssiger), NewDealAssigner),
 		builder.Override(StartDealTracker, NewDealTracker),
+		builder.Override(new(*PieceScrubber), NewPieceScrubber),
+		builder.Override(new(*PieceGC), NewPieceGC),
 	)
 }
 
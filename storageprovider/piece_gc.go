@@ -0,0 +1,276 @@
+package storageprovider
+
+import (
+	"context"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/filecoin-project/dagstore"
+	"github.com/filecoin-project/dagstore/shard"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus/pkg/constants"
+	"github.com/ipfs/go-cid"
+	"go.uber.org/fx"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	v1api "github.com/filecoin-project/venus/venus-shared/api/chain/v1"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+
+	"github.com/ipfs-force-community/venus-common-utils/metrics"
+)
+
+// PieceGC deletes pieces whose deals have all expired, been slashed or failed for longer than the grace period,
+// from piece storage, dagstore and the cid index
+type PieceGC struct {
+	interval        time.Duration
+	grace           time.Duration
+	storageRepo     repo.StorageDealRepo
+	cidInfoRepo     repo.ICidInfoRepo
+	pieceStorageMgr *piecestorage.PieceStorageManager
+	dagStore        *dagstore.DAGStore
+	fullNode        v1api.FullNode
+
+	lk      sync.Mutex
+	running bool
+}
+
+func NewPieceGC(mctx metrics.MetricsCtx, lc fx.Lifecycle, cfg *config.PieceStorage, r repo.Repo, pieceStorageMgr *piecestorage.PieceStorageManager, dagStore *dagstore.DAGStore, fullNode v1api.FullNode) *PieceGC {
+	gc := &PieceGC{
+		interval:        time.Duration(cfg.GCInterval),
+		grace:           time.Duration(cfg.GCGracePeriod),
+		storageRepo:     r.StorageDealRepo(),
+		cidInfoRepo:     r.CidInfoRepo(),
+		pieceStorageMgr: pieceStorageMgr,
+		dagStore:        dagStore,
+		fullNode:        fullNode,
+	}
+
+	if gc.interval > 0 {
+		ctx := metrics.LifecycleCtx(mctx, lc)
+		lc.Append(fx.Hook{
+			OnStart: func(context.Context) error {
+				go gc.loop(ctx)
+				return nil
+			},
+		})
+	}
+	return gc
+}
+
+func (gc *PieceGC) loop(ctx context.Context) {
+	ticker := time.NewTicker(gc.interval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			report, err := gc.Run(ctx, false)
+			if err != nil {
+				log.Warnf("skip scheduled piece gc: %v", err)
+				continue
+			}
+			log.Infof("piece gc finished, collected %d pieces", len(report.Pieces))
+		case <-ctx.Done():
+			log.Warnf("exit piece gc by context")
+			return
+		}
+	}
+}
+
+// Run collect pieces of ended deals, nothing is deleted if dryRun is true, the report lists the pieces
+// which are or would be deleted
+func (gc *PieceGC) Run(ctx context.Context, dryRun bool) (types2.PieceGCReport, error) {
+	gc.lk.Lock()
+	if gc.running {
+		gc.lk.Unlock()
+		return types2.PieceGCReport{}, fmt.Errorf("piece gc is already running")
+	}
+	gc.running = true
+	gc.lk.Unlock()
+	defer func() {
+		gc.lk.Lock()
+		gc.running = false
+		gc.lk.Unlock()
+	}()
+
+	report := types2.PieceGCReport{DryRun: dryRun, Start: time.Now()}
+	head, err := gc.fullNode.ChainHead(ctx)
+	if err != nil {
+		return report, fmt.Errorf("get chain head: %w", err)
+	}
+	deals, err := gc.storageRepo.ListDeal(ctx)
+	if err != nil {
+		return report, fmt.Errorf("list deals: %w", err)
+	}
+
+	grace := abi.ChainEpoch(gc.grace / (time.Duration(constants.MainNetBlockDelaySecs) * time.Second))
+	for _, piece := range collectablePieces(deals, head.Height(), grace) {
+		if ctx.Err() != nil {
+			return report, ctx.Err()
+		}
+		item := gc.planPiece(ctx, piece)
+		if len(item.Storages) == 0 && !item.Shard {
+			continue
+		}
+		report.Pieces = append(report.Pieces, item)
+	}
+	if !dryRun {
+		if err := gc.collect(ctx, report.Pieces); err != nil {
+			return report, err
+		}
+	}
+	report.End = time.Now()
+	return report, nil
+}
+
+// planPiece find out the writable storages and the dagstore shard holding the piece
+func (gc *PieceGC) planPiece(ctx context.Context, item types2.PieceGCItem) types2.PieceGCItem {
+	for _, st := range gc.pieceStorageMgr.FindStoragesForRead(ctx, item.PieceCID.String()) {
+		if !st.ReadOnly() {
+			item.Storages = append(item.Storages, st.GetName())
+		}
+	}
+	if gc.dagStore != nil {
+		_, err := gc.dagStore.GetShardInfo(shard.KeyFromCID(item.PieceCID))
+		item.Shard = err == nil
+	}
+	return item
+}
+
+// collect destroy the shards first so that nothing is served from the pieces while they are being deleted,
+// then remove the block locations of all pieces in one pass and delete them from storages. errors of a piece
+// are recorded in its item
+func (gc *PieceGC) collect(ctx context.Context, items []types2.PieceGCItem) error {
+	var destroyed []cid.Cid
+	for i := range items {
+		if ctx.Err() != nil {
+			return ctx.Err()
+		}
+		if err := gc.destroyShard(ctx, items[i]); err != nil {
+			gc.collectFailed(&items[i], err)
+			continue
+		}
+		destroyed = append(destroyed, items[i].PieceCID)
+	}
+
+	if err := gc.cidInfoRepo.RemovePieceBlockLocations(ctx, destroyed); err != nil {
+		for i := range items {
+			if len(items[i].Error) == 0 {
+				gc.collectFailed(&items[i], fmt.Errorf("remove block locations: %w", err))
+			}
+		}
+		return nil
+	}
+
+	for i := range items {
+		if ctx.Err() != nil {
+			return ctx.Err()
+		}
+		if len(items[i].Error) > 0 {
+			continue
+		}
+		if err := gc.deleteFromStorages(ctx, items[i]); err != nil {
+			gc.collectFailed(&items[i], err)
+		}
+	}
+	return nil
+}
+
+func (gc *PieceGC) collectFailed(item *types2.PieceGCItem, err error) {
+	log.Errorf("collect piece %s: %v", item.PieceCID, err)
+	item.Error = err.Error()
+}
+
+func (gc *PieceGC) destroyShard(ctx context.Context, item types2.PieceGCItem) error {
+	if !item.Shard {
+		return nil
+	}
+	resCh := make(chan dagstore.ShardResult, 1)
+	if err := gc.dagStore.DestroyShard(ctx, shard.KeyFromCID(item.PieceCID), resCh, dagstore.DestroyOpts{}); err != nil {
+		return fmt.Errorf("destroy shard: %w", err)
+	}
+	select {
+	case res := <-resCh:
+		if res.Error != nil {
+			return fmt.Errorf("destroy shard: %w", res.Error)
+		}
+	case <-ctx.Done():
+		return ctx.Err()
+	}
+	return nil
+}
+
+func (gc *PieceGC) deleteFromStorages(ctx context.Context, item types2.PieceGCItem) error {
+	for _, name := range item.Storages {
+		st, err := gc.pieceStorageMgr.GetPieceStorageByName(name)
+		if err != nil {
+			return err
+		}
+		if err := st.Delete(ctx, item.PieceCID.String()); err != nil {
+			return fmt.Errorf("delete from storage %s: %w", name, err)
+		}
+	}
+	return nil
+}
+
+// collectablePieces returns the pieces which have no active deals left, and whose deals have all been ended for
+// more than grace epochs. A failed deal is taken as ended at its start epoch, after which it can never be activated
+func collectablePieces(deals []*types.MinerDeal, head, grace abi.ChainEpoch) []types2.PieceGCItem {
+	type pieceDeals struct {
+		live    bool
+		lastEnd abi.ChainEpoch
+		ended   []cid.Cid
+	}
+
+	var order []cid.Cid
+	pieces := make(map[cid.Cid]*pieceDeals)
+	for _, deal := range deals {
+		pieceCid := deal.ClientDealProposal.Proposal.PieceCID
+		p, ok := pieces[pieceCid]
+		if !ok {
+			p = &pieceDeals{}
+			pieces[pieceCid] = p
+			order = append(order, pieceCid)
+		}
+
+		switch deal.State {
+		case storagemarket.StorageDealExpired, storagemarket.StorageDealSlashed,
+			storagemarket.StorageDealError, storagemarket.StorageDealFailing, storagemarket.StorageDealProposalRejected:
+			p.ended = append(p.ended, deal.ProposalCid)
+			if end := dealEndEpoch(deal); end > p.lastEnd {
+				p.lastEnd = end
+			}
+		default:
+			p.live = true
+		}
+	}
+
+	var out []types2.PieceGCItem
+	for _, pieceCid := range order {
+		p := pieces[pieceCid]
+		if p.live || len(p.ended) == 0 || head-p.lastEnd < grace {
+			continue
+		}
+		out = append(out, types2.PieceGCItem{PieceCID: pieceCid, Deals: p.ended})
+	}
+	return out
+}
+
+// dealEndEpoch returns the epoch a deal ended at, the end epoch of proposal is used for slashed deals
+// whose slash epoch wasn't recorded, and the start epoch for failed deals
+func dealEndEpoch(deal *types.MinerDeal) abi.ChainEpoch {
+	switch deal.State {
+	case storagemarket.StorageDealSlashed:
+		if deal.SlashEpoch > 0 {
+			return deal.SlashEpoch
+		}
+	case storagemarket.StorageDealError, storagemarket.StorageDealFailing, storagemarket.StorageDealProposalRejected:
+		return deal.ClientDealProposal.Proposal.StartEpoch
+	}
+	return deal.ClientDealProposal.Proposal.EndEpoch
+}
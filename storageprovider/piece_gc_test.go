@@ -0,0 +1,74 @@
+package storageprovider
+
+import (
+	"testing"
+
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/ipfs/go-cid"
+	"github.com/stretchr/testify/assert"
+
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+)
+
+func TestCollectablePieces(t *testing.T) {
+	pieces := shared_testutil.GenerateCids(7)
+	proposals := shared_testutil.GenerateCids(11)
+
+	deal := func(i int, piece cid.Cid, state storagemarket.StorageDealStatus, end abi.ChainEpoch) *types.MinerDeal {
+		d := &types.MinerDeal{ProposalCid: proposals[i], State: state}
+		d.ClientDealProposal.Proposal.PieceCID = piece
+		d.ClientDealProposal.Proposal.EndEpoch = end
+		return d
+	}
+	failed := func(i int, piece cid.Cid, state storagemarket.StorageDealStatus, start abi.ChainEpoch) *types.MinerDeal {
+		d := deal(i, piece, state, start+1000)
+		d.ClientDealProposal.Proposal.StartEpoch = start
+		return d
+	}
+	slashed := func(i int, piece cid.Cid, slashEpoch abi.ChainEpoch) *types.MinerDeal {
+		d := deal(i, piece, storagemarket.StorageDealSlashed, 2000)
+		d.SlashEpoch = slashEpoch
+		return d
+	}
+	deals := []*types.MinerDeal{
+		// expired long ago
+		deal(0, pieces[0], storagemarket.StorageDealExpired, 100),
+		// still referenced by an active deal
+		deal(1, pieces[1], storagemarket.StorageDealExpired, 100),
+		deal(2, pieces[1], storagemarket.StorageDealActive, 2000),
+		// within grace period
+		deal(3, pieces[2], storagemarket.StorageDealExpired, 100),
+		deal(4, pieces[2], storagemarket.StorageDealExpired, 950),
+		// slashed and failed deals don't keep the piece
+		slashed(5, pieces[3], 500),
+		failed(6, pieces[3], storagemarket.StorageDealError, 200),
+		// all deals failed, ended at start epoch
+		failed(7, pieces[4], storagemarket.StorageDealError, 1020),
+		failed(8, pieces[4], storagemarket.StorageDealProposalRejected, 900),
+		// slashed within grace period
+		slashed(9, pieces[5], 950),
+		// slash epoch unknown, wait for the end of proposal
+		slashed(10, pieces[6], 0),
+	}
+
+	items := collectablePieces(deals, 1000, 100)
+	assert.Len(t, items, 2)
+	assert.Equal(t, pieces[0], items[0].PieceCID)
+	assert.Equal(t, []cid.Cid{proposals[0]}, items[0].Deals)
+	assert.Equal(t, pieces[3], items[1].PieceCID)
+	assert.Equal(t, []cid.Cid{proposals[5], proposals[6]}, items[1].Deals)
+
+	items = collectablePieces(deals, 1050, 100)
+	assert.Len(t, items, 4)
+	assert.Equal(t, pieces[2], items[1].PieceCID)
+	assert.Equal(t, []cid.Cid{proposals[3], proposals[4]}, items[1].Deals)
+	assert.Equal(t, pieces[5], items[3].PieceCID)
+
+	items = collectablePieces(deals, 2100, 100)
+	assert.Len(t, items, 6)
+	assert.Equal(t, pieces[4], items[3].PieceCID)
+	assert.Equal(t, []cid.Cid{proposals[7], proposals[8]}, items[3].Deals)
+	assert.Equal(t, pieces[6], items[5].PieceCID)
+}
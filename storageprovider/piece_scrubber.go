@@ -0,0 +1,216 @@
+package storageprovider
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"sync"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/ipfs/go-cid"
+	"go.uber.org/fx"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/repo"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	"github.com/filecoin-project/venus-market/v2/utils"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+
+	"github.com/ipfs-force-community/venus-common-utils/metrics"
+)
+
+const pieceScrubReportFile = "piece_scrub.json"
+
+// PieceScrubber re-calculate CommP of stored pieces and compare them with the piece cid of deals,
+// to find out pieces that are corrupt or missing in piece storage
+type PieceScrubber struct {
+	interval        time.Duration
+	reportPath      string
+	storageRepo     repo.StorageDealRepo
+	pieceStorageMgr *piecestorage.PieceStorageManager
+
+	ctx    context.Context
+	lk     sync.Mutex
+	report types2.PieceScrubReport
+}
+
+func NewPieceScrubber(mctx metrics.MetricsCtx, lc fx.Lifecycle, home config.IHome, cfg *config.PieceStorage, r repo.Repo, pieceStorageMgr *piecestorage.PieceStorageManager) (*PieceScrubber, error) {
+	reportPath, err := home.HomeJoin(pieceScrubReportFile)
+	if err != nil {
+		return nil, err
+	}
+
+	scrubber := &PieceScrubber{
+		interval:        time.Duration(cfg.ScrubInterval),
+		reportPath:      reportPath,
+		storageRepo:     r.StorageDealRepo(),
+		pieceStorageMgr: pieceStorageMgr,
+		ctx:             metrics.LifecycleCtx(mctx, lc),
+	}
+	if err = scrubber.loadReport(); err != nil {
+		log.Warnf("load last piece scrub report: %v", err)
+	}
+
+	if scrubber.interval > 0 {
+		lc.Append(fx.Hook{
+			OnStart: func(context.Context) error {
+				go scrubber.loop()
+				return nil
+			},
+		})
+	}
+	return scrubber, nil
+}
+
+// Report return the result of the last scrub
+func (scrubber *PieceScrubber) Report() types2.PieceScrubReport {
+	scrubber.lk.Lock()
+	defer scrubber.lk.Unlock()
+	return scrubber.report
+}
+
+// Start begin a scrub in background, return error if there is one already running
+func (scrubber *PieceScrubber) Start() error {
+	scrubber.lk.Lock()
+	defer scrubber.lk.Unlock()
+	if scrubber.report.Running {
+		return fmt.Errorf("piece scrub is already running since %s", scrubber.report.Start)
+	}
+	scrubber.report = types2.PieceScrubReport{Running: true, Start: time.Now()}
+
+	go scrubber.scrub(scrubber.ctx)
+	return nil
+}
+
+func (scrubber *PieceScrubber) loop() {
+	ticker := time.NewTicker(scrubber.interval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			if err := scrubber.Start(); err != nil {
+				log.Warnf("skip scheduled piece scrub: %v", err)
+			}
+		case <-scrubber.ctx.Done():
+			log.Warnf("exit piece scrubber by context")
+			return
+		}
+	}
+}
+
+func (scrubber *PieceScrubber) scrub(ctx context.Context) {
+	report := types2.PieceScrubReport{Start: scrubber.Report().Start}
+	defer func() {
+		report.End = time.Now()
+		log.Infof("piece scrub finished, checked %d, corrupt %d, missing %d", report.Checked, len(report.Corrupt), len(report.Missing))
+
+		scrubber.lk.Lock()
+		scrubber.report = report
+		scrubber.lk.Unlock()
+		if err := scrubber.saveReport(report); err != nil {
+			log.Errorf("save piece scrub report: %v", err)
+		}
+	}()
+
+	deals, err := scrubber.storageRepo.GetDealByAddrAndStatus(ctx, address.Undef, ReadyRetrievalDealStatus...)
+	if err != nil {
+		if !errors.Is(err, repo.ErrNotFound) {
+			log.Errorf("list deals to scrub: %v", err)
+		}
+		return
+	}
+
+	checked := make(map[cid.Cid]struct{})
+	for _, deal := range deals {
+		if ctx.Err() != nil {
+			return
+		}
+		pieceCid := deal.ClientDealProposal.Proposal.PieceCID
+		if _, ok := checked[pieceCid]; ok {
+			continue
+		}
+		checked[pieceCid] = struct{}{}
+
+		report.Checked++
+		corrupt, missing := scrubber.scrubPiece(ctx, deal)
+		report.Corrupt = append(report.Corrupt, corrupt...)
+		if missing != nil {
+			report.Missing = append(report.Missing, *missing)
+		}
+	}
+}
+
+// scrubPiece check every copy of the piece of deal, return corrupt copies, or a result if no copy found
+func (scrubber *PieceScrubber) scrubPiece(ctx context.Context, deal *types.MinerDeal) ([]types2.PieceScrubResult, *types2.PieceScrubResult) {
+	pieceCid := deal.ClientDealProposal.Proposal.PieceCID
+	storages := scrubber.pieceStorageMgr.FindStoragesForRead(ctx, pieceCid.String())
+	if len(storages) == 0 {
+		log.Warnf("piece %s of deal %s is missing from piece storage", pieceCid, deal.ProposalCid)
+		return nil, &types2.PieceScrubResult{PieceCID: pieceCid, Error: "piece not found in any storage"}
+	}
+
+	var corrupt []types2.PieceScrubResult
+	for _, st := range storages {
+		actual, err := calcStoredCommP(ctx, st, pieceCid.String(), deal)
+		if err != nil {
+			log.Errorf("scrub piece %s in storage %s: %v", pieceCid, st.GetName(), err)
+			corrupt = append(corrupt, types2.PieceScrubResult{PieceCID: pieceCid, Storage: st.GetName(), Error: err.Error()})
+			continue
+		}
+		if !actual.Equals(pieceCid) {
+			log.Errorf("piece %s in storage %s is corrupt, got commP %s", pieceCid, st.GetName(), actual)
+			corrupt = append(corrupt, types2.PieceScrubResult{PieceCID: pieceCid, Storage: st.GetName(), Actual: actual, Error: "commP mismatch"})
+		}
+	}
+	return corrupt, nil
+}
+
+func calcStoredCommP(ctx context.Context, st piecestorage.IPieceStorage, resourceId string, deal *types.MinerDeal) (cid.Cid, error) {
+	r, err := st.GetReaderCloser(ctx, resourceId)
+	if err != nil {
+		return cid.Undef, err
+	}
+	defer r.Close() //nolint:errcheck
+
+	actual, written, err := utils.GenerateCommPFromReader(r, deal.ClientDealProposal.Proposal.PieceSize)
+	if err != nil {
+		return cid.Undef, err
+	}
+	if deal.PayloadSize != 0 && written != int64(deal.PayloadSize) {
+		return actual, fmt.Errorf("stored size %d not equal to payload size %d", written, deal.PayloadSize)
+	}
+	return actual, nil
+}
+
+func (scrubber *PieceScrubber) loadReport() error {
+	data, err := ioutil.ReadFile(scrubber.reportPath)
+	if err != nil {
+		if os.IsNotExist(err) {
+			return nil
+		}
+		return err
+	}
+
+	var report types2.PieceScrubReport
+	if err = json.Unmarshal(data, &report); err != nil {
+		return err
+	}
+	// the scrub was interrupted by restart
+	report.Running = false
+	scrubber.report = report
+	return nil
+}
+
+func (scrubber *PieceScrubber) saveReport(report types2.PieceScrubReport) error {
+	data, err := json.MarshalIndent(report, "", "  ")
+	if err != nil {
+		return err
+	}
+	return ioutil.WriteFile(scrubber.reportPath, data, 0644)
+}
@@ -0,0 +1,102 @@
+package storageprovider
+
+import (
+	"bytes"
+	"context"
+	"math/rand"
+	"path/filepath"
+	"testing"
+
+	"github.com/filecoin-project/go-fil-markets/shared_testutil"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/ipfs/go-cid"
+	"github.com/ipfs/go-datastore"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/filecoin-project/venus-market/v2/config"
+	"github.com/filecoin-project/venus-market/v2/models/badger"
+	"github.com/filecoin-project/venus-market/v2/piecestorage"
+	types2 "github.com/filecoin-project/venus-market/v2/types"
+	"github.com/filecoin-project/venus-market/v2/utils"
+	types "github.com/filecoin-project/venus/venus-shared/types/market"
+)
+
+func TestPieceScrubber(t *testing.T) {
+	ctx := context.Background()
+	pieceSize := abi.PaddedPieceSize(2048)
+
+	newPiece := func() ([]byte, cid.Cid) {
+		data := make([]byte, 1000)
+		rand.Read(data) //nolint:gosec
+		commP, _, err := utils.GenerateCommPFromReader(bytes.NewReader(data), pieceSize)
+		require.NoError(t, err)
+		return data, commP
+	}
+	healthyData, healthy := newPiece()
+	corruptData, corrupt := newPiece()
+	shortData, short := newPiece()
+	_, missing := newPiece()
+
+	pieceStorageMgr, err := piecestorage.NewPieceStorageManager(&config.PieceStorage{})
+	require.NoError(t, err)
+	good := piecestorage.NewMemPieceStore("good", nil)
+	bad := piecestorage.NewMemPieceStore("bad", nil)
+	pieceStorageMgr.AddMemPieceStorage(good)
+	pieceStorageMgr.AddMemPieceStorage(bad)
+
+	save := func(st *piecestorage.MemPieceStore, piece cid.Cid, data []byte) {
+		_, err := st.SaveTo(ctx, piece.String(), bytes.NewReader(data))
+		require.NoError(t, err)
+	}
+	save(good, healthy, healthyData)
+	// one good copy and one copy with flipped bytes
+	save(good, corrupt, corruptData)
+	flipped := append([]byte{}, corruptData...)
+	flipped[0] ^= 0xff
+	save(bad, corrupt, flipped)
+	// a copy losing its tail
+	save(good, short, shortData[:500])
+
+	storageRepo := badger.NewStorageDealRepo(badger.NewStorageDealsDS(datastore.NewMapDatastore()))
+	proposals := shared_testutil.GenerateCids(5)
+	for i, piece := range []cid.Cid{healthy, corrupt, short, missing, healthy} {
+		deal := &types.MinerDeal{ProposalCid: proposals[i], State: storagemarket.StorageDealActive, PayloadSize: 1000}
+		deal.ClientDealProposal.Proposal.PieceCID = piece
+		deal.ClientDealProposal.Proposal.PieceSize = pieceSize
+		require.NoError(t, storageRepo.SaveDeal(ctx, deal))
+	}
+
+	scrubber := &PieceScrubber{
+		reportPath:      filepath.Join(t.TempDir(), pieceScrubReportFile),
+		storageRepo:     storageRepo,
+		pieceStorageMgr: pieceStorageMgr,
+		ctx:             ctx,
+	}
+	scrubber.scrub(ctx)
+	report := scrubber.Report()
+
+	// deals sharing a piece are checked once
+	assert.Equal(t, 4, report.Checked)
+	assert.False(t, report.End.IsZero())
+
+	require.Len(t, report.Missing, 1)
+	assert.Equal(t, missing, report.Missing[0].PieceCID)
+
+	byPiece := make(map[cid.Cid]types2.PieceScrubResult)
+	for _, res := range report.Corrupt {
+		byPiece[res.PieceCID] = res
+	}
+	assert.Len(t, byPiece, 2)
+	assert.NotContains(t, byPiece, healthy)
+	assert.Equal(t, "bad", byPiece[corrupt].Storage)
+	assert.Equal(t, "commP mismatch", byPiece[corrupt].Error)
+	assert.Equal(t, "good", byPiece[short].Storage)
+	assert.Contains(t, byPiece[short].Error, "not equal to payload size")
+
+	// the report survives restart
+	reloaded := &PieceScrubber{reportPath: scrubber.reportPath}
+	require.NoError(t, reloaded.loadReport())
+	assert.Equal(t, report.Checked, reloaded.Report().Checked)
+}
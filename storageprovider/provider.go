@@ -269,6 +269,15 @@ func (n *ProviderNodeAdapter) WaitForPublishDeals(ctx context.Context, publishCi
 		return nil, fmt.Errorf("WaitForPublishDeals errored: %w", err)
 	}
 	if receipt.Receipt.ExitCode != exitcode.Ok {
+		// the deal may be published again without the invalid deals in a batch, the final cid is the retry
+		retryCid, retried, err := n.dealPublisher.RetriedPublish(ctx, publishCid, proposal)
+		if err != nil {
+			return nil, fmt.Errorf("WaitForPublishDeals getting retry of %s errored: %w", publishCid, err)
+		}
+		if retried {
+			log.Infof("publish message %s failed with exit code %s, wait for retry %s", publishCid, receipt.Receipt.ExitCode, retryCid)
+			return n.WaitForPublishDeals(ctx, retryCid, proposal)
+		}
 		return nil, fmt.Errorf("WaitForPublishDeals exit code: %s", receipt.Receipt.ExitCode)
 	}
 
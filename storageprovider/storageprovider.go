@@ -33,6 +33,7 @@ import (
 	"github.com/filecoin-project/venus-market/v2/minermgr"
 	"github.com/filecoin-project/venus-market/v2/models/repo"
 	"github.com/filecoin-project/venus-market/v2/network"
+	"github.com/filecoin-project/venus-market/v2/notify"
 	"github.com/filecoin-project/venus-market/v2/piecestorage"
 	"github.com/filecoin-project/venus-market/v2/utils"
 
@@ -114,12 +115,14 @@ func NewStorageProvider(
 	cfg *config.MarketConfig,
 	homeDir *config.HomeDir,
 	pieceStorageMgr *piecestorage.PieceStorageManager,
+	repairWorker *piecestorage.RepairWorker,
 	dataTransfer network.ProviderDataTransfer,
 	spn StorageProviderNode,
 	dagStore stores.DAGStoreWrapper,
 	repo repo.Repo,
 	minerMgr minermgr.IAddrMgr,
 	mixMsgClient clients.IMixMessage,
+	notifier *notify.WebhookNotifier,
 ) (StorageProvider, error) {
 	net := smnet.NewFromLibp2pHost(h)
 
@@ -152,7 +155,7 @@ func NewStorageProvider(
 		minerMgr: minerMgr,
 	}
 
-	dealProcess, err := NewStorageDealProcessImpl(spV2.conns, newPeerTagger(spV2.net), spV2.spn, spV2.dealStore, spV2.storedAsk, spV2.fs, minerMgr, repo, pieceStorageMgr, dataTransfer, dagStore)
+	dealProcess, err := NewStorageDealProcessImpl(spV2.conns, newPeerTagger(spV2.net), spV2.spn, spV2.dealStore, spV2.storedAsk, spV2.fs, minerMgr, repo, pieceStorageMgr, repairWorker, dataTransfer, dagStore, notifier)
 	if err != nil {
 		return nil, err
 	}
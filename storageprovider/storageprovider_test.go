@@ -87,7 +87,7 @@ func setup(t *testing.T) StorageProvider {
 	addrMgr := mockAddrMgr{}
 
 	//todo how to mock dagstore
-	provider, err := NewStorageProvider(ask, h, config.DefaultMarketConfig, &homeDir, psManager, dt, spn, nil, r, addrMgr, nil)
+	provider, err := NewStorageProvider(ask, h, config.DefaultMarketConfig, &homeDir, psManager, nil, dt, spn, nil, r, addrMgr, nil, nil)
 	if err != nil {
 		t.Error(err)
 	}
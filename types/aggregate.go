@@ -0,0 +1,35 @@
+package types
+
+import (
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus/venus-shared/types/market/client"
+	"github.com/ipfs/go-cid"
+)
+
+// AggregateFile is an imported file packed into an aggregated CAR
+type AggregateFile struct {
+	// ImportID is the import which the file comes from
+	ImportID client.ImportID
+	// Name is the name of the link to the file in the root directory
+	Name string
+	// FilePath is the local path of the original file
+	FilePath string
+	Root     cid.Cid
+	// Offset is the position in the CAR of the first block of the file, and Length is the bytes of the blocks
+	// of the file from Offset. Blocks shared with files before it are not repeated, so they may lie before Offset.
+	Offset uint64
+	Length uint64
+}
+
+// AggregateManifest describes a CAR packing many imports, the root of the CAR is a UnixFS directory linking
+// to the root of each file, so that each file can be retrieved by its root or path
+type AggregateManifest struct {
+	Root cid.Cid
+	// ImportID is the import holding the aggregated CAR
+	ImportID  client.ImportID
+	CARPath   string
+	CARSize   uint64
+	PieceCID  cid.Cid
+	PieceSize abi.UnpaddedPieceSize
+	Files     []AggregateFile
+}
@@ -0,0 +1,42 @@
+package types
+
+import (
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/ipfs/go-cid"
+)
+
+// StartDealResult is the outcome of a deal started in batch
+type StartDealResult struct {
+	// Index is the position of the deal in the batch
+	Index      int
+	Miner      address.Address
+	Wallet     address.Address
+	Root       cid.Cid
+	StartEpoch abi.ChainEpoch
+	EndEpoch   abi.ChainEpoch
+	// Escrow is the storage fee plus client collateral of the deal, locked in the market actor by the wallet
+	Escrow           abi.TokenAmount
+	ClientCollateral abi.TokenAmount
+	// ProviderCollateral is locked by the miner, it is decided when proposing if it is not set in params
+	ProviderCollateral abi.TokenAmount
+	ProposalCid        *cid.Cid
+	// AddFundsCid is the message topping up escrow for all the deals of the wallet, nil if balance is enough
+	AddFundsCid *cid.Cid
+	// Error is empty if the deal is proposed successfully
+	Error string
+}
+
+// DealRenewal records a deal proposed to replace an active deal approaching its end
+type DealRenewal struct {
+	// Original is the proposal of the deal renewed, Replacement is the proposal of the new deal
+	Original    cid.Cid
+	Replacement cid.Cid
+	Miner       address.Address
+	Root        cid.Cid
+	// EndEpoch is the end of the original deal
+	EndEpoch     abi.ChainEpoch
+	CreationTime time.Time
+}
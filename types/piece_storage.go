@@ -0,0 +1,76 @@
+package types
+
+import (
+	"time"
+
+	"github.com/ipfs/go-cid"
+)
+
+// PieceRepairStatus is the status of the worker which repairs under-replicated pieces
+type PieceRepairStatus struct {
+	Enabled bool
+	Running bool
+
+	LastStart time.Time
+	LastEnd   time.Time
+
+	// statistics of the last round
+	Scanned         int
+	UnderReplicated int
+	Repaired        int
+	Failed          int
+	LastError       string
+}
+
+// PieceScrubResult is a piece which failed the integrity check
+type PieceScrubResult struct {
+	PieceCID cid.Cid
+	// Storage which holds the bad copy, empty if the piece is missing from all storages
+	Storage string
+	// Actual is the CommP calculated from stored data, undefined if the data can not be read
+	Actual cid.Cid
+	Error  string
+}
+
+// PieceScrubReport is the result of the last scrub round over stored pieces
+type PieceScrubReport struct {
+	Running bool
+
+	Start time.Time
+	End   time.Time
+
+	Checked int
+	Corrupt []PieceScrubResult
+	Missing []PieceScrubResult
+}
+
+// PieceGCItem is a piece whose deals have all ended
+type PieceGCItem struct {
+	PieceCID cid.Cid
+	// Deals are the proposal cids of the ended or failed deals of the piece
+	Deals []cid.Cid
+	// Storages are the writable piece storages holding the piece
+	Storages []string
+	// Shard tells whether the piece has a shard in dagstore
+	Shard bool
+	Error string
+}
+
+// PieceGCReport is the result of a garbage collection of pieces, nothing is deleted in a dry run
+type PieceGCReport struct {
+	DryRun bool
+
+	Start time.Time
+	End   time.Time
+
+	Pieces []PieceGCItem
+}
+
+// ConfigReloadResult describe changes applied by reloading config file
+type ConfigReloadResult struct {
+	AddedPieceStorages   []string
+	RemovedPieceStorages []string
+	UpdatedPieceStorages []string
+	// RestartRequired are the changed settings which are not applied until restarting daemon
+	RestartRequired []string
+}
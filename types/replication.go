@@ -0,0 +1,51 @@
+package types
+
+import (
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus/venus-shared/types/market/client"
+)
+
+// ReplicationParams describes data to be stored with many distinct miners chosen by the replication planner
+type ReplicationParams struct {
+	// Deal is the template of the deals, Miner and EpochPrice are filled from the ask of each chosen miner
+	Deal client.StartDealParams
+	// Replicas is the number of distinct miners to store the data with
+	Replicas int
+	// Miners are the candidates, all miners with minimum power are queried if empty
+	Miners []address.Address
+	// MaxPrice is the highest ask price per GiB per epoch accepted, the verified price is compared for verified
+	// deals, no limit if nil
+	MaxPrice abi.TokenAmount
+	// MinReliability is the lowest ratio of past deals with a miner which didn't fail, miners which never finished
+	// a deal with the client are accepted
+	MinReliability float64
+}
+
+// ReplicaCandidate is a miner answering the ask query of the replication planner
+type ReplicaCandidate struct {
+	Miner address.Address
+	// Price is the ask price per GiB per epoch for the kind of the deal, EpochPrice is the price of the piece
+	Price        abi.TokenAmount
+	EpochPrice   abi.TokenAmount
+	MinPieceSize abi.PaddedPieceSize
+	MaxPieceSize abi.PaddedPieceSize
+	// FinishedDeals is the number of past deals with the miner which are active, expired or failed,
+	// Reliability is the ratio of them not failed, 1 if there is none
+	FinishedDeals int
+	Reliability   float64
+	// Rejected is the reason why the miner is filtered out, empty if accepted
+	Rejected string
+}
+
+// ReplicationResult is the outcome of replicating data to many miners
+type ReplicationResult struct {
+	// Candidates are the accepted miners in the order they are tried
+	Candidates []ReplicaCandidate
+	// Rejected are the miners filtered out
+	Rejected []ReplicaCandidate
+	// Deals are the deals tried in order, failed ones included
+	Deals []StartDealResult
+	// Replicas is the number of deals proposed successfully
+	Replicas int
+}
@@ -0,0 +1,182 @@
+package types
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/filecoin-project/go-address"
+	"github.com/filecoin-project/go-fil-markets/storagemarket"
+	"github.com/filecoin-project/go-state-types/abi"
+	"github.com/filecoin-project/venus/venus-shared/types/market"
+	"github.com/ipfs/go-cid"
+)
+
+// PieceFailed is the piece status of deals which fail before being sealed, such deals are not assigned to sectors
+const PieceFailed market.PieceStatus = "Failed"
+
+// DealSortField is the field storage deals are ordered by, ties are broken by proposal cid
+type DealSortField string
+
+const (
+	DealSortByCreationTime DealSortField = "creation_time"
+	DealSortByDealID       DealSortField = "deal_id"
+	DealSortByPieceSize    DealSortField = "piece_size"
+)
+
+// DefaultDealQueryLimit is the page size used when DealQueryParams.Limit is not positive
+const DefaultDealQueryLimit = 100
+
+// DealQueryParams describes the filters, order and page of a storage deal query.
+// A filter with zero value is not applied.
+type DealQueryParams struct {
+	Miner address.Address
+	// Client is the wallet address of the deal client
+	Client        address.Address
+	States        []storagemarket.StorageDealStatus
+	PieceStatuses []market.PieceStatus
+	Verified      *bool
+	// CreatedAfter is inclusive and CreatedBefore is exclusive
+	CreatedAfter  time.Time
+	CreatedBefore time.Time
+	PieceCID      *cid.Cid
+	PayloadCID    *cid.Cid
+	DealID        *abi.DealID
+
+	// SortBy defaults to DealSortByCreationTime
+	SortBy DealSortField
+	Desc   bool
+	// Cursor is the NextCursor of the previous page, empty for the first page
+	Cursor string
+	Limit  int
+}
+
+// DealQueryResult is a page of storage deals
+type DealQueryResult struct {
+	Deals []*market.MinerDeal
+	// NextCursor is empty if there are no more deals
+	NextCursor string
+}
+
+// SortField return the field to sort by, and an error if it is not supported
+func (p *DealQueryParams) SortField() (DealSortField, error) {
+	switch p.SortBy {
+	case "":
+		return DealSortByCreationTime, nil
+	case DealSortByCreationTime, DealSortByDealID, DealSortByPieceSize:
+		return p.SortBy, nil
+	default:
+		return "", fmt.Errorf("unsupported sort field %s", p.SortBy)
+	}
+}
+
+// PageSize return the max number of deals in a page
+func (p *DealQueryParams) PageSize() int {
+	if p.Limit <= 0 {
+		return DefaultDealQueryLimit
+	}
+	return p.Limit
+}
+
+// Match return whether the deal passes the filters of params
+func (p *DealQueryParams) Match(deal *market.MinerDeal) bool {
+	if p.Miner != address.Undef && deal.Proposal.Provider != p.Miner {
+		return false
+	}
+	if p.Client != address.Undef && deal.Proposal.Client != p.Client {
+		return false
+	}
+	if len(p.States) > 0 {
+		var found bool
+		for _, state := range p.States {
+			if deal.State == state {
+				found = true
+				break
+			}
+		}
+		if !found {
+			return false
+		}
+	}
+	if len(p.PieceStatuses) > 0 {
+		var found bool
+		for _, status := range p.PieceStatuses {
+			if deal.PieceStatus == status {
+				found = true
+				break
+			}
+		}
+		if !found {
+			return false
+		}
+	}
+	if p.Verified != nil && deal.Proposal.VerifiedDeal != *p.Verified {
+		return false
+	}
+	createdAt := deal.CreationTime.Time()
+	if !p.CreatedAfter.IsZero() && createdAt.Before(p.CreatedAfter) {
+		return false
+	}
+	if !p.CreatedBefore.IsZero() && !createdAt.Before(p.CreatedBefore) {
+		return false
+	}
+	if p.PieceCID != nil && !deal.Proposal.PieceCID.Equals(*p.PieceCID) {
+		return false
+	}
+	if p.PayloadCID != nil && (deal.Ref == nil || !deal.Ref.Root.Equals(*p.PayloadCID)) {
+		return false
+	}
+	if p.DealID != nil && deal.DealID != *p.DealID {
+		return false
+	}
+	return true
+}
+
+// DealSortValue return the value of field of a deal used for ordering and cursor
+func DealSortValue(field DealSortField, deal *market.MinerDeal) int64 {
+	switch field {
+	case DealSortByDealID:
+		return int64(deal.DealID)
+	case DealSortByPieceSize:
+		return int64(deal.Proposal.PieceSize)
+	default:
+		return deal.CreationTime.Time().UnixNano()
+	}
+}
+
+// EncodeDealCursor build a cursor points to the position after the deal
+func EncodeDealCursor(field DealSortField, deal *market.MinerDeal) string {
+	return fmt.Sprintf("%d/%s", DealSortValue(field, deal), deal.ProposalCid)
+}
+
+// DecodeDealCursor parse a cursor into the sort value and proposal cid of the last deal of previous page
+func DecodeDealCursor(cursor string) (int64, cid.Cid, error) {
+	parts := strings.SplitN(cursor, "/", 2)
+	if len(parts) != 2 {
+		return 0, cid.Undef, fmt.Errorf("invalid cursor %s", cursor)
+	}
+	value, err := strconv.ParseInt(parts[0], 10, 64)
+	if err != nil {
+		return 0, cid.Undef, fmt.Errorf("invalid cursor %s: %w", cursor, err)
+	}
+	proposalCid, err := cid.Decode(parts[1])
+	if err != nil {
+		return 0, cid.Undef, fmt.Errorf("invalid cursor %s: %w", cursor, err)
+	}
+	return value, proposalCid, nil
+}
+
+// NewDealQueryResult build a page from sorted deals, which may contain one more deal than the page size
+// to tell whether there is a next page
+func NewDealQueryResult(params *DealQueryParams, field DealSortField, deals []*market.MinerDeal) *DealQueryResult {
+	res := &DealQueryResult{Deals: deals}
+	if size := params.PageSize(); len(deals) > size {
+		res.Deals = deals[:size]
+		res.NextCursor = EncodeDealCursor(field, res.Deals[size-1])
+	}
+	if res.Deals == nil {
+		res.Deals = []*market.MinerDeal{}
+	}
+	return res
+}
@@ -1,9 +1,13 @@
 package utils
 
 import (
+	"fmt"
 	"io"
 
 	"github.com/filecoin-project/go-commp-utils/ffiwrapper"
+	"github.com/filecoin-project/go-commp-utils/writer"
+	commcid "github.com/filecoin-project/go-fil-commcid"
+	commp "github.com/filecoin-project/go-fil-commp-hashhash"
 	"github.com/filecoin-project/go-padreader"
 	"github.com/filecoin-project/go-state-types/abi"
 	"github.com/ipfs/go-cid"
@@ -17,3 +21,43 @@ func GeneratePieceCommitment(rt abi.RegisteredSealProof, rd io.Reader, pieceSize
 	}
 	return commitment, nil
 }
+
+// GenerateCommPFromReader dump the CARv1 payload to the CommP writer and pad the CommP up to deal size,
+// return the CommP and the number of bytes read
+func GenerateCommPFromReader(rd io.Reader, dealSize abi.PaddedPieceSize) (cid.Cid, int64, error) {
+	w := &writer.Writer{}
+	written, err := io.Copy(w, rd)
+	if err != nil {
+		return cid.Undef, written, fmt.Errorf("failed to write to CommP writer: %w", err)
+	}
+
+	commP, err := PaddedCommP(w, dealSize)
+	if err != nil {
+		return cid.Undef, written, err
+	}
+	return commP, written, nil
+}
+
+// PaddedCommP sum the data written to the CommP writer and pad the CommP up to deal size
+func PaddedCommP(w *writer.Writer, dealSize abi.PaddedPieceSize) (cid.Cid, error) {
+	cidAndSize, err := w.Sum()
+	if err != nil {
+		return cid.Undef, fmt.Errorf("failed to get CommP: %w", err)
+	}
+
+	if cidAndSize.PieceSize < dealSize {
+		// need to pad up!
+		rawPaddedCommp, err := commp.PadCommP(
+			// we know how long a pieceCid "hash" is, just blindly extract the trailing 32 bytes
+			cidAndSize.PieceCID.Hash()[len(cidAndSize.PieceCID.Hash())-32:],
+			uint64(cidAndSize.PieceSize),
+			uint64(dealSize),
+		)
+		if err != nil {
+			return cid.Undef, err
+		}
+		cidAndSize.PieceCID, _ = commcid.DataCommitmentV1ToCID(rawPaddedCommp)
+	}
+
+	return cidAndSize.PieceCID, nil
+}